@@ -0,0 +1,372 @@
+//! Offline proxy, VPN and Tor detection from address lists.
+//!
+//! An [`Anonymizers`] loads the Tor Project's exit node list and any number of VPN or proxy
+//! range files, and flags lookups whose address is on one of them (see
+//! [`Client::anonymizers`](crate::Client::anonymizers)). The files are re-read from disk
+//! with [`Anonymizers::reload`] or by polling them with [`Anonymizers::watch`].
+//!
+//! Range files are plain text with one address or CIDR range per line (`#` starts a comment),
+//! or CSV (`.csv`) with the range in the first column. The Tor list can be either the bulk exit
+//! list (one address per line) or the `exit-addresses` format.
+
+use crate::offline::{modified, Watcher};
+use crate::threat::{Flag, Security, Source};
+use crate::GeoError;
+use ipnet::IpNet;
+use std::fs;
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime};
+use tracing::debug;
+
+/// What the addresses on a list are.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ListKind {
+    /// Tor exit nodes.
+    Tor,
+    Vpn,
+    Proxy,
+}
+
+/// Tor exit and VPN/proxy range lists, loaded from files.
+#[derive(Debug, Clone, Default)]
+pub struct Anonymizers {
+    current: Arc<RwLock<Arc<Vec<List>>>>,
+}
+
+#[derive(Debug, Clone)]
+struct List {
+    name: String,
+    kind: ListKind,
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    /// Sorted, non-overlapping ranges.
+    ranges: Vec<IpNet>,
+}
+
+impl Anonymizers {
+    /// Creates an empty set of lists.
+    pub fn new() -> Self {
+        Anonymizers::default()
+    }
+
+    /// Loads the Tor exit node list at `path`.
+    pub fn tor_exits<P: AsRef<Path>>(self, path: P) -> std::result::Result<Self, GeoError> {
+        self.add(path.as_ref(), ListKind::Tor)
+    }
+
+    /// Loads a list of VPN ranges at `path`.
+    pub fn vpn_ranges<P: AsRef<Path>>(self, path: P) -> std::result::Result<Self, GeoError> {
+        self.add(path.as_ref(), ListKind::Vpn)
+    }
+
+    /// Loads a list of proxy ranges at `path`.
+    pub fn proxy_ranges<P: AsRef<Path>>(self, path: P) -> std::result::Result<Self, GeoError> {
+        self.add(path.as_ref(), ListKind::Proxy)
+    }
+
+    /// Re-reads every list from disk. If any of them fails to load, none are replaced.
+    pub fn reload(&self) -> std::result::Result<(), GeoError> {
+        let lists = self.snapshot();
+
+        let mut reloaded = Vec::with_capacity(lists.len());
+        for list in lists.iter() {
+            reloaded.push(List::open(&list.path, list.kind)?);
+        }
+
+        debug!("reloaded {} anonymizer lists", reloaded.len());
+
+        *self.current.write().unwrap() = Arc::new(reloaded);
+
+        Ok(())
+    }
+
+    /// Reloads the lists if any of their files has been modified since they were loaded.
+    ///
+    /// Returns whether they were reloaded.
+    pub fn reload_if_changed(&self) -> std::result::Result<bool, GeoError> {
+        let changed = self
+            .snapshot()
+            .iter()
+            .any(|list| modified(&list.path) != list.modified);
+
+        if !changed {
+            return Ok(false);
+        }
+
+        self.reload()?;
+        Ok(true)
+    }
+
+    /// Polls the list files every `interval` and reloads them when they change.
+    ///
+    /// Polling stops when the returned [`Watcher`] is dropped.
+    pub fn watch(&self, interval: Duration) -> Watcher {
+        let anonymizers = self.clone();
+        Watcher::spawn(interval, "anonymizer lists", move || {
+            anonymizers.reload_if_changed()
+        })
+    }
+
+    /// Flags `ip` as a Tor exit, VPN or proxy for each list it's on, with the list's file
+    /// name as the source. Flags for lists it isn't on stay unknown.
+    pub fn classify(&self, ip: IpAddr) -> Security {
+        let mut security = Security::default();
+
+        for list in self.snapshot().iter().filter(|list| list.contains(ip)) {
+            let signal = match list.kind {
+                ListKind::Tor => &mut security.tor,
+                ListKind::Vpn => &mut security.vpn,
+                ListKind::Proxy => &mut security.proxy,
+            };
+
+            signal.set(Flag::Yes, Source::List(list.name.clone()));
+        }
+
+        security
+    }
+
+    fn add(self, path: &Path, kind: ListKind) -> std::result::Result<Self, GeoError> {
+        let list = List::open(path, kind)?;
+
+        let mut lists = self.snapshot().as_ref().clone();
+        lists.push(list);
+
+        *self.current.write().unwrap() = Arc::new(lists);
+
+        Ok(self)
+    }
+
+    fn snapshot(&self) -> Arc<Vec<List>> {
+        self.current.read().unwrap().clone()
+    }
+}
+
+impl List {
+    fn open(path: &Path, kind: ListKind) -> std::result::Result<Self, GeoError> {
+        let modified = modified(path);
+
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(error) => {
+                return Err(GeoError::ParseError(format!(
+                    "Couldn't read {}: {}",
+                    path.display(),
+                    error
+                )))
+            }
+        };
+
+        let csv = path.extension().and_then(|extension| extension.to_str()) == Some("csv");
+
+        let mut ranges = Vec::new();
+        for (number, line) in contents.lines().enumerate() {
+            let entry = line.split('#').next().unwrap_or("").trim();
+
+            let entry = if csv {
+                entry
+                    .split(',')
+                    .next()
+                    .unwrap_or("")
+                    .trim()
+                    .trim_matches('"')
+            } else if let Some(rest) = entry.strip_prefix("ExitAddress ") {
+                rest.split_whitespace().next().unwrap_or("")
+            } else if kind == ListKind::Tor && entry.contains(' ') {
+                // The other lines of the `exit-addresses` format
+                continue;
+            } else {
+                entry
+            };
+
+            if entry.is_empty() {
+                continue;
+            }
+
+            match parse_range(entry) {
+                Some(range) => ranges.push(range),
+                // A CSV header
+                None if csv && number == 0 => (),
+                None => {
+                    return Err(GeoError::ParseError(format!(
+                        "{} line {}: {} isn't an address or CIDR range",
+                        path.display(),
+                        number + 1,
+                        entry
+                    )))
+                }
+            }
+        }
+
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| path.display().to_string());
+
+        debug!("loaded {} ranges from {}", ranges.len(), name);
+
+        Ok(List {
+            name,
+            kind,
+            path: path.to_path_buf(),
+            modified,
+            ranges: IpNet::aggregate(&ranges),
+        })
+    }
+
+    fn contains(&self, ip: IpAddr) -> bool {
+        // The ranges are sorted and don't overlap, so only the last one starting at or
+        // before `ip` can contain it.
+        let index = self.ranges.partition_point(|range| range.network() <= ip);
+
+        index > 0 && self.ranges[index - 1].contains(&ip)
+    }
+}
+
+pub(crate) fn parse_range(entry: &str) -> Option<IpNet> {
+    match entry.parse::<IpNet>() {
+        Ok(range) => Some(range.trunc()),
+        Err(_) => entry.parse::<IpAddr>().ok().map(IpNet::from),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::File;
+    use std::thread;
+
+    fn directory(test: &str) -> PathBuf {
+        let directory = std::env::temp_dir().join(format!(
+            "ipgeolocate-anonymizers-{}-{}",
+            std::process::id(),
+            test
+        ));
+        let _ = fs::remove_dir_all(&directory);
+        fs::create_dir_all(&directory).unwrap();
+        directory
+    }
+
+    fn ip(ip: &str) -> IpAddr {
+        ip.parse().unwrap()
+    }
+
+    #[test]
+    fn reads_tor_exit_formats() {
+        let directory = directory("tor");
+        let bulk = directory.join("bulk.txt");
+        fs::write(&bulk, "192.0.2.1\n192.0.2.2\n").unwrap();
+        let exits = directory.join("exit-addresses");
+        fs::write(
+            &exits,
+            "ExitNode 0011BD2485AD45D984EC4159C88FC066E5E3300E\n\
+             Published 2024-01-01 00:00:00\n\
+             LastStatus 2024-01-01 01:00:00\n\
+             ExitAddress 198.51.100.9 2024-01-01 01:02:03\n",
+        )
+        .unwrap();
+
+        let anonymizers = Anonymizers::new()
+            .tor_exits(&bulk)
+            .unwrap()
+            .tor_exits(&exits)
+            .unwrap();
+
+        let security = anonymizers.classify(ip("192.0.2.2"));
+        assert_eq!(security.tor.flag, Flag::Yes);
+        assert_eq!(
+            security.tor.source,
+            Some(Source::List("bulk.txt".to_string()))
+        );
+
+        let security = anonymizers.classify(ip("198.51.100.9"));
+        assert_eq!(
+            security.tor.source,
+            Some(Source::List("exit-addresses".to_string()))
+        );
+
+        assert_eq!(
+            anonymizers.classify(ip("192.0.2.3")).tor.flag,
+            Flag::Unknown
+        );
+    }
+
+    #[test]
+    fn reads_range_lists() {
+        let directory = directory("ranges");
+        let vpn = directory.join("vpn.txt");
+        fs::write(
+            &vpn,
+            "# VPN providers\n10.8.0.0/16 # office\n\n2001:db8:1::/48\n203.0.113.7\n",
+        )
+        .unwrap();
+        let proxies = directory.join("proxies.csv");
+        fs::write(&proxies, "network,provider\n\"192.0.2.0/24\",Example\n").unwrap();
+
+        let anonymizers = Anonymizers::new()
+            .vpn_ranges(&vpn)
+            .unwrap()
+            .proxy_ranges(&proxies)
+            .unwrap();
+
+        assert_eq!(anonymizers.classify(ip("10.8.200.1")).vpn.flag, Flag::Yes);
+        assert_eq!(
+            anonymizers.classify(ip("2001:db8:1::5")).vpn.flag,
+            Flag::Yes
+        );
+        assert_eq!(anonymizers.classify(ip("203.0.113.7")).vpn.flag, Flag::Yes);
+        assert_eq!(
+            anonymizers.classify(ip("203.0.113.8")).vpn.flag,
+            Flag::Unknown
+        );
+        assert_eq!(anonymizers.classify(ip("10.9.0.1")).vpn.flag, Flag::Unknown);
+
+        let security = anonymizers.classify(ip("192.0.2.200"));
+        assert_eq!(security.proxy.flag, Flag::Yes);
+        assert_eq!(security.vpn.flag, Flag::Unknown);
+    }
+
+    #[test]
+    fn reports_bad_lines() {
+        let directory = directory("bad");
+        let vpn = directory.join("vpn.txt");
+        fs::write(&vpn, "10.8.0.0/16\nnot-a-range\n").unwrap();
+
+        match Anonymizers::new().vpn_ranges(&vpn) {
+            Err(GeoError::ParseError(error)) => assert!(error.contains("line 2")),
+            other => panic!("expected a parse error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn watches_for_changes() {
+        let directory = directory("watch");
+        let vpn = directory.join("vpn.txt");
+        fs::write(&vpn, "10.8.0.0/16\n").unwrap();
+
+        let anonymizers = Anonymizers::new().vpn_ranges(&vpn).unwrap();
+        assert!(!anonymizers.reload_if_changed().unwrap());
+
+        let _watcher = anonymizers.watch(Duration::from_millis(10));
+
+        fs::write(&vpn, "10.9.0.0/16\n").unwrap();
+        let later = SystemTime::now() + Duration::from_secs(5);
+        File::options()
+            .write(true)
+            .open(&vpn)
+            .unwrap()
+            .set_modified(later)
+            .unwrap();
+
+        for _ in 0..200 {
+            if anonymizers.classify(ip("10.9.0.1")).vpn.flag == Flag::Yes {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        assert_eq!(anonymizers.classify(ip("10.9.0.1")).vpn.flag, Flag::Yes);
+        assert_eq!(anonymizers.classify(ip("10.8.0.1")).vpn.flag, Flag::Unknown);
+    }
+}
@@ -0,0 +1,363 @@
+//! What each service can do.
+//!
+//! [`Service::capabilities`] describes a service:
+//!
+//! - the [`Field`]s it fills in on the free plan ([`fields`](Capabilities::fields)),
+//! - those it only fills in with a key ([`paid_fields`](Capabilities::paid_fields)),
+//! - those it only fills in when they're selected
+//!   ([`selected_fields`](Capabilities::selected_fields)),
+//! - and whether it looks up IPv6 addresses, is served over HTTPS and takes batches, its
+//!   documented free quota and whether it needs a key.
+//!
+//! [`best`] and [`Client::for_fields`](crate::Client::for_fields) pick a service that supplies
+//! the fields you need.
+
+use crate::Service;
+use std::fmt;
+
+/// Information a service can put on a [`Locator`](crate::Locator).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Field {
+    Latitude,
+    Longitude,
+    City,
+    Region,
+    /// ISO 3166-2 region code, sent by the service rather than looked up (see
+    /// [`iso`](crate::iso)).
+    ///
+    /// Services that don't send one still get a code for regions in Australia, Brazil,
+    /// Canada, Germany, Mexico and the US, whose subdivisions are bundled (see
+    /// [`iso::SUBDIVISIONS`](crate::iso::SUBDIVISIONS)), but they aren't counted as supplying
+    /// it since it's empty everywhere else.
+    RegionCode,
+    Country,
+    CountryCode,
+    Timezone,
+    Isp,
+    Postal,
+    MetroCode,
+    /// The autonomous system or organisation owning the address.
+    Network,
+    /// Proxy, VPN, Tor or hosting flags.
+    Security,
+}
+
+impl fmt::Display for Field {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Field::Latitude => write!(f, "latitude"),
+            Field::Longitude => write!(f, "longitude"),
+            Field::City => write!(f, "city"),
+            Field::Region => write!(f, "region"),
+            Field::RegionCode => write!(f, "region code"),
+            Field::Country => write!(f, "country"),
+            Field::CountryCode => write!(f, "country code"),
+            Field::Timezone => write!(f, "timezone"),
+            Field::Isp => write!(f, "isp"),
+            Field::Postal => write!(f, "postal code"),
+            Field::MetroCode => write!(f, "metro code"),
+            Field::Network => write!(f, "network"),
+            Field::Security => write!(f, "security flags"),
+        }
+    }
+}
+
+/// Whether a service is served over HTTPS.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Https {
+    Always,
+    /// Only the paid endpoint is; the free one is plain HTTP.
+    PaidOnly,
+}
+
+/// Whether a service needs an API key (see [`Client::api_key`](crate::Client::api_key)).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Auth {
+    /// Keys are ignored.
+    None,
+    /// Works without a key; a key unlocks a paid plan.
+    Optional,
+    Required,
+}
+
+/// The period a [`Quota`] is counted over.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Period {
+    Minute,
+    Hour,
+    Day,
+    Month,
+}
+
+impl fmt::Display for Period {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Period::Minute => write!(f, "minute"),
+            Period::Hour => write!(f, "hour"),
+            Period::Day => write!(f, "day"),
+            Period::Month => write!(f, "month"),
+        }
+    }
+}
+
+/// How many free lookups a service allows, as documented by the service.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Quota {
+    pub requests: u32,
+    pub period: Period,
+}
+
+impl fmt::Display for Quota {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} requests per {}", self.requests, self.period)
+    }
+}
+
+/// What a service can do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Capabilities {
+    pub service: Service,
+    /// The fields the service fills in on the free plan.
+    pub fields: &'static [Field],
+    /// The fields it fills in only with a key.
+    pub paid_fields: &'static [Field],
+    /// The fields it fills in only when they're selected, like ip-api.com's security flags
+    /// (see [`Client::ipapi_fields`](crate::Client::ipapi_fields)).
+    pub selected_fields: &'static [Field],
+    /// Whether it looks up IPv6 addresses.
+    pub ipv6: bool,
+    pub https: Https,
+    /// Whether [`Client::get_batch`](crate::Client::get_batch) sends a batch in one request.
+    pub batch: bool,
+    /// The free quota.
+    pub quota: Quota,
+    pub auth: Auth,
+}
+
+impl Capabilities {
+    /// Whether the service fills in `field`, on the free plan or, if `paid`, with a key.
+    ///
+    /// Fields it only sends when selected don't count (see [`selected_fields`](Self::selected_fields)).
+    pub fn supplies(&self, field: Field, paid: bool) -> bool {
+        self.fields.contains(&field) || (paid && self.paid_fields.contains(&field))
+    }
+}
+
+/// The services, most preferred first: ip-api.com has the largest quota, freegeoip.app is
+/// the least reliable.
+pub const SERVICES: &[Capabilities] = &[
+    Capabilities {
+        service: Service::IpApi,
+        fields: &[
+            Field::Latitude,
+            Field::Longitude,
+            Field::City,
+            Field::Region,
+            Field::RegionCode,
+            Field::Country,
+            Field::CountryCode,
+            Field::Timezone,
+            Field::Isp,
+            Field::Postal,
+            Field::Network,
+        ],
+        paid_fields: &[],
+        selected_fields: &[Field::Security],
+        ipv6: true,
+        https: Https::PaidOnly,
+        batch: false,
+        quota: Quota {
+            requests: 45,
+            period: Period::Minute,
+        },
+        auth: Auth::Optional,
+    },
+    Capabilities {
+        service: Service::IpInfo,
+        fields: &[
+            Field::Latitude,
+            Field::Longitude,
+            Field::City,
+            Field::Region,
+            Field::Country,
+            Field::CountryCode,
+            Field::Timezone,
+            Field::Isp,
+            Field::Postal,
+            Field::Network,
+        ],
+        paid_fields: &[Field::Security],
+        selected_fields: &[],
+        ipv6: true,
+        https: Https::Always,
+        batch: true,
+        quota: Quota {
+            requests: 50_000,
+            period: Period::Month,
+        },
+        auth: Auth::Optional,
+    },
+    Capabilities {
+        service: Service::IpApiCo,
+        fields: &[
+            Field::Latitude,
+            Field::Longitude,
+            Field::City,
+            Field::Region,
+            Field::RegionCode,
+            Field::Country,
+            Field::CountryCode,
+            Field::Timezone,
+            Field::Postal,
+            Field::Network,
+        ],
+        paid_fields: &[Field::Security],
+        selected_fields: &[],
+        ipv6: true,
+        https: Https::Always,
+        batch: false,
+        quota: Quota {
+            requests: 1_000,
+            period: Period::Day,
+        },
+        auth: Auth::Optional,
+    },
+    Capabilities {
+        service: Service::IpWhois,
+        fields: &[
+            Field::Latitude,
+            Field::Longitude,
+            Field::City,
+            Field::Region,
+            Field::Country,
+            Field::CountryCode,
+            Field::Timezone,
+            Field::Isp,
+            Field::Network,
+        ],
+        paid_fields: &[Field::Postal, Field::Security],
+        selected_fields: &[],
+        ipv6: true,
+        https: Https::PaidOnly,
+        batch: false,
+        quota: Quota {
+            requests: 10_000,
+            period: Period::Month,
+        },
+        auth: Auth::Optional,
+    },
+    Capabilities {
+        service: Service::FreeGeoIp,
+        fields: &[
+            Field::Latitude,
+            Field::Longitude,
+            Field::City,
+            Field::Region,
+            Field::RegionCode,
+            Field::Country,
+            Field::CountryCode,
+            Field::Timezone,
+            Field::Postal,
+            Field::MetroCode,
+        ],
+        paid_fields: &[],
+        selected_fields: &[],
+        ipv6: true,
+        https: Https::Always,
+        batch: false,
+        quota: Quota {
+            requests: 15_000,
+            period: Period::Hour,
+        },
+        auth: Auth::None,
+    },
+];
+
+/// The capabilities of `service`.
+pub fn of(service: Service) -> &'static Capabilities {
+    SERVICES
+        .iter()
+        .find(|capabilities| capabilities.service == service)
+        .expect("every service is in SERVICES")
+}
+
+/// The most preferred service (see [`SERVICES`]) that supplies every one of `fields`, on
+/// its free plan or, if `paid`, with a key. With `https_only`, only services served over
+/// HTTPS on that plan are considered. `None` if no service can.
+///
+/// Fields a service only sends when selected count, so build the client with
+/// [`Client::for_fields`](crate::Client::for_fields) to have them selected.
+pub fn best(fields: &[Field], paid: bool, https_only: bool) -> Option<Service> {
+    SERVICES
+        .iter()
+        .filter(|capabilities| paid || capabilities.auth != Auth::Required)
+        .filter(|capabilities| !https_only || paid || capabilities.https == Https::Always)
+        .find(|capabilities| {
+            fields.iter().all(|&field| {
+                capabilities.supplies(field, paid) || capabilities.selected_fields.contains(&field)
+            })
+        })
+        .map(|capabilities| capabilities.service)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn describes_every_service() {
+        for service in [
+            Service::IpApi,
+            Service::IpInfo,
+            Service::IpApiCo,
+            Service::IpWhois,
+            Service::FreeGeoIp,
+        ] {
+            assert_eq!(of(service).service, service);
+        }
+        assert_eq!(
+            of(Service::IpInfo).quota.to_string(),
+            "50000 requests per month"
+        );
+    }
+
+    #[test]
+    fn prefers_earlier_services() {
+        assert_eq!(best(&[], false, false), Some(Service::IpApi));
+        assert_eq!(
+            best(&[Field::City, Field::Isp], false, false),
+            Some(Service::IpApi)
+        );
+        assert_eq!(
+            best(&[Field::MetroCode], false, false),
+            Some(Service::FreeGeoIp)
+        );
+    }
+
+    #[test]
+    fn counts_selected_fields() {
+        assert_eq!(best(&[Field::Security], false, false), Some(Service::IpApi));
+    }
+
+    #[test]
+    fn counts_paid_fields_only_when_paid() {
+        let fields = [Field::Isp, Field::Postal, Field::Security];
+
+        assert_eq!(best(&fields, false, true), None);
+        assert_eq!(best(&fields, true, true), Some(Service::IpApi));
+        assert_eq!(
+            best(&[Field::Security, Field::MetroCode], true, false),
+            None
+        );
+    }
+
+    #[test]
+    fn skips_plain_http_when_https_only() {
+        assert_eq!(best(&[Field::City], false, true), Some(Service::IpInfo));
+        assert_eq!(
+            best(&[Field::RegionCode], false, true),
+            Some(Service::IpApiCo)
+        );
+        assert_eq!(best(&[Field::City], true, true), Some(Service::IpApi));
+    }
+}
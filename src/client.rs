@@ -0,0 +1,664 @@
+//! A configurable lookup client.
+//!
+//! [`Locator::get`] and friends use a [`Client`] with the default settings. Build one
+//! yourself to change how addresses are prepared before they're sent to a service.
+
+use crate::anonymizers::Anonymizers;
+use crate::cloud::CloudRanges;
+use crate::ipapi::Field;
+use crate::lenient::Warning;
+use crate::overrides::Override;
+use crate::special::AddressCategory;
+use crate::threat::{Flag, Source};
+use crate::transition::Transition;
+use crate::{
+    capabilities, ipapi, ipinfo, language, lenient, special, transition, Database, Discovery,
+    GeoError, Locator, Overrides, Raw, Resolver, Service, TransportPolicy,
+};
+use futures::future::join_all;
+use reqwest::header::HeaderMap;
+use reqwest::StatusCode;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::net::IpAddr;
+use tracing::debug;
+
+/// Looks up addresses with a [`Service`] and a set of options.
+#[derive(Clone)]
+pub struct Client {
+    service: Service,
+    embedded_ipv4: bool,
+    resolver: Resolver,
+    discovery: Discovery,
+    api_key: Option<String>,
+    transport: TransportPolicy,
+    /// Built from `transport` once, so connections are pooled across lookups.
+    http: std::result::Result<reqwest::Client, GeoError>,
+    fields: Option<Vec<Field>>,
+    languages: Vec<String>,
+    asn_database: Option<Database>,
+    anonymizers: Option<Anonymizers>,
+    cloud_ranges: Option<CloudRanges>,
+    keep_raw: bool,
+    lenient: Option<Vec<lenient::Field>>,
+    overrides: Option<Overrides>,
+}
+
+// Written out so the API key never ends up in logs.
+impl fmt::Debug for Client {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Client")
+            .field("service", &self.service)
+            .field("embedded_ipv4", &self.embedded_ipv4)
+            .field("resolver", &self.resolver)
+            .field("discovery", &self.discovery)
+            .field("api_key", &self.api_key.as_ref().map(|_| "<redacted>"))
+            .field("transport", &self.transport)
+            .field("fields", &self.fields)
+            .field("languages", &self.languages)
+            .field("asn_database", &self.asn_database)
+            .field("anonymizers", &self.anonymizers)
+            .field("cloud_ranges", &self.cloud_ranges)
+            .field("keep_raw", &self.keep_raw)
+            .field("lenient", &self.lenient)
+            .field("overrides", &self.overrides)
+            .finish()
+    }
+}
+
+impl Client {
+    /// Creates a client for `service` with the default options.
+    pub fn new(service: Service) -> Self {
+        Client {
+            service,
+            embedded_ipv4: true,
+            resolver: Resolver::system(),
+            discovery: Discovery::new(),
+            api_key: None,
+            transport: TransportPolicy::new(),
+            http: TransportPolicy::new().http_client(Some(service)),
+            fields: None,
+            languages: Vec::new(),
+            asn_database: None,
+            anonymizers: None,
+            cloud_ranges: None,
+            keep_raw: false,
+            lenient: None,
+            overrides: None,
+        }
+    }
+
+    /// Creates a client for the most preferred service that supplies all of `fields` on its
+    /// free plan (see [`capabilities::best`]). With `https_only`, only services served over
+    /// HTTPS are considered and the client's [`TransportPolicy`] refuses plain HTTP.
+    ///
+    /// If that's [`Service::IpApi`] and [`Security`](capabilities::Field::Security) is among the
+    /// fields, the [`Locator`] and network fields are selected along with the ones asked for,
+    /// so the proxy and hosting flags are sent.
+    pub fn for_fields(
+        fields: &[capabilities::Field],
+        https_only: bool,
+    ) -> std::result::Result<Self, GeoError> {
+        let service = match capabilities::best(fields, false, https_only) {
+            Some(service) => service,
+            None => {
+                let fields: Vec<String> = fields.iter().map(|field| field.to_string()).collect();
+                return Err(GeoError::NoService(format!(
+                    "No {}service supplies {}",
+                    if https_only { "HTTPS " } else { "" },
+                    fields.join(", ")
+                )));
+            }
+        };
+
+        let client = Client::new(service).transport(TransportPolicy::new().https_only(https_only));
+        if service == Service::IpApi && fields.contains(&capabilities::Field::Security) {
+            Ok(client.ipapi_fields(&ipapi::selection(fields)))
+        } else {
+            Ok(client)
+        }
+    }
+
+    /// Whether IPv6 transition addresses are looked up through the IPv4 address embedded
+    /// in them (see [`transition`]). Defaults to `true`.
+    pub fn embedded_ipv4(mut self, enabled: bool) -> Self {
+        self.embedded_ipv4 = enabled;
+        self
+    }
+
+    /// Sets the [`Resolver`] used by [`get_host`](Client::get_host). Defaults to the system's.
+    pub fn resolver(mut self, resolver: Resolver) -> Self {
+        self.resolver = resolver;
+        self
+    }
+
+    /// Sets the [`Discovery`] used by [`get_self`](Client::get_self).
+    pub fn discovery(mut self, discovery: Discovery) -> Self {
+        self.discovery = discovery;
+        self
+    }
+
+    /// Sets the API key or access token sent to the service.
+    ///
+    /// For [`Service::IpApi`], [`Service::IpWhois`] and [`Service::IpApiCo`] this switches to
+    /// the paid endpoint (`pro.ip-api.com`, `ipwhois.pro` and keyed `ipapi.co`), which is
+    /// served over HTTPS and has the plan's higher quota. [`Service::IpInfo`] sends it as the
+    /// access token. [`Service::FreeGeoIp`] ignores it.
+    pub fn api_key(mut self, api_key: &str) -> Self {
+        self.api_key = Some(api_key.to_string());
+        self
+    }
+
+    /// Sets the [`TransportPolicy`] for requests to the service.
+    ///
+    /// The policy also covers the echo services [`get_self`](Client::get_self) asks. If it
+    /// can't be used, like when a root certificate is invalid, lookups fail with its error.
+    pub fn transport(mut self, transport: TransportPolicy) -> Self {
+        self.http = transport.http_client(Some(self.service));
+        self.transport = transport;
+        self
+    }
+
+    /// Asks [`Service::IpApi`] for just these fields (see [`ipapi`]).
+    ///
+    /// [`Locator`] fields that aren't selected are left empty, and fields beyond them are
+    /// returned in [`Locator::ipapi`](crate::Locator#structfield.ipapi). Other services ignore the selection.
+    pub fn ipapi_fields(mut self, fields: &[Field]) -> Self {
+        self.fields = Some(fields.to_vec());
+        self
+    }
+
+    /// Asks for place names in the first of these languages (BCP 47 tags, like `de-CH`) the
+    /// service supports, falling back to English.
+    ///
+    /// [`Service::IpApi`] and [`Service::IpWhois`] support a handful of languages (see
+    /// [`language`]); other services always answer in English.
+    pub fn languages(mut self, languages: &[&str]) -> Self {
+        self.languages = languages.iter().map(|tag| tag.to_string()).collect();
+        self
+    }
+
+    /// Fills in [`Locator::network`](crate::Locator#structfield.network) from an offline ASN
+    /// database (like GeoLite2-ASN) wherever the service didn't say who owns the address.
+    pub fn asn_database(mut self, database: Database) -> Self {
+        self.asn_database = Some(database);
+        self
+    }
+
+    /// Flags lookups whose address is on one of these Tor exit, VPN or proxy lists, in
+    /// addition to whatever the service reports.
+    pub fn anonymizers(mut self, anonymizers: Anonymizers) -> Self {
+        self.anonymizers = Some(anonymizers);
+        self
+    }
+
+    /// Sets [`Locator::cloud`](crate::Locator::cloud) for lookups in one of these cloud provider
+    /// ranges and flags them as hosting. If the ranges were built with
+    /// [`locate`](CloudRanges::locate), the location is replaced by the region's.
+    pub fn cloud_ranges(mut self, ranges: CloudRanges) -> Self {
+        self.cloud_ranges = Some(ranges);
+        self
+    }
+
+    /// Whether results keep the service's decoded response, HTTP status and rate limit
+    /// headers in [`Locator::raw`](crate::Locator::raw). Defaults to `false`.
+    pub fn keep_raw(mut self, enabled: bool) -> Self {
+        self.keep_raw = enabled;
+        self
+    }
+
+    /// Returns partial results when the service leaves fields out: only a missing `essential`
+    /// field fails the lookup, and the others are left empty with a warning in
+    /// [`Locator::warnings`](crate::Locator::warnings) (see [`lenient`]). By default any missing
+    /// field fails it.
+    pub fn lenient(mut self, essential: &[lenient::Field]) -> Self {
+        self.lenient = Some(essential.to_vec());
+        self
+    }
+
+    /// Consults `overrides` before the service (see [`overrides`](crate::overrides)).
+    ///
+    /// Addresses in a range with every field set are answered without a request. Partial
+    /// overrides are merged onto the service's result, or for special-purpose addresses,
+    /// which aren't sent to the service, make up the result on their own with
+    /// [`Locator::category`](crate::Locator::category) set.
+    pub fn overrides(mut self, overrides: Overrides) -> Self {
+        self.overrides = Some(overrides);
+        self
+    }
+
+    /// The service this client sends lookups to.
+    pub fn service(&self) -> Service {
+        self.service
+    }
+
+    /// Gets IP information from an [`IpAddr`].
+    pub async fn get_ipaddr(&self, ip: IpAddr) -> std::result::Result<Locator, GeoError> {
+        self.get(&ip.to_string()).await
+    }
+
+    /// Resolves `host` and gets IP information for each of its addresses.
+    ///
+    /// Fails only if `host` can't be resolved; each address has its own result.
+    pub async fn get_host(
+        &self,
+        host: &str,
+    ) -> std::result::Result<BTreeMap<IpAddr, std::result::Result<Locator, GeoError>>, GeoError>
+    {
+        let addresses = self.resolver.resolve(host).await?;
+
+        let lookups = addresses.iter().map(|address| self.get_ipaddr(*address));
+        let results = join_all(lookups).await;
+
+        Ok(addresses.into_iter().zip(results).collect())
+    }
+
+    /// Finds this host's public IP address and gets IP information for it.
+    ///
+    /// The public IPv4 address is used if the host has one, otherwise the IPv6 address.
+    pub async fn get_self(&self) -> std::result::Result<Locator, GeoError> {
+        let addresses = self.discovery.find_with(&self.transport).await?;
+
+        match (addresses.ipv4, addresses.ipv6) {
+            (Some(ipv4), _) => self.get_ipaddr(IpAddr::V4(ipv4)).await,
+            (None, Some(ipv6)) => self.get_ipaddr(IpAddr::V6(ipv6)).await,
+            (None, None) => Err(GeoError::DiscoveryError(
+                "Couldn't find a public address".to_string(),
+            )),
+        }
+    }
+
+    /// Gets IP information for several addresses.
+    ///
+    /// [`Service::IpInfo`] looks them up with its batch endpoint; other services are sent
+    /// one request per address. Each address has its own result.
+    pub async fn get_batch(
+        &self,
+        ips: &[&str],
+    ) -> std::result::Result<BTreeMap<String, std::result::Result<Locator, GeoError>>, GeoError>
+    {
+        if !matches!(self.service, Service::IpInfo) {
+            let lookups = ips.iter().map(|ip| self.get(ip));
+            let results = join_all(lookups).await;
+
+            return Ok(ips.iter().map(|ip| ip.to_string()).zip(results).collect());
+        }
+
+        let mut results = BTreeMap::new();
+        let mut prepared = Vec::new();
+
+        for ip in ips {
+            match self.prepare(ip, self.overrides.as_ref()) {
+                Ok(Prepared::Answered(location)) => {
+                    results.insert(ip.to_string(), Ok(*location));
+                }
+                Ok(Prepared::Lookup {
+                    address,
+                    transition,
+                    entry,
+                }) => prepared.push((ip.to_string(), address, transition, entry)),
+                Err(error) => {
+                    results.insert(ip.to_string(), Err(error));
+                }
+            }
+        }
+
+        for chunk in prepared.chunks(ipinfo::BATCH_LIMIT) {
+            let addresses: Vec<String> = chunk
+                .iter()
+                .map(|(_, address, _, _)| address.clone())
+                .collect();
+            let locations = ipinfo::batch(&addresses, self).await?;
+
+            for (ip, address, transition, entry) in chunk {
+                let location = locations[address].clone().map(|mut location| {
+                    location.transition = *transition;
+                    self.finish(location, *entry)
+                });
+
+                results.insert(ip.clone(), location);
+            }
+        }
+
+        Ok(results)
+    }
+
+    /// Gets IP information from a string.
+    pub async fn get(&self, ip: &str) -> std::result::Result<Locator, GeoError> {
+        self.get_overridden(ip, self.overrides.as_ref()).await
+    }
+
+    /// [`get`](Client::get), consulting `overrides` instead of the client's own table.
+    pub(crate) async fn get_overridden(
+        &self,
+        ip: &str,
+        overrides: Option<&Overrides>,
+    ) -> std::result::Result<Locator, GeoError> {
+        let (ip, transition, entry) = match self.prepare(ip, overrides)? {
+            Prepared::Answered(location) => return Ok(*location),
+            Prepared::Lookup {
+                address,
+                transition,
+                entry,
+            } => (address, transition, entry),
+        };
+
+        let mut result = match self.service {
+            Service::IpWhois => Locator::ipwhois(&ip, self).await,
+            Service::IpApi => Locator::ipapi(&ip, self).await,
+            Service::IpApiCo => Locator::ipapico(&ip, self).await,
+            Service::FreeGeoIp => Locator::freegeoip(&ip, self).await,
+            Service::IpInfo => ipinfo::get(&ip, self).await,
+        }?;
+
+        result.transition = transition;
+
+        Ok(self.finish(result, entry))
+    }
+
+    /// The API key, if one was set.
+    pub(crate) fn key(&self) -> Option<&str> {
+        self.api_key.as_deref()
+    }
+
+    /// The [`Service::IpApi`] field selection, if one was set.
+    pub(crate) fn fields(&self) -> Option<&[Field]> {
+        self.fields.as_deref()
+    }
+
+    /// The preferred language out of those `available`, if a preference was set.
+    pub(crate) fn language(&self, available: &[&'static str]) -> Option<&'static str> {
+        language::negotiate(&self.languages, available)
+    }
+
+    /// Checks `url` against the transport policy and returns the HTTP client that enforces it.
+    pub(crate) fn http(&self, url: &str) -> std::result::Result<reqwest::Client, GeoError> {
+        self.transport.check(url)?;
+        self.http.clone()
+    }
+
+    /// `value`, or if the service didn't send it, see [`missing`](Client::missing).
+    pub(crate) fn required(
+        &self,
+        value: Option<String>,
+        field: lenient::Field,
+        warnings: &mut Vec<Warning>,
+    ) -> std::result::Result<String, GeoError> {
+        match value {
+            Some(value) => Ok(value),
+            None => self.missing(field, warnings),
+        }
+    }
+
+    /// Handles `field` missing from the service's response: fails, unless the client is
+    /// lenient and it isn't essential, in which case it's recorded in `warnings` and left empty.
+    pub(crate) fn missing(
+        &self,
+        field: lenient::Field,
+        warnings: &mut Vec<Warning>,
+    ) -> std::result::Result<String, GeoError> {
+        match &self.lenient {
+            Some(essential) if !essential.contains(&field) => {
+                debug!("{} didn't send {}", self.service, field);
+                warnings.push(Warning {
+                    field,
+                    service: self.service,
+                });
+                Ok(String::default())
+            }
+            _ => Err(GeoError::ParseError(format!(
+                "Unable to find {} in parsed JSON",
+                field
+            ))),
+        }
+    }
+
+    /// The response to keep on a result, if [`keep_raw`](Client::keep_raw) is set.
+    pub(crate) fn raw(&self, status: StatusCode, headers: &HeaderMap, body: &Value) -> Option<Raw> {
+        if self.keep_raw {
+            Some(Raw::new(status, headers, body))
+        } else {
+            None
+        }
+    }
+
+    /// Fills in what the service left out of `location` and merges the override `entry` onto
+    /// it.
+    fn finish(&self, mut location: Locator, entry: Option<&Override>) -> Locator {
+        self.fill(&mut location);
+
+        match entry {
+            Some(entry) => entry.apply(location),
+            None => location,
+        }
+    }
+
+    /// Fills in what the service left out of `location`.
+    fn fill(&self, location: &mut Locator) {
+        let address = location.ip.parse().ok();
+
+        if let (Some(ranges), Some(address)) = (&self.cloud_ranges, address) {
+            if let Some((cloud, file)) = ranges.lookup(address) {
+                location
+                    .security
+                    .hosting
+                    .set(Flag::Yes, Source::List(file.to_string()));
+
+                if let Some(region) = cloud.location().filter(|_| ranges.locates()) {
+                    region.apply(location);
+                }
+
+                location.cloud = Some(cloud);
+            }
+        }
+
+        location.normalize();
+
+        let address = match address {
+            Some(address) => address,
+            None => return,
+        };
+
+        if let Some(database) = &self.asn_database {
+            match database.network(address) {
+                Ok(found) => match &mut location.network {
+                    Some(network) => network.merge(found),
+                    None => location.network = Some(found),
+                },
+                Err(error) => debug!("{}", error),
+            }
+        }
+
+        if let Some(anonymizers) = &self.anonymizers {
+            location.security.merge(anonymizers.classify(address));
+        }
+    }
+
+    /// Works out which address to send to the service, or answers from `overrides`, or fails
+    /// if the address can't have a location.
+    fn prepare<'a>(
+        &self,
+        ip: &str,
+        overrides: Option<&'a Overrides>,
+    ) -> std::result::Result<Prepared<'a>, GeoError> {
+        let mut address = ip.parse().ok();
+
+        let entry = match (overrides, address) {
+            (Some(overrides), Some(address)) => overrides.find(address),
+            _ => None,
+        };
+
+        if let Some(entry) = entry.filter(|entry| entry.is_complete()) {
+            return Ok(Prepared::Answered(Box::new(entry.locator(ip))));
+        }
+
+        let transition = match address {
+            Some(IpAddr::V6(ipv6)) if self.embedded_ipv4 => transition::embedded_ipv4(ipv6),
+            _ => None,
+        };
+
+        // The IPv6 address is classified before the IPv4 address embedded in it, so a
+        // link-local or unique local transition address is never looked up. IPv4-mapped
+        // addresses are only special when they aren't looked up through their IPv4 address.
+        let mut category = address
+            .and_then(special::classify)
+            .filter(|&category| category != AddressCategory::Ipv4Mapped || transition.is_none());
+
+        if let Some(transition) = transition.filter(|_| category.is_none()) {
+            address = Some(IpAddr::V4(transition.ipv4));
+            category = special::classify(IpAddr::V4(transition.ipv4));
+        }
+
+        if let (Some(address), Some(category)) = (address, category) {
+            return match entry {
+                Some(entry) => {
+                    let mut location = entry.locator(ip);
+                    location.category = Some(category);
+                    Ok(Prepared::Answered(Box::new(location)))
+                }
+                None => Err(GeoError::SpecialAddress(address, category)),
+            };
+        }
+
+        let ip = match transition {
+            Some(transition) => transition.ipv4.to_string(),
+            None => ip.to_string(),
+        };
+
+        Ok(Prepared::Lookup {
+            address: ip,
+            transition,
+            entry,
+        })
+    }
+}
+
+/// What [`Client::prepare`] decided to do with an address.
+enum Prepared<'a> {
+    /// An override answered without asking the service.
+    Answered(Box<Locator>),
+    /// `address` needs looking up, then `entry` merging onto the result.
+    Lookup {
+        address: String,
+        transition: Option<Transition>,
+        entry: Option<&'a Override>,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The category `prepare` refuses `ip` with, or the address it would look up.
+    fn prepared(client: &Client, ip: &str) -> std::result::Result<String, AddressCategory> {
+        match client.prepare(ip, None) {
+            Ok(Prepared::Lookup { address, .. }) => Ok(address),
+            Err(GeoError::SpecialAddress(_, category)) => Err(category),
+            Ok(Prepared::Answered(_)) => panic!("{} was answered without overrides", ip),
+            Err(error) => panic!("{} failed: {}", ip, error),
+        }
+    }
+
+    #[test]
+    fn classifies_transition_addresses_before_unwrapping() {
+        let client = Client::new(Service::IpApi);
+
+        assert_eq!(
+            prepared(&client, "fe80::5efe:808:808"),
+            Err(AddressCategory::LinkLocal)
+        );
+        assert_eq!(
+            prepared(&client, "fd00::5efe:808:808"),
+            Err(AddressCategory::UniqueLocal)
+        );
+        assert_eq!(
+            prepared(&client, "2002:a00:1::1"),
+            Err(AddressCategory::Private)
+        );
+        assert_eq!(
+            prepared(&client, "2a00:1450::5efe:808:808"),
+            Ok("8.8.8.8".to_string())
+        );
+        assert_eq!(
+            prepared(&client, "::ffff:1.1.1.1"),
+            Ok("1.1.1.1".to_string())
+        );
+
+        let client = client.embedded_ipv4(false);
+        assert_eq!(
+            prepared(&client, "::ffff:1.1.1.1"),
+            Err(AddressCategory::Ipv4Mapped)
+        );
+    }
+
+    #[test]
+    fn builds_http_client_with_policy() {
+        let client = Client::new(Service::IpInfo);
+        assert!(client.http("https://ipinfo.io/1.1.1.1").is_ok());
+
+        let policy = TransportPolicy::new().add_root_certificate_der(b"not a certificate");
+        let client = client.transport(policy);
+        match client.http("https://ipinfo.io/1.1.1.1") {
+            Err(GeoError::TransportError(error)) => assert!(error.contains("root certificate")),
+            other => panic!("expected a transport error, got {:?}", other.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn redacts_api_key() {
+        let client = Client::new(Service::IpInfo).api_key("secret-token");
+        let debug = format!("{:?}", client);
+
+        assert!(!debug.contains("secret-token"));
+        assert!(debug.contains("<redacted>"));
+    }
+
+    #[test]
+    fn selects_security_fields_without_reverse_dns() {
+        let client = Client::for_fields(
+            &[
+                capabilities::Field::Security,
+                capabilities::Field::RegionCode,
+            ],
+            false,
+        )
+        .unwrap();
+        assert_eq!(client.service, Service::IpApi);
+
+        let fields = client.fields().unwrap();
+        for field in [
+            Field::Proxy,
+            Field::Hosting,
+            Field::Region,
+            Field::Lat,
+            Field::As,
+        ] {
+            assert!(fields.contains(&field), "{} isn't selected", field);
+        }
+        assert!(!fields.contains(&Field::Reverse));
+
+        let distinct: std::collections::HashSet<_> = fields.iter().collect();
+        assert_eq!(distinct.len(), fields.len());
+    }
+
+    #[test]
+    fn keeps_default_ipapi_fields_without_security() {
+        let client = Client::for_fields(&[capabilities::Field::City], false).unwrap();
+        assert_eq!(client.service, Service::IpApi);
+        assert!(client.fields().is_none());
+    }
+
+    #[test]
+    fn only_advertises_selected_security_for_ipapi() {
+        let ipapi = Service::IpApi.capabilities();
+        assert!(!ipapi.supplies(capabilities::Field::Security, false));
+        assert!(ipapi
+            .selected_fields
+            .contains(&capabilities::Field::Security));
+        assert_eq!(
+            capabilities::best(&[capabilities::Field::Security], false, false),
+            Some(Service::IpApi)
+        );
+    }
+}
@@ -0,0 +1,2182 @@
+//! Cloud provider IP ranges.
+//!
+//! AWS, Google Cloud, Azure, Cloudflare and Oracle Cloud publish the address ranges they use.
+//! A [`CloudRanges`] loads those files and tells which provider, service and region an address
+//! belongs to (see [`Client::cloud_ranges`](crate::Client::cloud_ranges)). For a cloud address
+//! the region is usually a better answer than the city a geolocation service guesses, so the
+//! location can optionally be replaced by the region's documented location from [`REGIONS`].
+//!
+//! The files are read in the format each provider publishes them in:
+//!
+//! - AWS: `ip-ranges.json`
+//! - Google Cloud: `cloud.json`
+//! - Azure: the Service Tags JSON (`ServiceTags_Public_*.json`)
+//! - Cloudflare: `ips-v4` and `ips-v6`, one range per line
+//! - Oracle Cloud: `public_ip_ranges.json`
+
+use crate::anonymizers::parse_range;
+use crate::{GeoError, Locator};
+use ipnet::IpNet;
+use serde_json::Value;
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+use std::fs;
+use std::net::IpAddr;
+use std::path::Path;
+use std::sync::Arc;
+use tracing::debug;
+
+/// A cloud provider.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Provider {
+    Aws,
+    Gcp,
+    Azure,
+    Cloudflare,
+    Oracle,
+}
+
+impl fmt::Display for Provider {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Provider::Aws => write!(f, "AWS"),
+            Provider::Gcp => write!(f, "Google Cloud"),
+            Provider::Azure => write!(f, "Azure"),
+            Provider::Cloudflare => write!(f, "Cloudflare"),
+            Provider::Oracle => write!(f, "Oracle Cloud"),
+        }
+    }
+}
+
+/// Where in a provider's cloud an address is.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cloud {
+    pub provider: Provider,
+    /// The service using the range, as the provider names it, like `EC2` or `AzureStorage`.
+    pub service: Option<String>,
+    /// The provider's region, like `us-east-1`, `europe-west4` or `westeurope`.
+    pub region: Option<String>,
+}
+
+impl Cloud {
+    /// The documented location of the region, if it's in [`REGIONS`].
+    pub fn location(&self) -> Option<&'static RegionLocation> {
+        region_location(self.provider, self.region.as_deref()?)
+    }
+
+    /// How much this says about the address, to prefer the most detailed of several
+    /// entries for the same range.
+    fn detail(&self) -> u8 {
+        2 * self.region.is_some() as u8 + self.service.is_some() as u8
+    }
+
+    fn fill(&mut self, other: &Cloud) {
+        if self.service.is_none() {
+            self.service = other.service.clone();
+        }
+        if self.region.is_none() {
+            self.region = other.region.clone();
+        }
+    }
+}
+
+/// Where a provider says one of its regions is.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RegionLocation {
+    pub provider: Provider,
+    /// The region's name, like `us-east-1`.
+    pub region: &'static str,
+    pub city: &'static str,
+    /// The state or province, empty for city-states.
+    pub subdivision: &'static str,
+    /// The ISO 3166-1 alpha-2 code of the country.
+    pub country: &'static str,
+    pub latitude: f64,
+    pub longitude: f64,
+    /// The IANA time zone.
+    pub timezone: &'static str,
+}
+
+impl RegionLocation {
+    /// Replaces the location of `locator` with the region's.
+    pub(crate) fn apply(&self, locator: &mut Locator) {
+        locator.latitude = self.latitude.to_string();
+        locator.longitude = self.longitude.to_string();
+        locator.city = self.city.to_string();
+        locator.region = self.subdivision.to_string();
+        locator.region_code.clear();
+        locator.country.clear();
+        locator.country_code = self.country.to_string();
+        locator.country_code3.clear();
+        locator.timezone = self.timezone.to_string();
+        locator.postal.clear();
+        locator.metro_code = None;
+        locator.accuracy_radius = None;
+    }
+}
+
+macro_rules! regions {
+    ($(($provider:ident, $region:expr, $city:expr, $subdivision:expr, $country:expr, $latitude:expr, $longitude:expr, $timezone:expr)),* $(,)?) => {
+        &[$(RegionLocation {
+            provider: Provider::$provider,
+            region: $region,
+            city: $city,
+            subdivision: $subdivision,
+            country: $country,
+            latitude: $latitude,
+            longitude: $longitude,
+            timezone: $timezone,
+        }),*]
+    };
+}
+
+/// The documented locations of the providers' public regions. Cloudflare's network is
+/// anycast, so it has none.
+pub const REGIONS: &[RegionLocation] = regions![
+    (
+        Aws,
+        "us-east-1",
+        "Ashburn",
+        "Virginia",
+        "US",
+        39.04,
+        -77.49,
+        "America/New_York"
+    ),
+    (
+        Aws,
+        "us-east-2",
+        "Columbus",
+        "Ohio",
+        "US",
+        39.96,
+        -83.0,
+        "America/New_York"
+    ),
+    (
+        Aws,
+        "us-west-1",
+        "San Jose",
+        "California",
+        "US",
+        37.34,
+        -121.89,
+        "America/Los_Angeles"
+    ),
+    (
+        Aws,
+        "us-west-2",
+        "Boardman",
+        "Oregon",
+        "US",
+        45.84,
+        -119.7,
+        "America/Los_Angeles"
+    ),
+    (
+        Aws,
+        "ca-central-1",
+        "Montreal",
+        "Quebec",
+        "CA",
+        45.5,
+        -73.57,
+        "America/Toronto"
+    ),
+    (
+        Aws,
+        "ca-west-1",
+        "Calgary",
+        "Alberta",
+        "CA",
+        51.05,
+        -114.07,
+        "America/Edmonton"
+    ),
+    (
+        Aws,
+        "mx-central-1",
+        "Querétaro",
+        "Querétaro",
+        "MX",
+        20.59,
+        -100.39,
+        "America/Mexico_City"
+    ),
+    (
+        Aws,
+        "sa-east-1",
+        "São Paulo",
+        "São Paulo",
+        "BR",
+        -23.55,
+        -46.63,
+        "America/Sao_Paulo"
+    ),
+    (
+        Aws,
+        "eu-west-1",
+        "Dublin",
+        "",
+        "IE",
+        53.35,
+        -6.26,
+        "Europe/Dublin"
+    ),
+    (
+        Aws,
+        "eu-west-2",
+        "London",
+        "England",
+        "GB",
+        51.51,
+        -0.13,
+        "Europe/London"
+    ),
+    (
+        Aws,
+        "eu-west-3",
+        "Paris",
+        "Île-de-France",
+        "FR",
+        48.86,
+        2.35,
+        "Europe/Paris"
+    ),
+    (
+        Aws,
+        "eu-central-1",
+        "Frankfurt",
+        "Hesse",
+        "DE",
+        50.11,
+        8.68,
+        "Europe/Berlin"
+    ),
+    (
+        Aws,
+        "eu-central-2",
+        "Zurich",
+        "Zurich",
+        "CH",
+        47.37,
+        8.54,
+        "Europe/Zurich"
+    ),
+    (
+        Aws,
+        "eu-north-1",
+        "Stockholm",
+        "Stockholm",
+        "SE",
+        59.33,
+        18.07,
+        "Europe/Stockholm"
+    ),
+    (
+        Aws,
+        "eu-south-1",
+        "Milan",
+        "Lombardy",
+        "IT",
+        45.46,
+        9.19,
+        "Europe/Rome"
+    ),
+    (
+        Aws,
+        "eu-south-2",
+        "Zaragoza",
+        "Aragon",
+        "ES",
+        41.65,
+        -0.88,
+        "Europe/Madrid"
+    ),
+    (
+        Aws,
+        "ap-northeast-1",
+        "Tokyo",
+        "Tokyo",
+        "JP",
+        35.68,
+        139.69,
+        "Asia/Tokyo"
+    ),
+    (
+        Aws,
+        "ap-northeast-2",
+        "Seoul",
+        "Seoul",
+        "KR",
+        37.57,
+        126.98,
+        "Asia/Seoul"
+    ),
+    (
+        Aws,
+        "ap-northeast-3",
+        "Osaka",
+        "Osaka",
+        "JP",
+        34.69,
+        135.5,
+        "Asia/Tokyo"
+    ),
+    (
+        Aws,
+        "ap-southeast-1",
+        "Singapore",
+        "",
+        "SG",
+        1.35,
+        103.82,
+        "Asia/Singapore"
+    ),
+    (
+        Aws,
+        "ap-southeast-2",
+        "Sydney",
+        "New South Wales",
+        "AU",
+        -33.87,
+        151.21,
+        "Australia/Sydney"
+    ),
+    (
+        Aws,
+        "ap-southeast-3",
+        "Jakarta",
+        "Jakarta",
+        "ID",
+        -6.21,
+        106.85,
+        "Asia/Jakarta"
+    ),
+    (
+        Aws,
+        "ap-southeast-4",
+        "Melbourne",
+        "Victoria",
+        "AU",
+        -37.81,
+        144.96,
+        "Australia/Melbourne"
+    ),
+    (
+        Aws,
+        "ap-southeast-5",
+        "Kuala Lumpur",
+        "Kuala Lumpur",
+        "MY",
+        3.139,
+        101.687,
+        "Asia/Kuala_Lumpur"
+    ),
+    (
+        Aws,
+        "ap-southeast-7",
+        "Bangkok",
+        "Bangkok",
+        "TH",
+        13.76,
+        100.5,
+        "Asia/Bangkok"
+    ),
+    (
+        Aws,
+        "ap-south-1",
+        "Mumbai",
+        "Maharashtra",
+        "IN",
+        19.08,
+        72.88,
+        "Asia/Kolkata"
+    ),
+    (
+        Aws,
+        "ap-south-2",
+        "Hyderabad",
+        "Telangana",
+        "IN",
+        17.39,
+        78.49,
+        "Asia/Kolkata"
+    ),
+    (
+        Aws,
+        "ap-east-1",
+        "Hong Kong",
+        "",
+        "HK",
+        22.32,
+        114.17,
+        "Asia/Hong_Kong"
+    ),
+    (
+        Aws,
+        "me-south-1",
+        "Manama",
+        "",
+        "BH",
+        26.23,
+        50.59,
+        "Asia/Bahrain"
+    ),
+    (
+        Aws,
+        "me-central-1",
+        "Dubai",
+        "Dubai",
+        "AE",
+        25.2,
+        55.27,
+        "Asia/Dubai"
+    ),
+    (
+        Aws,
+        "il-central-1",
+        "Tel Aviv",
+        "Tel Aviv",
+        "IL",
+        32.09,
+        34.78,
+        "Asia/Jerusalem"
+    ),
+    (
+        Aws,
+        "af-south-1",
+        "Cape Town",
+        "Western Cape",
+        "ZA",
+        -33.92,
+        18.42,
+        "Africa/Johannesburg"
+    ),
+    (
+        Gcp,
+        "us-central1",
+        "Council Bluffs",
+        "Iowa",
+        "US",
+        41.26,
+        -95.86,
+        "America/Chicago"
+    ),
+    (
+        Gcp,
+        "us-east1",
+        "Moncks Corner",
+        "South Carolina",
+        "US",
+        33.2,
+        -80.01,
+        "America/New_York"
+    ),
+    (
+        Gcp,
+        "us-east4",
+        "Ashburn",
+        "Virginia",
+        "US",
+        39.04,
+        -77.49,
+        "America/New_York"
+    ),
+    (
+        Gcp,
+        "us-east5",
+        "Columbus",
+        "Ohio",
+        "US",
+        39.96,
+        -83.0,
+        "America/New_York"
+    ),
+    (
+        Gcp,
+        "us-south1",
+        "Dallas",
+        "Texas",
+        "US",
+        32.78,
+        -96.8,
+        "America/Chicago"
+    ),
+    (
+        Gcp,
+        "us-west1",
+        "The Dalles",
+        "Oregon",
+        "US",
+        45.59,
+        -121.18,
+        "America/Los_Angeles"
+    ),
+    (
+        Gcp,
+        "us-west2",
+        "Los Angeles",
+        "California",
+        "US",
+        34.05,
+        -118.24,
+        "America/Los_Angeles"
+    ),
+    (
+        Gcp,
+        "us-west3",
+        "Salt Lake City",
+        "Utah",
+        "US",
+        40.76,
+        -111.89,
+        "America/Denver"
+    ),
+    (
+        Gcp,
+        "us-west4",
+        "Las Vegas",
+        "Nevada",
+        "US",
+        36.17,
+        -115.14,
+        "America/Los_Angeles"
+    ),
+    (
+        Gcp,
+        "northamerica-northeast1",
+        "Montreal",
+        "Quebec",
+        "CA",
+        45.5,
+        -73.57,
+        "America/Toronto"
+    ),
+    (
+        Gcp,
+        "northamerica-northeast2",
+        "Toronto",
+        "Ontario",
+        "CA",
+        43.65,
+        -79.38,
+        "America/Toronto"
+    ),
+    (
+        Gcp,
+        "northamerica-south1",
+        "Querétaro",
+        "Querétaro",
+        "MX",
+        20.59,
+        -100.39,
+        "America/Mexico_City"
+    ),
+    (
+        Gcp,
+        "southamerica-east1",
+        "Osasco",
+        "São Paulo",
+        "BR",
+        -23.53,
+        -46.79,
+        "America/Sao_Paulo"
+    ),
+    (
+        Gcp,
+        "southamerica-west1",
+        "Santiago",
+        "Santiago Metropolitan",
+        "CL",
+        -33.45,
+        -70.67,
+        "America/Santiago"
+    ),
+    (
+        Gcp,
+        "europe-west1",
+        "St. Ghislain",
+        "Hainaut",
+        "BE",
+        50.47,
+        3.82,
+        "Europe/Brussels"
+    ),
+    (
+        Gcp,
+        "europe-west2",
+        "London",
+        "England",
+        "GB",
+        51.51,
+        -0.13,
+        "Europe/London"
+    ),
+    (
+        Gcp,
+        "europe-west3",
+        "Frankfurt",
+        "Hesse",
+        "DE",
+        50.11,
+        8.68,
+        "Europe/Berlin"
+    ),
+    (
+        Gcp,
+        "europe-west4",
+        "Eemshaven",
+        "Groningen",
+        "NL",
+        53.44,
+        6.83,
+        "Europe/Amsterdam"
+    ),
+    (
+        Gcp,
+        "europe-west6",
+        "Zurich",
+        "Zurich",
+        "CH",
+        47.37,
+        8.54,
+        "Europe/Zurich"
+    ),
+    (
+        Gcp,
+        "europe-west8",
+        "Milan",
+        "Lombardy",
+        "IT",
+        45.46,
+        9.19,
+        "Europe/Rome"
+    ),
+    (
+        Gcp,
+        "europe-west9",
+        "Paris",
+        "Île-de-France",
+        "FR",
+        48.86,
+        2.35,
+        "Europe/Paris"
+    ),
+    (
+        Gcp,
+        "europe-west10",
+        "Berlin",
+        "Berlin",
+        "DE",
+        52.52,
+        13.4,
+        "Europe/Berlin"
+    ),
+    (
+        Gcp,
+        "europe-west12",
+        "Turin",
+        "Piedmont",
+        "IT",
+        45.07,
+        7.69,
+        "Europe/Rome"
+    ),
+    (
+        Gcp,
+        "europe-north1",
+        "Hamina",
+        "Kymenlaakso",
+        "FI",
+        60.57,
+        27.2,
+        "Europe/Helsinki"
+    ),
+    (
+        Gcp,
+        "europe-north2",
+        "Stockholm",
+        "Stockholm",
+        "SE",
+        59.33,
+        18.07,
+        "Europe/Stockholm"
+    ),
+    (
+        Gcp,
+        "europe-central2",
+        "Warsaw",
+        "Masovia",
+        "PL",
+        52.23,
+        21.01,
+        "Europe/Warsaw"
+    ),
+    (
+        Gcp,
+        "europe-southwest1",
+        "Madrid",
+        "Madrid",
+        "ES",
+        40.42,
+        -3.7,
+        "Europe/Madrid"
+    ),
+    (
+        Gcp,
+        "asia-east1",
+        "Changhua County",
+        "Changhua",
+        "TW",
+        24.07,
+        120.54,
+        "Asia/Taipei"
+    ),
+    (
+        Gcp,
+        "asia-east2",
+        "Hong Kong",
+        "",
+        "HK",
+        22.32,
+        114.17,
+        "Asia/Hong_Kong"
+    ),
+    (
+        Gcp,
+        "asia-northeast1",
+        "Tokyo",
+        "Tokyo",
+        "JP",
+        35.68,
+        139.69,
+        "Asia/Tokyo"
+    ),
+    (
+        Gcp,
+        "asia-northeast2",
+        "Osaka",
+        "Osaka",
+        "JP",
+        34.69,
+        135.5,
+        "Asia/Tokyo"
+    ),
+    (
+        Gcp,
+        "asia-northeast3",
+        "Seoul",
+        "Seoul",
+        "KR",
+        37.57,
+        126.98,
+        "Asia/Seoul"
+    ),
+    (
+        Gcp,
+        "asia-south1",
+        "Mumbai",
+        "Maharashtra",
+        "IN",
+        19.08,
+        72.88,
+        "Asia/Kolkata"
+    ),
+    (
+        Gcp,
+        "asia-south2",
+        "Delhi",
+        "Delhi",
+        "IN",
+        28.61,
+        77.21,
+        "Asia/Kolkata"
+    ),
+    (
+        Gcp,
+        "asia-southeast1",
+        "Jurong West",
+        "",
+        "SG",
+        1.34,
+        103.71,
+        "Asia/Singapore"
+    ),
+    (
+        Gcp,
+        "asia-southeast2",
+        "Jakarta",
+        "Jakarta",
+        "ID",
+        -6.21,
+        106.85,
+        "Asia/Jakarta"
+    ),
+    (
+        Gcp,
+        "australia-southeast1",
+        "Sydney",
+        "New South Wales",
+        "AU",
+        -33.87,
+        151.21,
+        "Australia/Sydney"
+    ),
+    (
+        Gcp,
+        "australia-southeast2",
+        "Melbourne",
+        "Victoria",
+        "AU",
+        -37.81,
+        144.96,
+        "Australia/Melbourne"
+    ),
+    (
+        Gcp,
+        "me-west1",
+        "Tel Aviv",
+        "Tel Aviv",
+        "IL",
+        32.09,
+        34.78,
+        "Asia/Jerusalem"
+    ),
+    (
+        Gcp,
+        "me-central1",
+        "Doha",
+        "Doha",
+        "QA",
+        25.29,
+        51.53,
+        "Asia/Qatar"
+    ),
+    (
+        Gcp,
+        "me-central2",
+        "Dammam",
+        "Eastern Province",
+        "SA",
+        26.43,
+        50.1,
+        "Asia/Riyadh"
+    ),
+    (
+        Gcp,
+        "africa-south1",
+        "Johannesburg",
+        "Gauteng",
+        "ZA",
+        -26.2,
+        28.05,
+        "Africa/Johannesburg"
+    ),
+    (
+        Azure,
+        "eastus",
+        "Boydton",
+        "Virginia",
+        "US",
+        36.67,
+        -78.39,
+        "America/New_York"
+    ),
+    (
+        Azure,
+        "eastus2",
+        "Boydton",
+        "Virginia",
+        "US",
+        36.67,
+        -78.39,
+        "America/New_York"
+    ),
+    (
+        Azure,
+        "centralus",
+        "Des Moines",
+        "Iowa",
+        "US",
+        41.59,
+        -93.62,
+        "America/Chicago"
+    ),
+    (
+        Azure,
+        "northcentralus",
+        "Chicago",
+        "Illinois",
+        "US",
+        41.88,
+        -87.63,
+        "America/Chicago"
+    ),
+    (
+        Azure,
+        "southcentralus",
+        "San Antonio",
+        "Texas",
+        "US",
+        29.42,
+        -98.49,
+        "America/Chicago"
+    ),
+    (
+        Azure,
+        "westcentralus",
+        "Cheyenne",
+        "Wyoming",
+        "US",
+        41.14,
+        -104.82,
+        "America/Denver"
+    ),
+    (
+        Azure,
+        "westus",
+        "San Francisco",
+        "California",
+        "US",
+        37.77,
+        -122.42,
+        "America/Los_Angeles"
+    ),
+    (
+        Azure,
+        "westus2",
+        "Quincy",
+        "Washington",
+        "US",
+        47.23,
+        -119.85,
+        "America/Los_Angeles"
+    ),
+    (
+        Azure,
+        "westus3",
+        "Phoenix",
+        "Arizona",
+        "US",
+        33.45,
+        -112.07,
+        "America/Phoenix"
+    ),
+    (
+        Azure,
+        "canadacentral",
+        "Toronto",
+        "Ontario",
+        "CA",
+        43.65,
+        -79.38,
+        "America/Toronto"
+    ),
+    (
+        Azure,
+        "canadaeast",
+        "Quebec City",
+        "Quebec",
+        "CA",
+        46.81,
+        -71.21,
+        "America/Toronto"
+    ),
+    (
+        Azure,
+        "mexicocentral",
+        "Querétaro",
+        "Querétaro",
+        "MX",
+        20.59,
+        -100.39,
+        "America/Mexico_City"
+    ),
+    (
+        Azure,
+        "brazilsouth",
+        "São Paulo",
+        "São Paulo",
+        "BR",
+        -23.55,
+        -46.63,
+        "America/Sao_Paulo"
+    ),
+    (
+        Azure,
+        "chilecentral",
+        "Santiago",
+        "Santiago Metropolitan",
+        "CL",
+        -33.45,
+        -70.67,
+        "America/Santiago"
+    ),
+    (
+        Azure,
+        "northeurope",
+        "Dublin",
+        "",
+        "IE",
+        53.35,
+        -6.26,
+        "Europe/Dublin"
+    ),
+    (
+        Azure,
+        "westeurope",
+        "Amsterdam",
+        "North Holland",
+        "NL",
+        52.37,
+        4.9,
+        "Europe/Amsterdam"
+    ),
+    (
+        Azure,
+        "uksouth",
+        "London",
+        "England",
+        "GB",
+        51.51,
+        -0.13,
+        "Europe/London"
+    ),
+    (
+        Azure,
+        "ukwest",
+        "Cardiff",
+        "Wales",
+        "GB",
+        51.48,
+        -3.18,
+        "Europe/London"
+    ),
+    (
+        Azure,
+        "francecentral",
+        "Paris",
+        "Île-de-France",
+        "FR",
+        48.86,
+        2.35,
+        "Europe/Paris"
+    ),
+    (
+        Azure,
+        "germanywestcentral",
+        "Frankfurt",
+        "Hesse",
+        "DE",
+        50.11,
+        8.68,
+        "Europe/Berlin"
+    ),
+    (
+        Azure,
+        "switzerlandnorth",
+        "Zurich",
+        "Zurich",
+        "CH",
+        47.37,
+        8.54,
+        "Europe/Zurich"
+    ),
+    (
+        Azure,
+        "norwayeast",
+        "Oslo",
+        "Oslo",
+        "NO",
+        59.91,
+        10.75,
+        "Europe/Oslo"
+    ),
+    (
+        Azure,
+        "swedencentral",
+        "Gävle",
+        "Gävleborg",
+        "SE",
+        60.67,
+        17.14,
+        "Europe/Stockholm"
+    ),
+    (
+        Azure,
+        "italynorth",
+        "Milan",
+        "Lombardy",
+        "IT",
+        45.46,
+        9.19,
+        "Europe/Rome"
+    ),
+    (
+        Azure,
+        "polandcentral",
+        "Warsaw",
+        "Masovia",
+        "PL",
+        52.23,
+        21.01,
+        "Europe/Warsaw"
+    ),
+    (
+        Azure,
+        "spaincentral",
+        "Madrid",
+        "Madrid",
+        "ES",
+        40.42,
+        -3.7,
+        "Europe/Madrid"
+    ),
+    (
+        Azure,
+        "eastasia",
+        "Hong Kong",
+        "",
+        "HK",
+        22.32,
+        114.17,
+        "Asia/Hong_Kong"
+    ),
+    (
+        Azure,
+        "southeastasia",
+        "Singapore",
+        "",
+        "SG",
+        1.35,
+        103.82,
+        "Asia/Singapore"
+    ),
+    (
+        Azure,
+        "japaneast",
+        "Tokyo",
+        "Tokyo",
+        "JP",
+        35.68,
+        139.69,
+        "Asia/Tokyo"
+    ),
+    (
+        Azure,
+        "japanwest",
+        "Osaka",
+        "Osaka",
+        "JP",
+        34.69,
+        135.5,
+        "Asia/Tokyo"
+    ),
+    (
+        Azure,
+        "koreacentral",
+        "Seoul",
+        "Seoul",
+        "KR",
+        37.57,
+        126.98,
+        "Asia/Seoul"
+    ),
+    (
+        Azure,
+        "koreasouth",
+        "Busan",
+        "Busan",
+        "KR",
+        35.18,
+        129.08,
+        "Asia/Seoul"
+    ),
+    (
+        Azure,
+        "centralindia",
+        "Pune",
+        "Maharashtra",
+        "IN",
+        18.52,
+        73.86,
+        "Asia/Kolkata"
+    ),
+    (
+        Azure,
+        "southindia",
+        "Chennai",
+        "Tamil Nadu",
+        "IN",
+        13.08,
+        80.27,
+        "Asia/Kolkata"
+    ),
+    (
+        Azure,
+        "westindia",
+        "Mumbai",
+        "Maharashtra",
+        "IN",
+        19.08,
+        72.88,
+        "Asia/Kolkata"
+    ),
+    (
+        Azure,
+        "indonesiacentral",
+        "Jakarta",
+        "Jakarta",
+        "ID",
+        -6.21,
+        106.85,
+        "Asia/Jakarta"
+    ),
+    (
+        Azure,
+        "malaysiawest",
+        "Kuala Lumpur",
+        "Kuala Lumpur",
+        "MY",
+        3.139,
+        101.687,
+        "Asia/Kuala_Lumpur"
+    ),
+    (
+        Azure,
+        "australiaeast",
+        "Sydney",
+        "New South Wales",
+        "AU",
+        -33.87,
+        151.21,
+        "Australia/Sydney"
+    ),
+    (
+        Azure,
+        "australiasoutheast",
+        "Melbourne",
+        "Victoria",
+        "AU",
+        -37.81,
+        144.96,
+        "Australia/Melbourne"
+    ),
+    (
+        Azure,
+        "australiacentral",
+        "Canberra",
+        "Australian Capital Territory",
+        "AU",
+        -35.28,
+        149.13,
+        "Australia/Sydney"
+    ),
+    (
+        Azure,
+        "newzealandnorth",
+        "Auckland",
+        "Auckland",
+        "NZ",
+        -36.85,
+        174.76,
+        "Pacific/Auckland"
+    ),
+    (
+        Azure,
+        "uaenorth",
+        "Dubai",
+        "Dubai",
+        "AE",
+        25.2,
+        55.27,
+        "Asia/Dubai"
+    ),
+    (
+        Azure,
+        "qatarcentral",
+        "Doha",
+        "Doha",
+        "QA",
+        25.29,
+        51.53,
+        "Asia/Qatar"
+    ),
+    (
+        Azure,
+        "israelcentral",
+        "Tel Aviv",
+        "Tel Aviv",
+        "IL",
+        32.09,
+        34.78,
+        "Asia/Jerusalem"
+    ),
+    (
+        Azure,
+        "southafricanorth",
+        "Johannesburg",
+        "Gauteng",
+        "ZA",
+        -26.2,
+        28.05,
+        "Africa/Johannesburg"
+    ),
+    (
+        Oracle,
+        "us-ashburn-1",
+        "Ashburn",
+        "Virginia",
+        "US",
+        39.04,
+        -77.49,
+        "America/New_York"
+    ),
+    (
+        Oracle,
+        "us-phoenix-1",
+        "Phoenix",
+        "Arizona",
+        "US",
+        33.45,
+        -112.07,
+        "America/Phoenix"
+    ),
+    (
+        Oracle,
+        "us-sanjose-1",
+        "San Jose",
+        "California",
+        "US",
+        37.34,
+        -121.89,
+        "America/Los_Angeles"
+    ),
+    (
+        Oracle,
+        "us-chicago-1",
+        "Chicago",
+        "Illinois",
+        "US",
+        41.88,
+        -87.63,
+        "America/Chicago"
+    ),
+    (
+        Oracle,
+        "ca-toronto-1",
+        "Toronto",
+        "Ontario",
+        "CA",
+        43.65,
+        -79.38,
+        "America/Toronto"
+    ),
+    (
+        Oracle,
+        "ca-montreal-1",
+        "Montreal",
+        "Quebec",
+        "CA",
+        45.5,
+        -73.57,
+        "America/Toronto"
+    ),
+    (
+        Oracle,
+        "mx-queretaro-1",
+        "Querétaro",
+        "Querétaro",
+        "MX",
+        20.59,
+        -100.39,
+        "America/Mexico_City"
+    ),
+    (
+        Oracle,
+        "mx-monterrey-1",
+        "Monterrey",
+        "Nuevo León",
+        "MX",
+        25.69,
+        -100.32,
+        "America/Monterrey"
+    ),
+    (
+        Oracle,
+        "sa-saopaulo-1",
+        "São Paulo",
+        "São Paulo",
+        "BR",
+        -23.55,
+        -46.63,
+        "America/Sao_Paulo"
+    ),
+    (
+        Oracle,
+        "sa-vinhedo-1",
+        "Vinhedo",
+        "São Paulo",
+        "BR",
+        -23.03,
+        -46.98,
+        "America/Sao_Paulo"
+    ),
+    (
+        Oracle,
+        "sa-santiago-1",
+        "Santiago",
+        "Santiago Metropolitan",
+        "CL",
+        -33.45,
+        -70.67,
+        "America/Santiago"
+    ),
+    (
+        Oracle,
+        "sa-valparaiso-1",
+        "Valparaíso",
+        "Valparaíso",
+        "CL",
+        -33.05,
+        -71.62,
+        "America/Santiago"
+    ),
+    (
+        Oracle,
+        "sa-bogota-1",
+        "Bogotá",
+        "Bogotá",
+        "CO",
+        4.71,
+        -74.07,
+        "America/Bogota"
+    ),
+    (
+        Oracle,
+        "uk-london-1",
+        "London",
+        "England",
+        "GB",
+        51.51,
+        -0.13,
+        "Europe/London"
+    ),
+    (
+        Oracle,
+        "uk-cardiff-1",
+        "Cardiff",
+        "Wales",
+        "GB",
+        51.48,
+        -3.18,
+        "Europe/London"
+    ),
+    (
+        Oracle,
+        "eu-frankfurt-1",
+        "Frankfurt",
+        "Hesse",
+        "DE",
+        50.11,
+        8.68,
+        "Europe/Berlin"
+    ),
+    (
+        Oracle,
+        "eu-amsterdam-1",
+        "Amsterdam",
+        "North Holland",
+        "NL",
+        52.37,
+        4.9,
+        "Europe/Amsterdam"
+    ),
+    (
+        Oracle,
+        "eu-zurich-1",
+        "Zurich",
+        "Zurich",
+        "CH",
+        47.37,
+        8.54,
+        "Europe/Zurich"
+    ),
+    (
+        Oracle,
+        "eu-paris-1",
+        "Paris",
+        "Île-de-France",
+        "FR",
+        48.86,
+        2.35,
+        "Europe/Paris"
+    ),
+    (
+        Oracle,
+        "eu-marseille-1",
+        "Marseille",
+        "Provence-Alpes-Côte d'Azur",
+        "FR",
+        43.3,
+        5.37,
+        "Europe/Paris"
+    ),
+    (
+        Oracle,
+        "eu-milan-1",
+        "Milan",
+        "Lombardy",
+        "IT",
+        45.46,
+        9.19,
+        "Europe/Rome"
+    ),
+    (
+        Oracle,
+        "eu-madrid-1",
+        "Madrid",
+        "Madrid",
+        "ES",
+        40.42,
+        -3.7,
+        "Europe/Madrid"
+    ),
+    (
+        Oracle,
+        "eu-stockholm-1",
+        "Stockholm",
+        "Stockholm",
+        "SE",
+        59.33,
+        18.07,
+        "Europe/Stockholm"
+    ),
+    (
+        Oracle,
+        "eu-jovanovac-1",
+        "Jovanovac",
+        "Šumadija",
+        "RS",
+        44.06,
+        20.92,
+        "Europe/Belgrade"
+    ),
+    (
+        Oracle,
+        "ap-tokyo-1",
+        "Tokyo",
+        "Tokyo",
+        "JP",
+        35.68,
+        139.69,
+        "Asia/Tokyo"
+    ),
+    (
+        Oracle,
+        "ap-osaka-1",
+        "Osaka",
+        "Osaka",
+        "JP",
+        34.69,
+        135.5,
+        "Asia/Tokyo"
+    ),
+    (
+        Oracle,
+        "ap-seoul-1",
+        "Seoul",
+        "Seoul",
+        "KR",
+        37.57,
+        126.98,
+        "Asia/Seoul"
+    ),
+    (
+        Oracle,
+        "ap-chuncheon-1",
+        "Chuncheon",
+        "Gangwon",
+        "KR",
+        37.88,
+        127.73,
+        "Asia/Seoul"
+    ),
+    (
+        Oracle,
+        "ap-mumbai-1",
+        "Mumbai",
+        "Maharashtra",
+        "IN",
+        19.08,
+        72.88,
+        "Asia/Kolkata"
+    ),
+    (
+        Oracle,
+        "ap-hyderabad-1",
+        "Hyderabad",
+        "Telangana",
+        "IN",
+        17.39,
+        78.49,
+        "Asia/Kolkata"
+    ),
+    (
+        Oracle,
+        "ap-singapore-1",
+        "Singapore",
+        "",
+        "SG",
+        1.35,
+        103.82,
+        "Asia/Singapore"
+    ),
+    (
+        Oracle,
+        "ap-sydney-1",
+        "Sydney",
+        "New South Wales",
+        "AU",
+        -33.87,
+        151.21,
+        "Australia/Sydney"
+    ),
+    (
+        Oracle,
+        "ap-melbourne-1",
+        "Melbourne",
+        "Victoria",
+        "AU",
+        -37.81,
+        144.96,
+        "Australia/Melbourne"
+    ),
+    (
+        Oracle,
+        "me-dubai-1",
+        "Dubai",
+        "Dubai",
+        "AE",
+        25.2,
+        55.27,
+        "Asia/Dubai"
+    ),
+    (
+        Oracle,
+        "me-abudhabi-1",
+        "Abu Dhabi",
+        "Abu Dhabi",
+        "AE",
+        24.45,
+        54.38,
+        "Asia/Dubai"
+    ),
+    (
+        Oracle,
+        "me-jeddah-1",
+        "Jeddah",
+        "Makkah",
+        "SA",
+        21.49,
+        39.19,
+        "Asia/Riyadh"
+    ),
+    (
+        Oracle,
+        "me-riyadh-1",
+        "Riyadh",
+        "Riyadh",
+        "SA",
+        24.71,
+        46.68,
+        "Asia/Riyadh"
+    ),
+    (
+        Oracle,
+        "il-jerusalem-1",
+        "Jerusalem",
+        "Jerusalem",
+        "IL",
+        31.77,
+        35.21,
+        "Asia/Jerusalem"
+    ),
+    (
+        Oracle,
+        "af-johannesburg-1",
+        "Johannesburg",
+        "Gauteng",
+        "ZA",
+        -26.2,
+        28.05,
+        "Africa/Johannesburg"
+    ),
+];
+
+/// Finds the documented location of a provider's region, ignoring case.
+pub fn region_location(provider: Provider, region: &str) -> Option<&'static RegionLocation> {
+    REGIONS.iter().find(|location| {
+        location.provider == provider && location.region.eq_ignore_ascii_case(region.trim())
+    })
+}
+
+/// Cloud provider ranges, loaded from the files the providers publish.
+///
+/// Cloning is cheap; clones share the loaded ranges.
+#[derive(Debug, Clone, Default)]
+pub struct CloudRanges {
+    table: Arc<Table>,
+    locate: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+struct Table {
+    /// The file names the ranges were loaded from.
+    files: Vec<String>,
+    /// Entries by range, most detailed first.
+    entries: HashMap<IpNet, Vec<Entry>>,
+    /// The prefix lengths that have entries, for each address family.
+    v4_lengths: BTreeSet<u8>,
+    v6_lengths: BTreeSet<u8>,
+}
+
+#[derive(Debug, Clone)]
+struct Entry {
+    cloud: Cloud,
+    file: usize,
+}
+
+impl CloudRanges {
+    /// Creates an empty set of ranges.
+    pub fn new() -> Self {
+        CloudRanges::default()
+    }
+
+    /// Loads AWS's `ip-ranges.json` at `path`.
+    pub fn aws<P: AsRef<Path>>(self, path: P) -> std::result::Result<Self, GeoError> {
+        self.add(path.as_ref(), Provider::Aws)
+    }
+
+    /// Loads Google Cloud's `cloud.json` at `path`.
+    pub fn gcp<P: AsRef<Path>>(self, path: P) -> std::result::Result<Self, GeoError> {
+        self.add(path.as_ref(), Provider::Gcp)
+    }
+
+    /// Loads an Azure Service Tags file at `path`.
+    pub fn azure<P: AsRef<Path>>(self, path: P) -> std::result::Result<Self, GeoError> {
+        self.add(path.as_ref(), Provider::Azure)
+    }
+
+    /// Loads one of Cloudflare's `ips-v4` or `ips-v6` lists at `path`.
+    pub fn cloudflare<P: AsRef<Path>>(self, path: P) -> std::result::Result<Self, GeoError> {
+        self.add(path.as_ref(), Provider::Cloudflare)
+    }
+
+    /// Loads Oracle Cloud's `public_ip_ranges.json` at `path`.
+    pub fn oracle<P: AsRef<Path>>(self, path: P) -> std::result::Result<Self, GeoError> {
+        self.add(path.as_ref(), Provider::Oracle)
+    }
+
+    /// Whether lookups in a known region get the region's documented location instead of
+    /// the one the service reported (see [`REGIONS`]). Defaults to `false`.
+    pub fn locate(mut self, enabled: bool) -> Self {
+        self.locate = enabled;
+        self
+    }
+
+    /// Finds the provider, service and region of `ip`, or `None` if it isn't in any of the
+    /// loaded ranges.
+    ///
+    /// The most specific range decides, with broader ranges of the same provider filling in
+    /// a service or region it doesn't name.
+    pub fn classify(&self, ip: IpAddr) -> Option<Cloud> {
+        self.lookup(ip).map(|(cloud, _)| cloud)
+    }
+
+    /// Like [`classify`](CloudRanges::classify), along with the name of the file the range
+    /// came from.
+    pub(crate) fn lookup(&self, ip: IpAddr) -> Option<(Cloud, &str)> {
+        let table = &self.table;
+
+        let lengths = match ip {
+            IpAddr::V4(_) => &table.v4_lengths,
+            IpAddr::V6(_) => &table.v6_lengths,
+        };
+
+        let mut found: Option<(Cloud, usize)> = None;
+        for &length in lengths.iter().rev() {
+            let range = match IpNet::new(ip, length) {
+                Ok(range) => range.trunc(),
+                Err(_) => continue,
+            };
+
+            for entry in table.entries.get(&range).into_iter().flatten() {
+                match &mut found {
+                    None => found = Some((entry.cloud.clone(), entry.file)),
+                    Some((cloud, _)) if cloud.provider == entry.cloud.provider => {
+                        cloud.fill(&entry.cloud)
+                    }
+                    Some(_) => (),
+                }
+            }
+        }
+
+        found.map(|(cloud, file)| (cloud, table.files[file].as_str()))
+    }
+
+    pub(crate) fn locates(&self) -> bool {
+        self.locate
+    }
+
+    fn add(mut self, path: &Path, provider: Provider) -> std::result::Result<Self, GeoError> {
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(error) => {
+                return Err(GeoError::ParseError(format!(
+                    "Couldn't read {}: {}",
+                    path.display(),
+                    error
+                )))
+            }
+        };
+
+        let ranges = match provider {
+            Provider::Cloudflare => parse_list(&contents, path)?,
+            _ => {
+                let json: Value =
+                    match serde_json::from_str(contents.trim_start_matches('\u{feff}')) {
+                        Ok(json) => json,
+                        Err(error) => {
+                            return Err(GeoError::ParseError(format!(
+                                "{} isn't valid JSON: {}",
+                                path.display(),
+                                error
+                            )))
+                        }
+                    };
+
+                parse_json(&json, provider, path)?
+            }
+        };
+
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| path.display().to_string());
+
+        debug!("loaded {} {} ranges from {}", ranges.len(), provider, name);
+
+        let table = Arc::make_mut(&mut self.table);
+        let file = table.files.len();
+        table.files.push(name);
+
+        for (range, cloud) in ranges {
+            match range {
+                IpNet::V4(_) => table.v4_lengths.insert(range.prefix_len()),
+                IpNet::V6(_) => table.v6_lengths.insert(range.prefix_len()),
+            };
+
+            let entries = table.entries.entry(range).or_default();
+            entries.push(Entry { cloud, file });
+            entries.sort_by_key(|entry| std::cmp::Reverse(entry.cloud.detail()));
+        }
+
+        Ok(self)
+    }
+}
+
+/// Reads the ranges out of one of the providers' JSON files.
+fn parse_json(
+    json: &Value,
+    provider: Provider,
+    path: &Path,
+) -> std::result::Result<Vec<(IpNet, Cloud)>, GeoError> {
+    let mut ranges = Vec::new();
+
+    let mut push = |range: &Value, service: Option<&str>, region: Option<&str>| {
+        let range = match range.as_str().and_then(parse_range) {
+            Some(range) => range,
+            None => {
+                return Err(GeoError::ParseError(format!(
+                    "{}: {} isn't a CIDR range",
+                    path.display(),
+                    range
+                )))
+            }
+        };
+
+        ranges.push((
+            range,
+            Cloud {
+                provider,
+                service: service.and_then(non_empty),
+                region: region.and_then(non_empty),
+            },
+        ));
+
+        Ok(())
+    };
+
+    match provider {
+        Provider::Aws => {
+            for (key, prefix) in [("prefixes", "ip_prefix"), ("ipv6_prefixes", "ipv6_prefix")] {
+                for entry in array(&json[key]) {
+                    // `AMAZON` covers every service; the other entries say which one.
+                    let service = entry["service"].as_str().filter(|&s| s != "AMAZON");
+                    push(&entry[prefix], service, entry["region"].as_str())?;
+                }
+            }
+        }
+        Provider::Gcp => {
+            for entry in array(&json["prefixes"]) {
+                let range = match entry.get("ipv4Prefix") {
+                    Some(range) => range,
+                    None => &entry["ipv6Prefix"],
+                };
+                push(range, entry["service"].as_str(), entry["scope"].as_str())?;
+            }
+        }
+        Provider::Azure => {
+            for tag in array(&json["values"]) {
+                let properties = &tag["properties"];
+                for range in array(&properties["addressPrefixes"]) {
+                    push(
+                        range,
+                        properties["systemService"].as_str(),
+                        properties["region"].as_str(),
+                    )?;
+                }
+            }
+        }
+        Provider::Oracle => {
+            for region in array(&json["regions"]) {
+                for entry in array(&region["cidrs"]) {
+                    let service = entry["tags"][0].as_str();
+                    push(&entry["cidr"], service, region["region"].as_str())?;
+                }
+            }
+        }
+        Provider::Cloudflare => (),
+    }
+
+    if ranges.is_empty() {
+        return Err(GeoError::ParseError(format!(
+            "{} has no {} ranges",
+            path.display(),
+            provider
+        )));
+    }
+
+    Ok(ranges)
+}
+
+/// The elements of a JSON array, or none if `value` isn't one.
+fn array(value: &Value) -> &[Value] {
+    value.as_array().map(Vec::as_slice).unwrap_or(&[])
+}
+
+/// Reads a plain list with one range per line.
+fn parse_list(contents: &str, path: &Path) -> std::result::Result<Vec<(IpNet, Cloud)>, GeoError> {
+    let mut ranges = Vec::new();
+
+    for (number, line) in contents.lines().enumerate() {
+        let entry = line.split('#').next().unwrap_or("").trim();
+        if entry.is_empty() {
+            continue;
+        }
+
+        match parse_range(entry) {
+            Some(range) => ranges.push((
+                range,
+                Cloud {
+                    provider: Provider::Cloudflare,
+                    service: None,
+                    region: None,
+                },
+            )),
+            None => {
+                return Err(GeoError::ParseError(format!(
+                    "{} line {}: {} isn't a CIDR range",
+                    path.display(),
+                    number + 1,
+                    entry
+                )))
+            }
+        }
+    }
+
+    Ok(ranges)
+}
+
+/// `Some` with the name, unless it's empty or means the provider's whole network.
+fn non_empty(value: &str) -> Option<String> {
+    match value.trim() {
+        "" => None,
+        value if value.eq_ignore_ascii_case("global") => None,
+        value => Some(value.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    const AWS: &str = r#"{"syncToken":"1","prefixes":[
+        {"ip_prefix":"3.5.140.0/22","region":"ap-northeast-2","service":"AMAZON"},
+        {"ip_prefix":"3.5.140.0/22","region":"ap-northeast-2","service":"S3"},
+        {"ip_prefix":"52.94.0.0/16","region":"GLOBAL","service":"AMAZON"},
+        {"ip_prefix":"52.94.5.0/24","region":"us-east-1","service":"AMAZON"},
+        {"ip_prefix":"52.94.5.0/28","region":"GLOBAL","service":"ROUTE53"}],
+        "ipv6_prefixes":[{"ipv6_prefix":"2600:1f00::/24","region":"GLOBAL","service":"AMAZON"}]}"#;
+
+    const GCP: &str = r#"{"prefixes":[
+        {"ipv4Prefix":"34.1.208.0/20","service":"Google Cloud","scope":"africa-south1"},
+        {"ipv6Prefix":"2600:1900:8000::/44","service":"Google Cloud","scope":"us-central1"}]}"#;
+
+    // Azure's files start with a byte order mark.
+    const AZURE: &str = "\u{feff}{\"values\":[\
+        {\"name\":\"Storage.WestEurope\",\"properties\":{\"region\":\"westeurope\",\
+        \"systemService\":\"AzureStorage\",\"addressPrefixes\":[\"20.38.108.0/23\"]}},\
+        {\"name\":\"AzureCloud\",\"properties\":{\"region\":\"\",\"systemService\":\"\",\
+        \"addressPrefixes\":[\"20.0.0.0/11\"]}}]}";
+
+    const ORACLE: &str = r#"{"regions":[{"region":"eu-frankfurt-1",
+        "cidrs":[{"cidr":"130.61.0.0/16","tags":["OCI"]}]}]}"#;
+
+    const CLOUDFLARE: &str = "173.245.48.0/20\n# comment\n\n2400:cb00::/32\n";
+
+    fn file(name: &str, contents: &str) -> PathBuf {
+        let directory =
+            std::env::temp_dir().join(format!("ipgeolocate-cloud-{}", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+
+        let path = directory.join(name);
+        fs::write(&path, contents).unwrap();
+        path
+    }
+
+    fn ranges() -> CloudRanges {
+        CloudRanges::new()
+            .aws(file("ip-ranges.json", AWS))
+            .unwrap()
+            .gcp(file("cloud.json", GCP))
+            .unwrap()
+            .azure(file("ServiceTags_Public.json", AZURE))
+            .unwrap()
+            .oracle(file("public_ip_ranges.json", ORACLE))
+            .unwrap()
+            .cloudflare(file("ips-v4", CLOUDFLARE))
+            .unwrap()
+    }
+
+    fn cloud(ranges: &CloudRanges, ip: &str) -> Option<(Provider, Option<String>, Option<String>)> {
+        ranges
+            .classify(ip.parse().unwrap())
+            .map(|cloud| (cloud.provider, cloud.service, cloud.region))
+    }
+
+    fn some(value: &str) -> Option<String> {
+        Some(value.to_string())
+    }
+
+    #[test]
+    fn classifies_each_provider() {
+        let ranges = ranges();
+
+        assert_eq!(
+            cloud(&ranges, "3.5.141.1"),
+            Some((Provider::Aws, some("S3"), some("ap-northeast-2")))
+        );
+        assert_eq!(
+            cloud(&ranges, "34.1.210.1"),
+            Some((Provider::Gcp, some("Google Cloud"), some("africa-south1")))
+        );
+        assert_eq!(
+            cloud(&ranges, "2600:1900:8000::1"),
+            Some((Provider::Gcp, some("Google Cloud"), some("us-central1")))
+        );
+        assert_eq!(
+            cloud(&ranges, "20.38.109.1"),
+            Some((Provider::Azure, some("AzureStorage"), some("westeurope")))
+        );
+        assert_eq!(
+            cloud(&ranges, "20.1.0.1"),
+            Some((Provider::Azure, None, None))
+        );
+        assert_eq!(
+            cloud(&ranges, "130.61.1.1"),
+            Some((Provider::Oracle, some("OCI"), some("eu-frankfurt-1")))
+        );
+        assert_eq!(
+            cloud(&ranges, "2400:cb00::1"),
+            Some((Provider::Cloudflare, None, None))
+        );
+        assert_eq!(cloud(&ranges, "1.1.1.1"), None);
+    }
+
+    #[test]
+    fn fills_in_from_broader_ranges() {
+        let ranges = ranges();
+
+        // AMAZON and GLOBAL don't name a service or region.
+        assert_eq!(
+            cloud(&ranges, "52.94.1.1"),
+            Some((Provider::Aws, None, None))
+        );
+        assert_eq!(
+            cloud(&ranges, "2600:1f00::1"),
+            Some((Provider::Aws, None, None))
+        );
+        // The /28 names the service, the /24 around it the region.
+        assert_eq!(
+            cloud(&ranges, "52.94.5.1"),
+            Some((Provider::Aws, some("ROUTE53"), some("us-east-1")))
+        );
+        assert_eq!(
+            cloud(&ranges, "52.94.5.100"),
+            Some((Provider::Aws, None, some("us-east-1")))
+        );
+    }
+
+    #[test]
+    fn reports_file_and_region_location() {
+        let ranges = ranges();
+
+        let (cloud, file) = ranges.lookup("130.61.1.1".parse().unwrap()).unwrap();
+        assert_eq!(file, "public_ip_ranges.json");
+
+        let location = cloud.location().unwrap();
+        assert_eq!(location.city, "Frankfurt");
+        assert_eq!(location.country, "DE");
+        assert_eq!(location.timezone, "Europe/Berlin");
+
+        assert!(region_location(Provider::Aws, "us-east-1").is_some());
+        assert!(region_location(Provider::Gcp, "us-east-1").is_none());
+    }
+
+    #[test]
+    fn rejects_bad_files() {
+        let bad_json = file("bad.json", "nope");
+        match CloudRanges::new().aws(&bad_json) {
+            Err(GeoError::ParseError(error)) => assert!(error.contains("isn't valid JSON")),
+            other => panic!("expected a parse error, got {:?}", other.map(|_| ())),
+        }
+
+        let empty = file("empty.json", r#"{"prefixes":[]}"#);
+        match CloudRanges::new().gcp(&empty) {
+            Err(GeoError::ParseError(error)) => {
+                assert!(error.contains("has no Google Cloud ranges"))
+            }
+            other => panic!("expected a parse error, got {:?}", other.map(|_| ())),
+        }
+
+        let bad_list = file("ips-v6", "2400:cb00::/32\nnope\n");
+        match CloudRanges::new().cloudflare(&bad_list) {
+            Err(GeoError::ParseError(error)) => assert!(error.contains("line 2")),
+            other => panic!("expected a parse error, got {:?}", other.map(|_| ())),
+        }
+    }
+}
@@ -0,0 +1,400 @@
+//! Discovering the host's own public IP addresses.
+//!
+//! A [`Discovery`] asks several echo services (which reply with the address a request
+//! came from) and, optionally, [STUN](crate::stun) servers over IPv4 and IPv6 separately,
+//! and only trusts an address that enough of them agree on.
+
+use crate::{stun, GeoError, TransportPolicy};
+use futures::future::join_all;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::time::Duration;
+use tracing::debug;
+
+/// Echo services used by default. Each replies with the caller's address as plain text
+/// over both IPv4 and IPv6.
+pub const DEFAULT_ECHO_SERVICES: &[&str] = &[
+    "https://icanhazip.com",
+    "https://api64.ipify.org",
+    "https://ifconfig.me/ip",
+];
+
+/// The host's public addresses. Either may be missing if the host lacks that connectivity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PublicAddresses {
+    /// The public IPv4 address.
+    pub ipv4: Option<Ipv4Addr>,
+    /// The public IPv6 address.
+    pub ipv6: Option<Ipv6Addr>,
+}
+
+/// Finds the host's public IP addresses by asking echo services.
+#[derive(Debug, Clone)]
+pub struct Discovery {
+    echo_services: Vec<String>,
+    stun_servers: Vec<String>,
+    quorum: usize,
+    timeout: Duration,
+}
+
+impl Default for Discovery {
+    fn default() -> Self {
+        Discovery {
+            echo_services: DEFAULT_ECHO_SERVICES
+                .iter()
+                .map(|url| url.to_string())
+                .collect(),
+            stun_servers: Vec::new(),
+            quorum: 2,
+            timeout: Duration::from_secs(5),
+        }
+    }
+}
+
+impl Discovery {
+    /// Creates a discovery using [`DEFAULT_ECHO_SERVICES`] and a quorum of two.
+    pub fn new() -> Self {
+        Discovery::default()
+    }
+
+    /// Replaces the echo services. Each URL must reply with just the caller's IP address,
+    /// like `http://ip-api.com/line/?fields=query`.
+    ///
+    /// [`Client::get_self`](crate::Client::get_self) skips plain HTTP services if the client's
+    /// [`TransportPolicy`] is `https_only`.
+    pub fn echo_services(mut self, urls: &[&str]) -> Self {
+        self.echo_services = urls.iter().map(|url| url.to_string()).collect();
+        self
+    }
+
+    /// Also asks these STUN servers (`host:port`), e.g. [`stun::DEFAULT_STUN_SERVERS`].
+    ///
+    /// STUN answers count towards the quorum the same way echo service answers do. Clear
+    /// the echo services to rely on STUN alone when HTTP goes through a proxy.
+    pub fn stun_servers(mut self, servers: &[&str]) -> Self {
+        self.stun_servers = servers.iter().map(|server| server.to_string()).collect();
+        self
+    }
+
+    /// How many sources have to agree on an address. Defaults to two.
+    ///
+    /// Discovery fails if there are fewer echo services and STUN servers than this.
+    pub fn quorum(mut self, quorum: usize) -> Self {
+        self.quorum = quorum.max(1);
+        self
+    }
+
+    /// How long to wait for each echo service or STUN server. Defaults to five seconds.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Finds both public addresses.
+    ///
+    /// Fails only if neither could be found.
+    pub async fn find(&self) -> std::result::Result<PublicAddresses, GeoError> {
+        self.find_with(&TransportPolicy::new()).await
+    }
+
+    /// [`find`](Discovery::find), asking the echo services as `transport` allows.
+    pub(crate) async fn find_with(
+        &self,
+        transport: &TransportPolicy,
+    ) -> std::result::Result<PublicAddresses, GeoError> {
+        let (ipv4, ipv6) = futures::join!(
+            self.consensus(IpAddr::V4(Ipv4Addr::UNSPECIFIED), transport),
+            self.consensus(IpAddr::V6(Ipv6Addr::UNSPECIFIED), transport)
+        );
+        let (ipv4, ipv6) = (ipv4.and_then(expect_ipv4), ipv6.and_then(expect_ipv6));
+
+        match (ipv4, ipv6) {
+            (Err(error), Err(_)) => Err(error),
+            (ipv4, ipv6) => Ok(PublicAddresses {
+                ipv4: ipv4.ok(),
+                ipv6: ipv6.ok(),
+            }),
+        }
+    }
+
+    /// Finds the public IPv4 address.
+    pub async fn ipv4(&self) -> std::result::Result<Ipv4Addr, GeoError> {
+        let local_address = IpAddr::V4(Ipv4Addr::UNSPECIFIED);
+        expect_ipv4(
+            self.consensus(local_address, &TransportPolicy::new())
+                .await?,
+        )
+    }
+
+    /// Finds the public IPv6 address.
+    pub async fn ipv6(&self) -> std::result::Result<Ipv6Addr, GeoError> {
+        let local_address = IpAddr::V6(Ipv6Addr::UNSPECIFIED);
+        expect_ipv6(
+            self.consensus(local_address, &TransportPolicy::new())
+                .await?,
+        )
+    }
+
+    /// Asks every source from `local_address`'s family and returns the most common answer.
+    ///
+    /// Echo services that `transport` forbids are skipped.
+    async fn consensus(
+        &self,
+        local_address: IpAddr,
+        transport: &TransportPolicy,
+    ) -> std::result::Result<IpAddr, GeoError> {
+        let sources = self.echo_services.len() + self.stun_servers.len();
+        if sources < self.quorum {
+            return Err(GeoError::DiscoveryError(format!(
+                "A quorum of {} needs at least {} sources, but only {} are configured",
+                self.quorum, self.quorum, sources
+            )));
+        }
+
+        let client = match transport
+            .builder(None)?
+            .local_address(local_address)
+            .timeout(self.timeout)
+            .build()
+        {
+            Ok(client) => client,
+            Err(error) => {
+                return Err(GeoError::HttpError(format!(
+                    "Couldn't create HTTP client: {}",
+                    error
+                )))
+            }
+        };
+
+        let requests = self
+            .echo_services
+            .iter()
+            .map(|url| echo(&client, transport, url));
+
+        // STUN servers get a shorter first wait since requests are retried with backoff.
+        let bindings = self
+            .stun_servers
+            .iter()
+            .map(|server| stun::binding(server, local_address, self.timeout / 4));
+
+        let (echoes, bindings) = futures::join!(join_all(requests), join_all(bindings));
+
+        let mut answers: Vec<IpAddr> = echoes.into_iter().flatten().collect();
+        for binding in bindings {
+            match binding {
+                Ok(address) => answers.push(address.ip()),
+                Err(error) => debug!("{}", error),
+            }
+        }
+
+        tally(&answers, self.quorum, sources)
+    }
+}
+
+/// The address at least `quorum` of the `sources` answered with, if no other address got as
+/// many answers.
+fn tally(
+    answers: &[IpAddr],
+    quorum: usize,
+    sources: usize,
+) -> std::result::Result<IpAddr, GeoError> {
+    // Counted in the order the answers came in, so the error messages are stable.
+    let mut votes: Vec<(IpAddr, usize)> = Vec::new();
+    for answer in answers {
+        match votes.iter_mut().find(|(ip, _)| ip == answer) {
+            Some((_, count)) => *count += 1,
+            None => votes.push((*answer, 1)),
+        }
+    }
+
+    debug!("sources answered {:?}", votes);
+
+    let most = match votes.iter().map(|(_, count)| *count).max() {
+        Some(most) => most,
+        None => {
+            return Err(GeoError::DiscoveryError(
+                "No echo service or STUN server could be reached".to_string(),
+            ))
+        }
+    };
+
+    let leaders: Vec<IpAddr> = votes
+        .iter()
+        .filter(|(_, count)| *count == most)
+        .map(|(ip, _)| *ip)
+        .collect();
+
+    if leaders.len() > 1 {
+        let leaders: Vec<String> = leaders.iter().map(|ip| ip.to_string()).collect();
+        return Err(GeoError::DiscoveryError(format!(
+            "Sources were split between {}",
+            leaders.join(" and ")
+        )));
+    }
+
+    if most < quorum {
+        return Err(GeoError::DiscoveryError(format!(
+            "Only {} of {} sources agreed on an address",
+            most, sources
+        )));
+    }
+
+    Ok(leaders[0])
+}
+
+fn expect_ipv4(ip: IpAddr) -> std::result::Result<Ipv4Addr, GeoError> {
+    match ip {
+        IpAddr::V4(ip) => Ok(ip),
+        IpAddr::V6(ip) => Err(GeoError::DiscoveryError(format!(
+            "Echo services answered with {} over IPv4",
+            ip
+        ))),
+    }
+}
+
+fn expect_ipv6(ip: IpAddr) -> std::result::Result<Ipv6Addr, GeoError> {
+    match ip {
+        IpAddr::V6(ip) => Ok(ip),
+        IpAddr::V4(ip) => Err(GeoError::DiscoveryError(format!(
+            "Echo services answered with {} over IPv6",
+            ip
+        ))),
+    }
+}
+
+async fn echo(client: &reqwest::Client, transport: &TransportPolicy, url: &str) -> Option<IpAddr> {
+    if let Err(error) = transport.check(url) {
+        debug!("skipping echo service: {}", error);
+        return None;
+    }
+
+    let response = client.get(url).send().await.ok()?;
+    let body = response.text().await.ok()?;
+
+    match body.trim().parse() {
+        Ok(ip) => Some(ip),
+        Err(_) => {
+            debug!("{} didn't answer with an IP address", url);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// A local plain HTTP echo service that always answers with `body`.
+    async fn echo_service(body: &'static str) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+
+        tokio::spawn(async move {
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut request = Vec::new();
+                let mut buffer = [0; 1024];
+                while !request.ends_with(b"\r\n\r\n") {
+                    match stream.read(&mut buffer).await {
+                        Ok(0) | Err(_) => break,
+                        Ok(read) => request.extend_from_slice(&buffer[..read]),
+                    }
+                }
+
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+                let _ = stream.write_all(response.as_bytes()).await;
+            }
+        });
+
+        url
+    }
+
+    fn ip(ip: &str) -> IpAddr {
+        ip.parse().unwrap()
+    }
+
+    fn discovery_error(result: std::result::Result<IpAddr, GeoError>) -> String {
+        match result {
+            Err(GeoError::DiscoveryError(error)) => error,
+            other => panic!("expected a discovery error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn agrees_on_the_most_common_answer() {
+        let answers = [ip("192.0.2.1"), ip("198.51.100.1"), ip("192.0.2.1")];
+        assert_eq!(tally(&answers, 2, 3).unwrap(), ip("192.0.2.1"));
+        assert_eq!(tally(&answers[..1], 1, 1).unwrap(), ip("192.0.2.1"));
+    }
+
+    #[test]
+    fn refuses_disagreement_below_quorum() {
+        let answers = [ip("192.0.2.1"), ip("198.51.100.1"), ip("192.0.2.1")];
+        let error = discovery_error(tally(&answers, 3, 3));
+        assert_eq!(error, "Only 2 of 3 sources agreed on an address");
+    }
+
+    #[test]
+    fn refuses_ties() {
+        let answers = [
+            ip("192.0.2.1"),
+            ip("198.51.100.1"),
+            ip("198.51.100.1"),
+            ip("192.0.2.1"),
+        ];
+        let error = discovery_error(tally(&answers, 2, 4));
+        assert_eq!(
+            error,
+            "Sources were split between 192.0.2.1 and 198.51.100.1"
+        );
+
+        let error = discovery_error(tally(&answers[..2], 1, 2));
+        assert!(error.contains("split"));
+    }
+
+    #[test]
+    fn refuses_missing_answers() {
+        let error = discovery_error(tally(&[], 2, 3));
+        assert!(error.contains("could be reached"));
+    }
+
+    #[tokio::test]
+    async fn refuses_quorum_above_sources() {
+        let discovery = Discovery::new()
+            .echo_services(&["https://icanhazip.com", "https://api64.ipify.org"])
+            .quorum(3);
+        let local_address = IpAddr::V4(Ipv4Addr::UNSPECIFIED);
+
+        let error = discovery_error(
+            discovery
+                .consensus(local_address, &TransportPolicy::new())
+                .await,
+        );
+        assert!(error.contains("only 2 are configured"));
+    }
+
+    #[tokio::test]
+    async fn skips_plain_http_when_https_only() {
+        let first = echo_service("203.0.113.5\n").await;
+        let second = echo_service("203.0.113.5\n").await;
+        let discovery = Discovery::new().echo_services(&[&first, &second]);
+        let local_address = IpAddr::V4(Ipv4Addr::UNSPECIFIED);
+
+        let found = discovery
+            .consensus(local_address, &TransportPolicy::new())
+            .await
+            .unwrap();
+        assert_eq!(found, IpAddr::V4(Ipv4Addr::new(203, 0, 113, 5)));
+
+        let https_only = TransportPolicy::new().https_only(true);
+        match discovery.consensus(local_address, &https_only).await {
+            Err(GeoError::DiscoveryError(error)) => assert!(error.contains("could be reached")),
+            other => panic!("expected no answers, got {:?}", other),
+        }
+    }
+}
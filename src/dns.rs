@@ -0,0 +1,204 @@
+//! Hostname resolution for looking up domains.
+//!
+//! A [`Resolver`] turns a hostname into its A and AAAA records so each address can be
+//! geolocated. It uses the system's DNS configuration unless it's pointed at specific
+//! name servers, such as a local DNS server in tests.
+
+use crate::GeoError;
+use hickory_resolver::config::{
+    LookupIpStrategy, NameServerConfig, Protocol, ResolverConfig, ResolverOpts,
+};
+use hickory_resolver::system_conf::read_system_conf;
+use hickory_resolver::TokioAsyncResolver;
+use std::net::{IpAddr, SocketAddr};
+use tracing::debug;
+
+/// Where hostnames are resolved.
+#[derive(Debug, Clone, Default)]
+pub struct Resolver {
+    name_servers: Vec<SocketAddr>,
+}
+
+impl Resolver {
+    /// Uses the system's DNS configuration (`/etc/resolv.conf` on Unix).
+    pub fn system() -> Self {
+        Resolver::default()
+    }
+
+    /// Sends queries to `name_servers` over UDP instead of the system's name servers.
+    pub fn with_name_servers(name_servers: &[SocketAddr]) -> Self {
+        Resolver {
+            name_servers: name_servers.to_vec(),
+        }
+    }
+
+    /// Resolves the A and AAAA records of `host`.
+    ///
+    /// IP address literals are returned as they are without a query.
+    pub async fn resolve(&self, host: &str) -> std::result::Result<Vec<IpAddr>, GeoError> {
+        if let Ok(ip) = host.parse() {
+            return Ok(vec![ip]);
+        }
+
+        let (config, mut options) = if self.name_servers.is_empty() {
+            match read_system_conf() {
+                Ok(system) => system,
+                Err(error) => {
+                    return Err(GeoError::DnsError(format!(
+                        "Couldn't read system DNS configuration: {}",
+                        error
+                    )))
+                }
+            }
+        } else {
+            let mut config = ResolverConfig::new();
+            for name_server in &self.name_servers {
+                config.add_name_server(NameServerConfig::new(*name_server, Protocol::Udp));
+            }
+
+            (config, ResolverOpts::default())
+        };
+
+        // Ask for both record types rather than stopping at the first one that answers.
+        options.ip_strategy = LookupIpStrategy::Ipv4AndIpv6;
+        let resolver = TokioAsyncResolver::tokio(config, options);
+
+        let addresses: Vec<IpAddr> = match resolver.lookup_ip(host).await {
+            Ok(lookup) => lookup.iter().collect(),
+            Err(error) => {
+                return Err(GeoError::DnsError(format!(
+                    "Couldn't resolve {}: {}",
+                    host, error
+                )))
+            }
+        };
+
+        debug!("{} resolved to {:?}", host, addresses);
+
+        if addresses.is_empty() {
+            return Err(GeoError::DnsError(format!(
+                "{} has no A or AAAA records",
+                host
+            )));
+        }
+
+        Ok(addresses)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::{Ipv4Addr, Ipv6Addr};
+    use tokio::net::UdpSocket;
+
+    const A: u16 = 1;
+    const AAAA: u16 = 28;
+
+    /// A local DNS stand-in: `dual.test` has A and AAAA records, `v4.test` only an A record,
+    /// and every other name exists with no records.
+    async fn name_server() -> SocketAddr {
+        let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let address = socket.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            let mut query = [0; 512];
+            loop {
+                let (length, peer) = socket.recv_from(&mut query).await.unwrap();
+                if let Some(response) = respond(&query[..length]) {
+                    socket.send_to(&response, peer).await.unwrap();
+                }
+            }
+        });
+
+        address
+    }
+
+    fn respond(query: &[u8]) -> Option<Vec<u8>> {
+        // The question starts after the 12-byte header: labels, then the type and class.
+        let mut labels = Vec::new();
+        let mut position = 12;
+        loop {
+            let length = *query.get(position)? as usize;
+            position += 1;
+            if length == 0 {
+                break;
+            }
+            labels.push(String::from_utf8_lossy(
+                query.get(position..position + length)?,
+            ));
+            position += length;
+        }
+        let record_type = u16::from_be_bytes([*query.get(position)?, *query.get(position + 1)?]);
+        let question = &query[12..position + 4];
+
+        let records: Vec<Vec<u8>> = match (labels.join(".").as_str(), record_type) {
+            ("dual.test", A) | ("v4.test", A) => {
+                vec![Ipv4Addr::new(192, 0, 2, 1).octets().to_vec()]
+            }
+            ("dual.test", AAAA) => vec![
+                "2001:db8::1".parse::<Ipv6Addr>().unwrap().octets().to_vec(),
+                "2001:db8::2".parse::<Ipv6Addr>().unwrap().octets().to_vec(),
+            ],
+            _ => Vec::new(),
+        };
+
+        let mut response = Vec::new();
+        response.extend_from_slice(&query[..2]);
+        response.extend_from_slice(&[0x81, 0x80, 0, 1]);
+        response.extend_from_slice(&(records.len() as u16).to_be_bytes());
+        response.extend_from_slice(&[0, 0, 0, 0]);
+        response.extend_from_slice(question);
+
+        for record in records {
+            // A pointer to the name in the question, then type, class IN and a TTL of 60.
+            response.extend_from_slice(&[0xc0, 0x0c]);
+            response.extend_from_slice(&record_type.to_be_bytes());
+            response.extend_from_slice(&[0, 1, 0, 0, 0, 60]);
+            response.extend_from_slice(&(record.len() as u16).to_be_bytes());
+            response.extend_from_slice(&record);
+        }
+
+        Some(response)
+    }
+
+    #[tokio::test]
+    async fn merges_a_and_aaaa_records() {
+        let resolver = Resolver::with_name_servers(&[name_server().await]);
+
+        let mut addresses = resolver.resolve("dual.test").await.unwrap();
+        addresses.sort();
+
+        let expected: Vec<IpAddr> = vec![
+            "192.0.2.1".parse().unwrap(),
+            "2001:db8::1".parse().unwrap(),
+            "2001:db8::2".parse().unwrap(),
+        ];
+        assert_eq!(addresses, expected);
+
+        let addresses = resolver.resolve("v4.test").await.unwrap();
+        assert_eq!(addresses, vec!["192.0.2.1".parse::<IpAddr>().unwrap()]);
+    }
+
+    #[tokio::test]
+    async fn fails_without_records() {
+        let resolver = Resolver::with_name_servers(&[name_server().await]);
+
+        match resolver.resolve("empty.test").await {
+            Err(GeoError::DnsError(error)) => assert!(error.contains("empty.test")),
+            other => panic!("expected a DNS error, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn returns_literals_without_a_query() {
+        // Nothing listens on this port, so a query would fail.
+        let resolver = Resolver::with_name_servers(&["127.0.0.1:9".parse().unwrap()]);
+
+        let addresses = resolver.resolve("2606:4700:4700::1111").await.unwrap();
+        assert_eq!(
+            addresses,
+            vec!["2606:4700:4700::1111".parse::<IpAddr>().unwrap()]
+        );
+    }
+}
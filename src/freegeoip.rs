@@ -0,0 +1,130 @@
+//! The [freegeoip.app](https://freegeoip.app/) service's response.
+
+use crate::lenient::Field;
+use crate::{Client, GeoError, Locator};
+use serde::Deserialize;
+use serde_json::Number;
+
+/// A freegeoip.app response.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct Response {
+    pub latitude: Option<Number>,
+    pub longitude: Option<Number>,
+    pub city: Option<String>,
+    pub region_name: Option<String>,
+    pub region_code: Option<String>,
+    pub country_name: Option<String>,
+    pub country_code: Option<String>,
+    pub time_zone: Option<String>,
+    pub zip_code: Option<String>,
+    /// Nielsen DMA code, 0 for addresses outside a metro area.
+    pub metro_code: Option<u16>,
+}
+
+impl Response {
+    pub(crate) fn locator(
+        self,
+        ip: &str,
+        client: &Client,
+    ) -> std::result::Result<Locator, GeoError> {
+        let mut warnings = Vec::new();
+
+        let latitude = self.latitude.map(|latitude| latitude.to_string());
+        let latitude = client.required(latitude, Field::Latitude, &mut warnings)?;
+        let longitude = self.longitude.map(|longitude| longitude.to_string());
+        let longitude = client.required(longitude, Field::Longitude, &mut warnings)?;
+        let city = client.required(self.city, Field::City, &mut warnings)?;
+        let region = client.required(self.region_name, Field::Region, &mut warnings)?;
+        let country = client.required(self.country_name, Field::Country, &mut warnings)?;
+        let timezone = client.required(self.time_zone, Field::Timezone, &mut warnings)?;
+
+        Ok(Locator {
+            ip: ip.to_string(),
+            latitude,
+            longitude,
+            city,
+            region,
+            country,
+            country_code: self.country_code.unwrap_or_default(),
+            region_code: self.region_code.unwrap_or_default(),
+            timezone,
+            postal: self.zip_code.unwrap_or_default(),
+            metro_code: self.metro_code.filter(|&code| code != 0),
+            warnings,
+            ..Locator::default()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{decode, Service};
+    use serde_json::{json, Value};
+
+    fn google() -> Value {
+        json!({
+            "ip": "8.8.8.8",
+            "country_code": "US",
+            "country_name": "United States",
+            "region_code": "CA",
+            "region_name": "California",
+            "city": "Mountain View",
+            "zip_code": "94043",
+            "time_zone": "America/Los_Angeles",
+            "latitude": 37.4223,
+            "longitude": -122.085,
+            "metro_code": 807
+        })
+    }
+
+    fn locate(body: &Value, client: &Client) -> std::result::Result<Locator, GeoError> {
+        let response: Response = decode(body, "freegeoip.app").unwrap();
+        response.locator("8.8.8.8", client)
+    }
+
+    #[test]
+    fn reads_response() {
+        let location = locate(&google(), &Client::new(Service::FreeGeoIp)).unwrap();
+
+        assert_eq!(location.latitude, "37.4223");
+        assert_eq!(location.longitude, "-122.085");
+        assert_eq!(location.city, "Mountain View");
+        assert_eq!(location.region, "California");
+        assert_eq!(location.region_code, "CA");
+        assert_eq!(location.country_code, "US");
+        assert_eq!(location.timezone, "America/Los_Angeles");
+        assert_eq!(location.postal, "94043");
+        assert_eq!(location.metro_code, Some(807));
+    }
+
+    #[test]
+    fn drops_zero_metro_code() {
+        let mut body = google();
+        body["metro_code"] = json!(0);
+
+        let location = locate(&body, &Client::new(Service::FreeGeoIp)).unwrap();
+        assert_eq!(location.metro_code, None);
+    }
+
+    #[test]
+    fn reads_error_response() {
+        // A response without coordinates can't be placed on a map, so the lookup fails.
+        let body = json!({
+            "ip": "10.0.0.1",
+            "country_code": "",
+            "country_name": "",
+            "region_code": "",
+            "region_name": "",
+            "city": "",
+            "zip_code": "",
+            "time_zone": "",
+            "metro_code": 0
+        });
+
+        match locate(&body, &Client::new(Service::FreeGeoIp)) {
+            Err(GeoError::ParseError(error)) => assert!(error.contains("latitude")),
+            other => panic!("expected a missing latitude, got {:?}", other),
+        }
+    }
+}
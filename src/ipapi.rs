@@ -0,0 +1,437 @@
+//! Field selection and responses for the [ip-api.com](https://ip-api.com/) service.
+//!
+//! ip-api.com returns a fixed set of fields unless it's told which ones to send. Pass a
+//! selection to [`Client::ipapi_fields`](crate::Client::ipapi_fields) to get extra fields like
+//! [`Field::Proxy`] or [`Field::Reverse`], or to trim the response down to what you need.
+//! Fields beyond the ones on [`Locator`] end up in [`Details`].
+
+use crate::capabilities;
+use crate::threat::Flags;
+use crate::{lenient, network, Client, GeoError, Locator, Network, Service};
+use serde::Deserialize;
+use serde_json::Number;
+use std::fmt;
+
+/// A field ip-api.com can return.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Field {
+    Continent,
+    ContinentCode,
+    Country,
+    CountryCode,
+    /// Region or state code, like `CA`.
+    Region,
+    /// Region or state name, like `California`.
+    RegionName,
+    City,
+    /// District (subdivision of city).
+    District,
+    Zip,
+    Lat,
+    Lon,
+    Timezone,
+    /// Timezone UTC offset in seconds.
+    Offset,
+    Currency,
+    Isp,
+    Org,
+    /// AS number and organization, like `AS13335 Cloudflare, Inc.`.
+    As,
+    AsName,
+    /// Reverse DNS of the address. Slows the lookup down.
+    Reverse,
+    /// Whether the address is on a mobile (cellular) connection.
+    Mobile,
+    /// Whether the address is a proxy, VPN or Tor exit.
+    Proxy,
+    /// Whether the address belongs to a hosting provider or data center.
+    Hosting,
+    /// The address that was looked up.
+    Query,
+}
+
+/// The fields needed for everything on [`Locator`].
+pub const LOCATION: &[Field] = &[
+    Field::Country,
+    Field::CountryCode,
+    Field::RegionName,
+    Field::Region,
+    Field::City,
+    Field::Zip,
+    Field::Lat,
+    Field::Lon,
+    Field::Timezone,
+    Field::Isp,
+];
+
+/// The network the address belongs to and how it connects.
+pub const NETWORK: &[Field] = &[
+    Field::Isp,
+    Field::Org,
+    Field::As,
+    Field::AsName,
+    Field::Mobile,
+    Field::Proxy,
+    Field::Hosting,
+];
+
+/// Every field ip-api.com has.
+pub const ALL: &[Field] = &[
+    Field::Continent,
+    Field::ContinentCode,
+    Field::Country,
+    Field::CountryCode,
+    Field::Region,
+    Field::RegionName,
+    Field::City,
+    Field::District,
+    Field::Zip,
+    Field::Lat,
+    Field::Lon,
+    Field::Timezone,
+    Field::Offset,
+    Field::Currency,
+    Field::Isp,
+    Field::Org,
+    Field::As,
+    Field::AsName,
+    Field::Reverse,
+    Field::Mobile,
+    Field::Proxy,
+    Field::Hosting,
+    Field::Query,
+];
+
+impl Field {
+    /// Whether the field is reported in [`Details`].
+    pub fn in_details(&self) -> bool {
+        !matches!(
+            self,
+            Field::Country
+                | Field::RegionName
+                | Field::City
+                | Field::Zip
+                | Field::Lat
+                | Field::Lon
+                | Field::Timezone
+                | Field::Isp
+                | Field::Query
+        )
+    }
+}
+
+impl fmt::Display for Field {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Field::Continent => "continent",
+            Field::ContinentCode => "continentCode",
+            Field::Country => "country",
+            Field::CountryCode => "countryCode",
+            Field::Region => "region",
+            Field::RegionName => "regionName",
+            Field::City => "city",
+            Field::District => "district",
+            Field::Zip => "zip",
+            Field::Lat => "lat",
+            Field::Lon => "lon",
+            Field::Timezone => "timezone",
+            Field::Offset => "offset",
+            Field::Currency => "currency",
+            Field::Isp => "isp",
+            Field::Org => "org",
+            Field::As => "as",
+            Field::AsName => "asname",
+            Field::Reverse => "reverse",
+            Field::Mobile => "mobile",
+            Field::Proxy => "proxy",
+            Field::Hosting => "hosting",
+            Field::Query => "query",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+/// Fields ip-api.com returns beyond the ones on [`Locator`].
+///
+/// Only returned when the selection includes one of them (see [`Field::in_details`]). A field
+/// is `None` when it wasn't selected.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Details {
+    pub continent: Option<String>,
+    pub continent_code: Option<String>,
+    pub country_code: Option<String>,
+    /// Region or state code, like `CA`.
+    pub region: Option<String>,
+    pub district: Option<String>,
+    /// Timezone UTC offset in seconds.
+    pub offset: Option<i64>,
+    pub currency: Option<String>,
+    pub org: Option<String>,
+    /// AS number and organization, like `AS13335 Cloudflare, Inc.`.
+    #[serde(rename = "as")]
+    pub asn: Option<String>,
+    #[serde(rename = "asname")]
+    pub as_name: Option<String>,
+    pub reverse: Option<String>,
+    pub mobile: Option<bool>,
+    pub proxy: Option<bool>,
+    pub hosting: Option<bool>,
+}
+
+/// An ip-api.com response.
+///
+/// A field is `None` when it wasn't selected, or when the lookup failed.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Response {
+    /// `success` or `fail`.
+    pub status: Option<String>,
+    /// Why the lookup failed, like `reserved range`.
+    pub message: Option<String>,
+    pub lat: Option<Number>,
+    pub lon: Option<Number>,
+    pub city: Option<String>,
+    pub region_name: Option<String>,
+    /// Region or state code, like `CA`.
+    pub region: Option<String>,
+    pub country: Option<String>,
+    pub country_code: Option<String>,
+    pub timezone: Option<String>,
+    pub isp: Option<String>,
+    pub zip: Option<String>,
+    /// `AS13335 Cloudflare, Inc.`
+    #[serde(rename = "as")]
+    pub asn: Option<String>,
+    #[serde(rename = "asname")]
+    pub as_name: Option<String>,
+    pub org: Option<String>,
+    #[serde(flatten)]
+    pub flags: Flags,
+}
+
+impl Response {
+    pub(crate) fn locator(
+        self,
+        ip: &str,
+        client: &Client,
+    ) -> std::result::Result<Locator, GeoError> {
+        let mut warnings = Vec::new();
+
+        // Fields left out of a selection are left empty rather than treated as missing
+        let fields = client.fields();
+        let mut required = |value: Option<String>, field: Field, name: lenient::Field| match value {
+            Some(value) => Ok(value),
+            None if fields.is_some_and(|fields| !fields.contains(&field)) => Ok(String::default()),
+            None => client.missing(name, &mut warnings),
+        };
+
+        let latitude = self.lat.map(|latitude| latitude.to_string());
+        let latitude = required(latitude, Field::Lat, lenient::Field::Latitude)?;
+        let longitude = self.lon.map(|longitude| longitude.to_string());
+        let longitude = required(longitude, Field::Lon, lenient::Field::Longitude)?;
+        let city = required(self.city, Field::City, lenient::Field::City)?;
+        let region = required(self.region_name, Field::RegionName, lenient::Field::Region)?;
+        let country = required(self.country, Field::Country, lenient::Field::Country)?;
+        let timezone = required(self.timezone, Field::Timezone, lenient::Field::Timezone)?;
+        let isp = required(self.isp, Field::Isp, lenient::Field::Isp)?;
+
+        // `as` is "AS13335 Cloudflare, Inc."; `asname` is only sent when selected
+        let (asn, as_name) = match &self.asn {
+            Some(autonomous_system) => network::split_as(autonomous_system),
+            None => (None, None),
+        };
+
+        let network = Network {
+            asn,
+            as_name: match &self.as_name {
+                Some(as_name) => network::non_empty(as_name),
+                None => as_name,
+            },
+            organization: self.org.as_deref().and_then(network::non_empty),
+            ..Network::default()
+        };
+
+        Ok(Locator {
+            ip: ip.to_string(),
+            latitude,
+            longitude,
+            city,
+            region,
+            country,
+            country_code: self.country_code.unwrap_or_default(),
+            region_code: self.region.unwrap_or_default(),
+            timezone,
+            isp,
+            postal: self.zip.unwrap_or_default(),
+            network: network.non_empty(),
+            security: self.flags.security(Service::IpApi),
+            warnings,
+            ..Locator::default()
+        })
+    }
+}
+
+/// The [`Details`] in a response, if the client's selection includes any of them.
+pub(crate) fn details(
+    parsed_json: &serde_json::Value,
+    client: &Client,
+) -> std::result::Result<Option<Details>, GeoError> {
+    let selected = client
+        .fields()
+        .is_some_and(|fields| fields.iter().any(Field::in_details));
+
+    if selected {
+        crate::decode(parsed_json, "ip-api.com").map(Some)
+    } else {
+        Ok(None)
+    }
+}
+
+/// The `fields` query parameter for `fields`, including the ones needed to report errors.
+pub(crate) fn query(fields: &[Field]) -> String {
+    let mut names = vec!["status".to_string(), "message".to_string()];
+    for field in fields {
+        let name = field.to_string();
+        if !names.contains(&name) {
+            names.push(name);
+        }
+    }
+
+    names.join(",")
+}
+
+/// The fields to select so ip-api.com sends everything on [`Locator`], its network details
+/// and each of `wanted`.
+pub(crate) fn selection(wanted: &[capabilities::Field]) -> Vec<Field> {
+    let mut fields = LOCATION.to_vec();
+
+    for &field in NETWORK {
+        if !fields.contains(&field) {
+            fields.push(field);
+        }
+    }
+
+    for wanted in wanted {
+        let needed: &[Field] = match wanted {
+            capabilities::Field::Latitude => &[Field::Lat],
+            capabilities::Field::Longitude => &[Field::Lon],
+            capabilities::Field::City => &[Field::City],
+            capabilities::Field::Region => &[Field::RegionName],
+            capabilities::Field::RegionCode => &[Field::Region],
+            capabilities::Field::Country => &[Field::Country],
+            capabilities::Field::CountryCode => &[Field::CountryCode],
+            capabilities::Field::Timezone => &[Field::Timezone],
+            capabilities::Field::Isp => &[Field::Isp],
+            capabilities::Field::Postal => &[Field::Zip],
+            capabilities::Field::MetroCode => &[],
+            capabilities::Field::Network => &[Field::As, Field::AsName, Field::Org],
+            capabilities::Field::Security => &[Field::Proxy, Field::Hosting],
+        };
+
+        for &field in needed {
+            if !fields.contains(&field) {
+                fields.push(field);
+            }
+        }
+    }
+
+    fields
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decode;
+    use serde_json::{json, Value};
+
+    fn cloudflare() -> Value {
+        json!({
+            "status": "success",
+            "country": "Australia",
+            "countryCode": "AU",
+            "region": "QLD",
+            "regionName": "Queensland",
+            "city": "South Brisbane",
+            "zip": "4101",
+            "lat": -27.4766,
+            "lon": 153.0166,
+            "timezone": "Australia/Brisbane",
+            "isp": "Cloudflare, Inc",
+            "org": "APNIC and Cloudflare DNS Resolver project",
+            "as": "AS13335 Cloudflare, Inc.",
+            "query": "1.1.1.1"
+        })
+    }
+
+    fn locate(body: &Value, client: &Client) -> std::result::Result<Locator, GeoError> {
+        let response: Response = decode(body, "ip-api.com").unwrap();
+        response.locator("1.1.1.1", client)
+    }
+
+    #[test]
+    fn builds_query() {
+        assert_eq!(
+            query(&[Field::Country, Field::Proxy, Field::Country]),
+            "status,message,country,proxy"
+        );
+        assert_eq!(query(&[]), "status,message");
+    }
+
+    #[test]
+    fn selects_locator_fields() {
+        let fields = selection(&[]);
+        for field in LOCATION.iter().chain(NETWORK) {
+            assert!(fields.contains(field), "{} isn't selected", field);
+        }
+        assert!(!fields.contains(&Field::Reverse));
+
+        let fields = selection(&[capabilities::Field::MetroCode, capabilities::Field::Postal]);
+        assert_eq!(fields.len(), selection(&[]).len());
+    }
+
+    #[test]
+    fn reads_default_response() {
+        let client = Client::new(Service::IpApi);
+        let location = locate(&cloudflare(), &client).unwrap();
+
+        assert_eq!(location.latitude, "-27.4766");
+        assert_eq!(location.country_code, "AU");
+        assert_eq!(location.region_code, "QLD");
+        assert_eq!(location.postal, "4101");
+
+        let network = location.network.unwrap();
+        assert_eq!(network.asn, Some(13335));
+        assert_eq!(network.as_name.as_deref(), Some("Cloudflare, Inc."));
+
+        assert_eq!(details(&cloudflare(), &client).unwrap(), None);
+    }
+
+    #[test]
+    fn reads_trimmed_selection() {
+        let body = json!({"status": "success", "country": "Australia", "proxy": false});
+
+        let client = Client::new(Service::IpApi).ipapi_fields(&[Field::Country, Field::Proxy]);
+        let location = locate(&body, &client).unwrap();
+        assert_eq!(location.country, "Australia");
+        assert_eq!(location.city, "");
+        assert!(location.warnings.is_empty());
+        assert_eq!(details(&body, &client).unwrap().unwrap().proxy, Some(false));
+
+        // Without a selection, the same fields are missing rather than left out.
+        assert!(locate(&body, &Client::new(Service::IpApi)).is_err());
+
+        let client = Client::new(Service::IpApi).ipapi_fields(&[Field::Country, Field::City]);
+        assert_eq!(details(&body, &client).unwrap(), None);
+    }
+
+    #[test]
+    fn fills_locator_from_location_selection() {
+        let client = Client::new(Service::IpApi).ipapi_fields(LOCATION);
+        let location = locate(&cloudflare(), &client).unwrap();
+
+        assert_eq!(location.country_code, "AU");
+        assert_eq!(location.region_code, "QLD");
+    }
+}
@@ -0,0 +1,170 @@
+//! The [ipapi.co](https://ipapi.co/) service's response.
+
+use crate::lenient::Field;
+use crate::threat::Flags;
+use crate::{network, Client, GeoError, Locator, Network, Service};
+use serde::Deserialize;
+use serde_json::Number;
+
+/// An ipapi.co response.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct Response {
+    /// Set on failed lookups, including invalid keys.
+    #[serde(default)]
+    pub error: bool,
+    /// Why the lookup failed.
+    pub reason: Option<String>,
+    pub latitude: Option<Number>,
+    pub longitude: Option<Number>,
+    pub city: Option<String>,
+    pub region: Option<String>,
+    pub region_code: Option<String>,
+    pub country_name: Option<String>,
+    pub country_code: Option<String>,
+    pub timezone: Option<String>,
+    pub postal: Option<String>,
+    /// The AS number, like `AS13335`.
+    pub asn: Option<String>,
+    /// The name of the autonomous system.
+    pub org: Option<String>,
+    /// Only sent on paid plans.
+    pub security: Option<Flags>,
+}
+
+impl Response {
+    pub(crate) fn locator(
+        self,
+        ip: &str,
+        client: &Client,
+    ) -> std::result::Result<Locator, GeoError> {
+        // Errors, including invalid keys, come back as `{"error": true, "reason": "..."}`
+        if self.error {
+            return match self.reason {
+                Some(reason) => Err(GeoError::HttpError(format!(
+                    "ipapi.co returned an error: {}",
+                    reason
+                ))),
+                None => Err(GeoError::HttpError(
+                    "ipapi.co returned an error".to_string(),
+                )),
+            };
+        }
+
+        let mut warnings = Vec::new();
+
+        let latitude = self.latitude.map(|latitude| latitude.to_string());
+        let latitude = client.required(latitude, Field::Latitude, &mut warnings)?;
+        let longitude = self.longitude.map(|longitude| longitude.to_string());
+        let longitude = client.required(longitude, Field::Longitude, &mut warnings)?;
+        let city = client.required(self.city, Field::City, &mut warnings)?;
+        let region = client.required(self.region, Field::Region, &mut warnings)?;
+        let country = client.required(self.country_name, Field::Country, &mut warnings)?;
+        let timezone = client.required(self.timezone, Field::Timezone, &mut warnings)?;
+
+        let network = Network {
+            asn: self.asn.as_deref().and_then(network::parse_asn),
+            as_name: self.org.as_deref().and_then(network::non_empty),
+            ..Network::default()
+        };
+
+        Ok(Locator {
+            ip: ip.to_string(),
+            latitude,
+            longitude,
+            city,
+            region,
+            country,
+            country_code: self.country_code.unwrap_or_default(),
+            region_code: self.region_code.unwrap_or_default(),
+            timezone,
+            postal: self.postal.unwrap_or_default(),
+            network: network.non_empty(),
+            security: self.security.unwrap_or_default().security(Service::IpApiCo),
+            warnings,
+            ..Locator::default()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decode;
+    use serde_json::{json, Value};
+
+    fn cloudflare() -> Value {
+        json!({
+            "ip": "1.1.1.1",
+            "network": "1.1.1.0/24",
+            "version": "IPv4",
+            "city": "Sydney",
+            "region": "New South Wales",
+            "region_code": "NSW",
+            "country": "AU",
+            "country_name": "Australia",
+            "country_code": "AU",
+            "country_code_iso3": "AUS",
+            "postal": "2000",
+            "latitude": -33.8688,
+            "longitude": 151.209,
+            "timezone": "Australia/Sydney",
+            "utc_offset": "+1100",
+            "asn": "AS13335",
+            "org": "CLOUDFLARENET"
+        })
+    }
+
+    fn locate(body: &Value, client: &Client) -> std::result::Result<Locator, GeoError> {
+        let response: Response = decode(body, "ipapi.co").unwrap();
+        response.locator("1.1.1.1", client)
+    }
+
+    #[test]
+    fn reads_response() {
+        let location = locate(&cloudflare(), &Client::new(Service::IpApiCo)).unwrap();
+
+        assert_eq!(location.latitude, "-33.8688");
+        assert_eq!(location.longitude, "151.209");
+        assert_eq!(location.city, "Sydney");
+        assert_eq!(location.region_code, "NSW");
+        assert_eq!(location.country, "Australia");
+        assert_eq!(location.timezone, "Australia/Sydney");
+        assert_eq!(location.postal, "2000");
+
+        let network = location.network.unwrap();
+        assert_eq!(network.asn, Some(13335));
+        assert_eq!(network.as_name.as_deref(), Some("CLOUDFLARENET"));
+
+        // Security data is only sent on paid plans.
+        assert!(location.security.proxy.source.is_none());
+    }
+
+    #[test]
+    fn reads_paid_security() {
+        let mut body = cloudflare();
+        body["security"] = json!({"proxy": false, "vpn": true, "tor": false, "hosting": true});
+
+        let client = Client::new(Service::IpApiCo).api_key("key");
+        let location = locate(&body, &client).unwrap();
+
+        assert!(location.security.vpn.flag.is_yes());
+        assert!(location.security.hosting.flag.is_yes());
+        assert!(!location.security.tor.flag.is_yes());
+    }
+
+    #[test]
+    fn reads_error_response() {
+        let body = json!({"ip": "1.1.1.1", "error": true, "reason": "RateLimited"});
+
+        match locate(&body, &Client::new(Service::IpApiCo)) {
+            Err(GeoError::HttpError(error)) => assert!(error.ends_with("RateLimited")),
+            other => panic!("expected an error, got {:?}", other),
+        }
+
+        let body = json!({"error": true});
+        assert!(matches!(
+            locate(&body, &Client::new(Service::IpApiCo)),
+            Err(GeoError::HttpError(_))
+        ));
+    }
+}
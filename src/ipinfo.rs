@@ -0,0 +1,461 @@
+//! The [ipinfo.io](https://ipinfo.io/) service.
+//!
+//! ipinfo.io works without a token at a low rate limit; paid plans need an access
+//! token (see [`Client::api_key`](crate::Client::api_key)) and add the `asn`, `company`,
+//! `privacy` and `abuse` objects collected in [`Details`].
+
+use crate::lenient::Field;
+use crate::threat::Flags;
+use crate::{network, Client, GeoError, Locator, Network, Security, Service};
+use reqwest::header::{HeaderMap, CONTENT_TYPE};
+use reqwest::StatusCode;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// The most addresses ipinfo.io accepts in one batch request.
+pub const BATCH_LIMIT: usize = 1000;
+
+/// Fields ipinfo.io returns beyond the ones on [`Locator`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct Details {
+    /// Reverse DNS hostname.
+    pub hostname: Option<String>,
+    /// `AS<number> <name>` of the network, on every plan.
+    pub org: Option<String>,
+    /// Whether the address is a bogon (private, reserved, or unallocated).
+    #[serde(default)]
+    pub bogon: bool,
+    pub asn: Option<Asn>,
+    pub company: Option<Company>,
+    pub privacy: Option<Privacy>,
+    pub abuse: Option<Abuse>,
+}
+
+/// An ipinfo.io response.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct Response {
+    /// `latitude,longitude`
+    pub loc: Option<String>,
+    pub city: Option<String>,
+    pub region: Option<String>,
+    /// The ISO 3166-1 alpha-2 code of the country.
+    pub country: Option<String>,
+    pub timezone: Option<String>,
+    pub postal: Option<String>,
+    #[serde(flatten)]
+    pub details: Details,
+}
+
+impl Response {
+    fn locator(self, ip: &str, client: &Client) -> std::result::Result<Locator, GeoError> {
+        let mut warnings = Vec::new();
+
+        let (latitude, longitude) = match self.loc.as_deref().and_then(|loc| loc.split_once(',')) {
+            Some((latitude, longitude)) => (latitude.to_string(), longitude.to_string()),
+            None => (
+                client.missing(Field::Latitude, &mut warnings)?,
+                client.missing(Field::Longitude, &mut warnings)?,
+            ),
+        };
+        let city = client.required(self.city, Field::City, &mut warnings)?;
+        let region = client.required(self.region, Field::Region, &mut warnings)?;
+        let country = client.required(self.country, Field::Country, &mut warnings)?;
+        let timezone = client.required(self.timezone, Field::Timezone, &mut warnings)?;
+
+        let details = self.details;
+
+        // Prefer the `asn` object's name, then `org` without its "AS<number> " prefix.
+        let isp = match (&details.asn, &details.org) {
+            (Some(asn), _) => asn.name.clone(),
+            (None, Some(org)) => match org.split_once(' ') {
+                Some((number, name)) if number.starts_with("AS") => name.to_string(),
+                _ => org.clone(),
+            },
+            (None, None) => String::default(),
+        };
+
+        let mut network = Network::default();
+        if let Some(asn) = &details.asn {
+            network.asn = network::parse_asn(&asn.asn);
+            network.as_name = network::non_empty(&asn.name);
+            network.prefix = asn.route.as_deref().and_then(|route| route.parse().ok());
+        }
+        if let Some(org) = &details.org {
+            let (asn, organization) = network::split_as(org);
+            network.asn = network.asn.or(asn);
+            network.organization = organization;
+        }
+        if let Some(company) = &details.company {
+            network.organization = network::non_empty(&company.name).or(network.organization);
+        }
+
+        let security = match &details.privacy {
+            Some(privacy) => Flags {
+                proxy: Some(privacy.proxy),
+                vpn: Some(privacy.vpn),
+                tor: Some(privacy.tor),
+                hosting: Some(privacy.hosting),
+            }
+            .security(Service::IpInfo),
+            None => Security::default(),
+        };
+
+        Ok(Locator {
+            ip: ip.to_string(),
+            latitude,
+            longitude,
+            city,
+            region,
+            country_code: country.clone(),
+            country,
+            timezone,
+            isp,
+            postal: self.postal.unwrap_or_default(),
+            network: network.non_empty(),
+            security,
+            ipinfo: Some(details),
+            warnings,
+            ..Locator::default()
+        })
+    }
+}
+
+/// The `asn` object: the autonomous system announcing the address.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct Asn {
+    /// The AS number, like `AS13335`.
+    pub asn: String,
+    pub name: String,
+    pub domain: Option<String>,
+    /// The announced prefix containing the address.
+    pub route: Option<String>,
+    /// `isp`, `business`, `education` or `hosting`.
+    #[serde(rename = "type")]
+    pub kind: Option<String>,
+}
+
+/// The `company` object: the organisation using the address.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct Company {
+    pub name: String,
+    pub domain: Option<String>,
+    /// `isp`, `business`, `education` or `hosting`.
+    #[serde(rename = "type")]
+    pub kind: Option<String>,
+}
+
+/// The `privacy` object: whether the address is used to hide its user's location.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct Privacy {
+    #[serde(default)]
+    pub vpn: bool,
+    #[serde(default)]
+    pub proxy: bool,
+    #[serde(default)]
+    pub tor: bool,
+    #[serde(default)]
+    pub relay: bool,
+    #[serde(default)]
+    pub hosting: bool,
+    /// The name of the VPN or privacy service, if known.
+    pub service: Option<String>,
+}
+
+/// The `abuse` object: who to contact about abuse from the address.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct Abuse {
+    pub address: Option<String>,
+    pub country: Option<String>,
+    pub email: Option<String>,
+    pub name: Option<String>,
+    pub network: Option<String>,
+    pub phone: Option<String>,
+}
+
+pub(crate) async fn get(ip: &str, client: &Client) -> std::result::Result<Locator, GeoError> {
+    let url = format!("https://ipinfo.io/{}/json", ip);
+
+    let mut request = client.http(&url)?.get(&url);
+    if let Some(token) = client.key() {
+        request = request.bearer_auth(token);
+    }
+
+    let (status, headers, parsed_json) = send(request).await?;
+
+    let mut location = parse(ip, &parsed_json, client)?;
+    location.raw = client.raw(status, &headers, &parsed_json);
+
+    Ok(location)
+}
+
+/// Looks up to [`BATCH_LIMIT`] addresses in one request.
+pub(crate) async fn batch(
+    ips: &[String],
+    client: &Client,
+) -> std::result::Result<BTreeMap<String, std::result::Result<Locator, GeoError>>, GeoError> {
+    let body = match serde_json::to_string(ips) {
+        Ok(body) => body,
+        Err(error) => {
+            return Err(GeoError::ParseError(format!(
+                "Couldn't encode batch request: {}",
+                error
+            )))
+        }
+    };
+
+    let url = "https://ipinfo.io/batch";
+
+    let mut request = client
+        .http(url)?
+        .post(url)
+        .header(CONTENT_TYPE, "application/json")
+        .body(body);
+    if let Some(token) = client.key() {
+        request = request.bearer_auth(token);
+    }
+
+    let (status, headers, parsed_json) = send(request).await?;
+
+    split_batch(ips, &parsed_json, status, &headers, client)
+}
+
+/// Parses each of `ips` out of a batch response, which maps addresses to single responses.
+fn split_batch(
+    ips: &[String],
+    parsed_json: &Value,
+    status: StatusCode,
+    headers: &HeaderMap,
+    client: &Client,
+) -> std::result::Result<BTreeMap<String, std::result::Result<Locator, GeoError>>, GeoError> {
+    let results = match parsed_json {
+        Value::Object(results) => results,
+        _ => {
+            return Err(GeoError::ParseError(
+                "Unable to find batch results in parsed JSON".to_string(),
+            ))
+        }
+    };
+
+    let mut locations = BTreeMap::new();
+    for ip in ips {
+        let location = match results.get(ip) {
+            Some(result) => parse(ip, result, client).map(|mut location| {
+                location.raw = client.raw(status, headers, result);
+                location
+            }),
+            None => Err(GeoError::ParseError(format!(
+                "Unable to find {} in batch results",
+                ip
+            ))),
+        };
+
+        locations.insert(ip.clone(), location);
+    }
+
+    Ok(locations)
+}
+
+/// Sends `request`, returning the response's status and headers with its decoded body.
+async fn send(
+    request: reqwest::RequestBuilder,
+) -> std::result::Result<(StatusCode, HeaderMap, Value), GeoError> {
+    let response = match request.send().await {
+        Ok(response) => response,
+        Err(_) => {
+            return Err(GeoError::HttpError(
+                "Couldn't connect to ipinfo.io".to_string(),
+            ))
+        }
+    };
+
+    let status = response.status();
+    let headers = response.headers().clone();
+
+    let response_body = match response.text().await {
+        Ok(response_body) => response_body,
+        Err(_) => {
+            return Err(GeoError::HttpError(
+                "Couldn't read data from ipinfo.io".to_string(),
+            ))
+        }
+    };
+
+    if status == StatusCode::TOO_MANY_REQUESTS {
+        return Err(GeoError::HttpError(
+            "You've hit the ipinfo.io rate limit".to_string(),
+        ));
+    }
+
+    // Turn the data into parsed_json
+    let parsed_json: Value = match serde_json::from_str(&response_body) {
+        Ok(parsed_json) => parsed_json,
+        Err(error) => {
+            return Err(GeoError::ParseError(format!(
+                "Couldn't parse json: {}",
+                error
+            )));
+        }
+    };
+
+    if let Some(message) = error_message(&parsed_json) {
+        return Err(GeoError::HttpError(format!(
+            "ipinfo.io returned an error: {}",
+            message
+        )));
+    }
+
+    if !status.is_success() {
+        return Err(GeoError::HttpError(format!(
+            "ipinfo.io returned {}",
+            status
+        )));
+    }
+
+    Ok((status, headers, parsed_json))
+}
+
+fn error_message(parsed_json: &Value) -> Option<String> {
+    match &parsed_json["error"] {
+        Value::Object(error) => match (&error.get("title"), &error.get("message")) {
+            (_, Some(Value::String(message))) => Some(message.clone()),
+            (Some(Value::String(title)), _) => Some(title.clone()),
+            _ => Some("unknown error".to_string()),
+        },
+        Value::String(message) => Some(message.clone()),
+        _ => None,
+    }
+}
+
+fn parse(ip: &str, parsed_json: &Value, client: &Client) -> std::result::Result<Locator, GeoError> {
+    if let Some(message) = error_message(parsed_json) {
+        return Err(GeoError::HttpError(format!(
+            "ipinfo.io returned an error: {}",
+            message
+        )));
+    }
+
+    let response: Response = crate::decode(parsed_json, "ipinfo.io")?;
+
+    if response.details.bogon {
+        return Err(GeoError::ParseError(format!(
+            "ipinfo.io says {} is a bogon address",
+            ip
+        )));
+    }
+
+    response.locator(ip, client)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn cloudflare() -> Value {
+        json!({
+            "ip": "1.1.1.1",
+            "hostname": "one.one.one.one",
+            "city": "Brisbane",
+            "region": "Queensland",
+            "country": "AU",
+            "loc": "-27.4816,153.0175",
+            "org": "AS13335 Cloudflare, Inc.",
+            "postal": "4101",
+            "timezone": "Australia/Brisbane",
+            "anycast": true
+        })
+    }
+
+    #[test]
+    fn reads_single_response() {
+        let client = Client::new(Service::IpInfo);
+        let location = parse("1.1.1.1", &cloudflare(), &client).unwrap();
+
+        assert_eq!(location.latitude, "-27.4816");
+        assert_eq!(location.longitude, "153.0175");
+        assert_eq!(location.city, "Brisbane");
+        assert_eq!(location.country_code, "AU");
+        assert_eq!(location.isp, "Cloudflare, Inc.");
+        assert_eq!(location.postal, "4101");
+
+        let network = location.network.unwrap();
+        assert_eq!(network.asn, Some(13335));
+        assert_eq!(network.organization.as_deref(), Some("Cloudflare, Inc."));
+
+        let details = location.ipinfo.unwrap();
+        assert_eq!(details.hostname.as_deref(), Some("one.one.one.one"));
+        assert!(details.privacy.is_none());
+    }
+
+    #[test]
+    fn reads_paid_objects() {
+        let mut body = cloudflare();
+        body["asn"] = json!({
+            "asn": "AS13335",
+            "name": "Cloudflare, Inc.",
+            "domain": "cloudflare.com",
+            "route": "1.1.1.0/24",
+            "type": "hosting"
+        });
+        body["company"] = json!({"name": "APNIC and Cloudflare DNS Resolver project"});
+        body["privacy"] = json!({
+            "vpn": false,
+            "proxy": false,
+            "tor": false,
+            "relay": false,
+            "hosting": true,
+            "service": ""
+        });
+
+        let client = Client::new(Service::IpInfo).api_key("token");
+        let location = parse("1.1.1.1", &body, &client).unwrap();
+
+        let network = location.network.unwrap();
+        assert_eq!(network.as_name.as_deref(), Some("Cloudflare, Inc."));
+        assert_eq!(network.prefix, Some("1.1.1.0/24".parse().unwrap()));
+        assert_eq!(
+            network.organization.as_deref(),
+            Some("APNIC and Cloudflare DNS Resolver project")
+        );
+        assert!(location.security.hosting.flag.is_yes());
+        assert!(!location.security.vpn.flag.is_yes());
+    }
+
+    #[test]
+    fn reads_batch_response() {
+        let ips: Vec<String> = ["1.1.1.1", "10.0.0.1", "999.0.0.1", "8.8.8.8"]
+            .iter()
+            .map(|ip| ip.to_string())
+            .collect();
+        let body = json!({
+            "1.1.1.1": cloudflare(),
+            "10.0.0.1": {"ip": "10.0.0.1", "bogon": true},
+            "999.0.0.1": {"error": {"title": "Wrong ip", "message": "Please provide a valid IP address"}}
+        });
+
+        let client = Client::new(Service::IpInfo).keep_raw(true);
+        let results = split_batch(&ips, &body, StatusCode::OK, &HeaderMap::new(), &client).unwrap();
+
+        let location = results["1.1.1.1"].as_ref().unwrap();
+        assert_eq!(location.city, "Brisbane");
+        assert_eq!(location.raw.as_ref().unwrap().body, cloudflare());
+
+        match &results["10.0.0.1"] {
+            Err(GeoError::ParseError(error)) => assert!(error.contains("bogon")),
+            other => panic!("expected a bogon, got {:?}", other),
+        }
+        match &results["999.0.0.1"] {
+            Err(GeoError::HttpError(error)) => {
+                assert!(error.contains("Please provide a valid IP address"))
+            }
+            other => panic!("expected an error, got {:?}", other),
+        }
+        match &results["8.8.8.8"] {
+            Err(GeoError::ParseError(error)) => assert!(error.contains("Unable to find")),
+            other => panic!("expected a missing result, got {:?}", other),
+        }
+
+        assert!(split_batch(&ips, &json!([]), StatusCode::OK, &HeaderMap::new(), &client).is_err());
+    }
+}
@@ -0,0 +1,230 @@
+//! The [ipwhois.app](https://ipwhois.app/) service's responses.
+//!
+//! The free ipwhois.app and the paid ipwhois.pro answer in different shapes. ipwhois.app sends
+//! a flat [`Response`] with the coordinates as strings. ipwhois.pro sends a [`ProResponse`]
+//! with numeric coordinates, the time zone as a [`Timezone`] object, the network and ISP in a
+//! [`Connection`], and the postal code and [`Flags`] under `security`.
+
+use crate::lenient::Field;
+use crate::threat::Flags;
+use crate::{network, Client, GeoError, Locator, Network, Service};
+use serde::Deserialize;
+use serde_json::Number;
+
+/// An ipwhois.app response.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct Response {
+    pub success: bool,
+    /// Why the lookup failed, like `Invalid API key`.
+    pub message: Option<String>,
+    pub latitude: Option<String>,
+    pub longitude: Option<String>,
+    pub city: Option<String>,
+    pub region: Option<String>,
+    pub country: Option<String>,
+    pub country_code: Option<String>,
+    pub timezone: Option<String>,
+    pub postal: Option<String>,
+    /// The AS number, like `AS13335`.
+    pub asn: Option<String>,
+    pub org: Option<String>,
+    pub isp: Option<String>,
+}
+
+/// An ipwhois.pro response.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct ProResponse {
+    pub success: bool,
+    /// Why the lookup failed, like `Invalid API key`.
+    pub message: Option<String>,
+    pub latitude: Option<Number>,
+    pub longitude: Option<Number>,
+    pub city: Option<String>,
+    pub region: Option<String>,
+    pub country: Option<String>,
+    pub country_code: Option<String>,
+    pub timezone: Option<Timezone>,
+    pub postal: Option<String>,
+    pub connection: Option<Connection>,
+    pub security: Option<Flags>,
+}
+
+/// The `timezone` object on ipwhois.pro.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct Timezone {
+    /// The tz database name, like `America/Denver`.
+    pub id: Option<String>,
+}
+
+/// The `connection` object on ipwhois.pro.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct Connection {
+    pub asn: Option<u32>,
+    pub org: Option<String>,
+    pub isp: Option<String>,
+}
+
+impl Response {
+    pub(crate) fn locator(
+        self,
+        ip: &str,
+        client: &Client,
+    ) -> std::result::Result<Locator, GeoError> {
+        let mut warnings = Vec::new();
+
+        let latitude = client.required(self.latitude, Field::Latitude, &mut warnings)?;
+        let longitude = client.required(self.longitude, Field::Longitude, &mut warnings)?;
+        let city = client.required(self.city, Field::City, &mut warnings)?;
+        let region = client.required(self.region, Field::Region, &mut warnings)?;
+        let country = client.required(self.country, Field::Country, &mut warnings)?;
+        let timezone = client.required(self.timezone, Field::Timezone, &mut warnings)?;
+
+        let network = Network {
+            asn: self.asn.as_deref().and_then(network::parse_asn),
+            organization: self.org.as_deref().and_then(network::non_empty),
+            ..Network::default()
+        };
+
+        Ok(Locator {
+            ip: ip.to_string(),
+            latitude,
+            longitude,
+            city,
+            region,
+            country,
+            country_code: self.country_code.unwrap_or_default(),
+            timezone,
+            isp: self.isp.unwrap_or_default(),
+            postal: self.postal.unwrap_or_default(),
+            network: network.non_empty(),
+            warnings,
+            ..Locator::default()
+        })
+    }
+}
+
+impl ProResponse {
+    pub(crate) fn locator(
+        self,
+        ip: &str,
+        client: &Client,
+    ) -> std::result::Result<Locator, GeoError> {
+        let mut warnings = Vec::new();
+
+        let latitude = self.latitude.map(|latitude| latitude.to_string());
+        let latitude = client.required(latitude, Field::Latitude, &mut warnings)?;
+        let longitude = self.longitude.map(|longitude| longitude.to_string());
+        let longitude = client.required(longitude, Field::Longitude, &mut warnings)?;
+        let city = client.required(self.city, Field::City, &mut warnings)?;
+        let region = client.required(self.region, Field::Region, &mut warnings)?;
+        let country = client.required(self.country, Field::Country, &mut warnings)?;
+        let timezone = self.timezone.and_then(|timezone| timezone.id);
+        let timezone = client.required(timezone, Field::Timezone, &mut warnings)?;
+
+        let connection = self.connection.unwrap_or_default();
+        let isp = client.required(connection.isp, Field::Isp, &mut warnings)?;
+        let network = Network {
+            asn: connection.asn,
+            organization: connection.org.as_deref().and_then(network::non_empty),
+            ..Network::default()
+        };
+
+        Ok(Locator {
+            ip: ip.to_string(),
+            latitude,
+            longitude,
+            city,
+            region,
+            country,
+            country_code: self.country_code.unwrap_or_default(),
+            timezone,
+            isp,
+            postal: self.postal.unwrap_or_default(),
+            network: network.non_empty(),
+            security: self.security.unwrap_or_default().security(Service::IpWhois),
+            warnings,
+            ..Locator::default()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decode;
+    use serde_json::json;
+
+    #[test]
+    fn reads_ipwhois_app_response() {
+        let body = json!({
+            "ip": "8.8.8.8",
+            "success": true,
+            "latitude": "37.3860517",
+            "longitude": "-122.0838511",
+            "city": "Mountain View",
+            "region": "California",
+            "country": "United States",
+            "country_code": "US",
+            "timezone": "America/Los_Angeles",
+            "asn": "AS15169",
+            "org": "Google LLC",
+            "isp": "Google LLC"
+        });
+
+        let response: Response = decode(&body, "ipwhois.app").unwrap();
+        let client = Client::new(Service::IpWhois);
+        let location = response.locator("8.8.8.8", &client).unwrap();
+
+        assert_eq!(location.latitude, "37.3860517");
+        assert_eq!(location.timezone, "America/Los_Angeles");
+        assert_eq!(location.isp, "Google LLC");
+        assert_eq!(location.network.unwrap().asn, Some(15169));
+    }
+
+    #[test]
+    fn reads_ipwhois_pro_response() {
+        let body = json!({
+            "ip": "8.8.8.8",
+            "success": true,
+            "latitude": 37.3860517,
+            "longitude": -122.0838511,
+            "city": "Mountain View",
+            "region": "California",
+            "country": "United States",
+            "country_code": "US",
+            "postal": "94039",
+            "connection": {
+                "asn": 15169,
+                "org": "Google LLC",
+                "isp": "Google LLC",
+                "domain": "google.com"
+            },
+            "timezone": {
+                "id": "America/Los_Angeles",
+                "abbr": "PDT",
+                "is_dst": true,
+                "offset": -25200,
+                "utc": "-07:00"
+            },
+            "security": {
+                "anonymous": false,
+                "proxy": false,
+                "vpn": false,
+                "tor": false,
+                "hosting": true
+            }
+        });
+
+        let response: ProResponse = decode(&body, "ipwhois.pro").unwrap();
+        let client = Client::new(Service::IpWhois).api_key("key");
+        let location = response.locator("8.8.8.8", &client).unwrap();
+
+        assert_eq!(location.latitude, "37.3860517");
+        assert_eq!(location.longitude, "-122.0838511");
+        assert_eq!(location.timezone, "America/Los_Angeles");
+        assert_eq!(location.isp, "Google LLC");
+        assert_eq!(location.postal, "94039");
+        assert_eq!(location.network.unwrap().asn, Some(15169));
+        assert!(location.security.hosting.flag.is_yes());
+    }
+}
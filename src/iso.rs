@@ -0,0 +1,734 @@
+//! ISO 3166 country and subdivision codes.
+//!
+//! Services disagree on how they name places: ipinfo.io sends `US` where ip-api.com sends
+//! `United States`, and regions come back as `California` or `CA`. Every [`Locator`] is run
+//! through [`normalize`] so that [`country_code`](Locator::country_code),
+//! [`country_code3`](Locator::country_code3) and [`region_code`](Locator::region_code) can be
+//! compared across services.
+//!
+//! All ISO 3166-1 countries are bundled, along with their continent, currency, calling code and
+//! EU/EEA membership (see [`Locator::country_info`]). ISO 3166-2 subdivisions are bundled for a
+//! few countries; elsewhere the region code is only known when the service sends one.
+
+use crate::Locator;
+use std::fmt;
+
+/// A continent, as used by the country table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Continent {
+    Africa,
+    Antarctica,
+    Asia,
+    Europe,
+    NorthAmerica,
+    Oceania,
+    SouthAmerica,
+}
+
+impl Continent {
+    /// The two-letter continent code, like `NA` for North America.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Continent::Africa => "AF",
+            Continent::Antarctica => "AN",
+            Continent::Asia => "AS",
+            Continent::Europe => "EU",
+            Continent::NorthAmerica => "NA",
+            Continent::Oceania => "OC",
+            Continent::SouthAmerica => "SA",
+        }
+    }
+}
+
+impl fmt::Display for Continent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Continent::Africa => "Africa",
+            Continent::Antarctica => "Antarctica",
+            Continent::Asia => "Asia",
+            Continent::Europe => "Europe",
+            Continent::NorthAmerica => "North America",
+            Continent::Oceania => "Oceania",
+            Continent::SouthAmerica => "South America",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+/// An ISO 3166-1 country.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Country {
+    /// The alpha-2 code, like `US`.
+    pub alpha2: &'static str,
+    /// The alpha-3 code, like `USA`.
+    pub alpha3: &'static str,
+    /// The short English name, like `United States`.
+    pub name: &'static str,
+    pub continent: Continent,
+    /// The ISO 4217 code of the main currency, like `USD`. Antarctica has none.
+    pub currency: Option<&'static str>,
+    /// The international calling code, like `+1` or `+44`.
+    pub calling_code: &'static str,
+    /// Whether the country is a member of the European Union.
+    pub eu: bool,
+    /// Whether the country is in the European Economic Area (the EU plus Iceland,
+    /// Liechtenstein and Norway).
+    pub eea: bool,
+}
+
+impl Country {
+    /// The country's flag emoji, made from the regional indicator symbols for its alpha-2 code.
+    pub fn flag(&self) -> String {
+        self.alpha2
+            .chars()
+            .filter_map(|letter| char::from_u32(0x1f1e6 + (letter as u32 - 'A' as u32)))
+            .collect()
+    }
+}
+
+/// An ISO 3166-2 subdivision.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Subdivision {
+    /// The full code, like `US-CA`.
+    pub code: &'static str,
+    /// The English name, like `California`.
+    pub name: &'static str,
+}
+
+macro_rules! countries {
+    ($((
+        $alpha2:expr,
+        $alpha3:expr,
+        $name:expr,
+        $continent:ident,
+        $currency:expr,
+        $calling_code:expr,
+        $eu:expr,
+        $eea:expr
+    )),* $(,)?) => {
+        &[$(Country {
+            alpha2: $alpha2,
+            alpha3: $alpha3,
+            name: $name,
+            continent: Continent::$continent,
+            currency: $currency,
+            calling_code: $calling_code,
+            eu: $eu,
+            eea: $eea,
+        }),*]
+    };
+}
+
+macro_rules! subdivisions {
+    ($(($code:expr, $name:expr)),* $(,)?) => {
+        &[$(Subdivision { code: $code, name: $name }),*]
+    };
+}
+
+/// Every ISO 3166-1 country.
+#[rustfmt::skip]
+pub const COUNTRIES: &[Country] = countries![
+    ("AD", "AND", "Andorra", Europe, Some("EUR"), "+376", false, false),
+    ("AE", "ARE", "United Arab Emirates", Asia, Some("AED"), "+971", false, false),
+    ("AF", "AFG", "Afghanistan", Asia, Some("AFN"), "+93", false, false),
+    ("AG", "ATG", "Antigua and Barbuda", NorthAmerica, Some("XCD"), "+1268", false, false),
+    ("AI", "AIA", "Anguilla", NorthAmerica, Some("XCD"), "+1264", false, false),
+    ("AL", "ALB", "Albania", Europe, Some("ALL"), "+355", false, false),
+    ("AM", "ARM", "Armenia", Asia, Some("AMD"), "+374", false, false),
+    ("AO", "AGO", "Angola", Africa, Some("AOA"), "+244", false, false),
+    ("AQ", "ATA", "Antarctica", Antarctica, None, "+672", false, false),
+    ("AR", "ARG", "Argentina", SouthAmerica, Some("ARS"), "+54", false, false),
+    ("AS", "ASM", "American Samoa", Oceania, Some("USD"), "+1684", false, false),
+    ("AT", "AUT", "Austria", Europe, Some("EUR"), "+43", true, true),
+    ("AU", "AUS", "Australia", Oceania, Some("AUD"), "+61", false, false),
+    ("AW", "ABW", "Aruba", NorthAmerica, Some("AWG"), "+297", false, false),
+    ("AX", "ALA", "Åland Islands", Europe, Some("EUR"), "+358", false, false),
+    ("AZ", "AZE", "Azerbaijan", Asia, Some("AZN"), "+994", false, false),
+    ("BA", "BIH", "Bosnia and Herzegovina", Europe, Some("BAM"), "+387", false, false),
+    ("BB", "BRB", "Barbados", NorthAmerica, Some("BBD"), "+1246", false, false),
+    ("BD", "BGD", "Bangladesh", Asia, Some("BDT"), "+880", false, false),
+    ("BE", "BEL", "Belgium", Europe, Some("EUR"), "+32", true, true),
+    ("BF", "BFA", "Burkina Faso", Africa, Some("XOF"), "+226", false, false),
+    ("BG", "BGR", "Bulgaria", Europe, Some("BGN"), "+359", true, true),
+    ("BH", "BHR", "Bahrain", Asia, Some("BHD"), "+973", false, false),
+    ("BI", "BDI", "Burundi", Africa, Some("BIF"), "+257", false, false),
+    ("BJ", "BEN", "Benin", Africa, Some("XOF"), "+229", false, false),
+    ("BL", "BLM", "Saint Barthélemy", NorthAmerica, Some("EUR"), "+590", false, false),
+    ("BM", "BMU", "Bermuda", NorthAmerica, Some("BMD"), "+1441", false, false),
+    ("BN", "BRN", "Brunei", Asia, Some("BND"), "+673", false, false),
+    ("BO", "BOL", "Bolivia", SouthAmerica, Some("BOB"), "+591", false, false),
+    ("BQ", "BES", "Caribbean Netherlands", NorthAmerica, Some("USD"), "+599", false, false),
+    ("BR", "BRA", "Brazil", SouthAmerica, Some("BRL"), "+55", false, false),
+    ("BS", "BHS", "Bahamas", NorthAmerica, Some("BSD"), "+1242", false, false),
+    ("BT", "BTN", "Bhutan", Asia, Some("BTN"), "+975", false, false),
+    ("BV", "BVT", "Bouvet Island", Antarctica, Some("NOK"), "+47", false, false),
+    ("BW", "BWA", "Botswana", Africa, Some("BWP"), "+267", false, false),
+    ("BY", "BLR", "Belarus", Europe, Some("BYN"), "+375", false, false),
+    ("BZ", "BLZ", "Belize", NorthAmerica, Some("BZD"), "+501", false, false),
+    ("CA", "CAN", "Canada", NorthAmerica, Some("CAD"), "+1", false, false),
+    ("CC", "CCK", "Cocos (Keeling) Islands", Asia, Some("AUD"), "+61", false, false),
+    ("CD", "COD", "DR Congo", Africa, Some("CDF"), "+243", false, false),
+    ("CF", "CAF", "Central African Republic", Africa, Some("XAF"), "+236", false, false),
+    ("CG", "COG", "Congo", Africa, Some("XAF"), "+242", false, false),
+    ("CH", "CHE", "Switzerland", Europe, Some("CHF"), "+41", false, false),
+    ("CI", "CIV", "Côte d'Ivoire", Africa, Some("XOF"), "+225", false, false),
+    ("CK", "COK", "Cook Islands", Oceania, Some("NZD"), "+682", false, false),
+    ("CL", "CHL", "Chile", SouthAmerica, Some("CLP"), "+56", false, false),
+    ("CM", "CMR", "Cameroon", Africa, Some("XAF"), "+237", false, false),
+    ("CN", "CHN", "China", Asia, Some("CNY"), "+86", false, false),
+    ("CO", "COL", "Colombia", SouthAmerica, Some("COP"), "+57", false, false),
+    ("CR", "CRI", "Costa Rica", NorthAmerica, Some("CRC"), "+506", false, false),
+    ("CU", "CUB", "Cuba", NorthAmerica, Some("CUP"), "+53", false, false),
+    ("CV", "CPV", "Cabo Verde", Africa, Some("CVE"), "+238", false, false),
+    ("CW", "CUW", "Curaçao", NorthAmerica, Some("ANG"), "+599", false, false),
+    ("CX", "CXR", "Christmas Island", Asia, Some("AUD"), "+61", false, false),
+    ("CY", "CYP", "Cyprus", Europe, Some("EUR"), "+357", true, true),
+    ("CZ", "CZE", "Czechia", Europe, Some("CZK"), "+420", true, true),
+    ("DE", "DEU", "Germany", Europe, Some("EUR"), "+49", true, true),
+    ("DJ", "DJI", "Djibouti", Africa, Some("DJF"), "+253", false, false),
+    ("DK", "DNK", "Denmark", Europe, Some("DKK"), "+45", true, true),
+    ("DM", "DMA", "Dominica", NorthAmerica, Some("XCD"), "+1767", false, false),
+    ("DO", "DOM", "Dominican Republic", NorthAmerica, Some("DOP"), "+1809", false, false),
+    ("DZ", "DZA", "Algeria", Africa, Some("DZD"), "+213", false, false),
+    ("EC", "ECU", "Ecuador", SouthAmerica, Some("USD"), "+593", false, false),
+    ("EE", "EST", "Estonia", Europe, Some("EUR"), "+372", true, true),
+    ("EG", "EGY", "Egypt", Africa, Some("EGP"), "+20", false, false),
+    ("EH", "ESH", "Western Sahara", Africa, Some("MAD"), "+212", false, false),
+    ("ER", "ERI", "Eritrea", Africa, Some("ERN"), "+291", false, false),
+    ("ES", "ESP", "Spain", Europe, Some("EUR"), "+34", true, true),
+    ("ET", "ETH", "Ethiopia", Africa, Some("ETB"), "+251", false, false),
+    ("FI", "FIN", "Finland", Europe, Some("EUR"), "+358", true, true),
+    ("FJ", "FJI", "Fiji", Oceania, Some("FJD"), "+679", false, false),
+    ("FK", "FLK", "Falkland Islands", SouthAmerica, Some("FKP"), "+500", false, false),
+    ("FM", "FSM", "Micronesia", Oceania, Some("USD"), "+691", false, false),
+    ("FO", "FRO", "Faroe Islands", Europe, Some("DKK"), "+298", false, false),
+    ("FR", "FRA", "France", Europe, Some("EUR"), "+33", true, true),
+    ("GA", "GAB", "Gabon", Africa, Some("XAF"), "+241", false, false),
+    ("GB", "GBR", "United Kingdom", Europe, Some("GBP"), "+44", false, false),
+    ("GD", "GRD", "Grenada", NorthAmerica, Some("XCD"), "+1473", false, false),
+    ("GE", "GEO", "Georgia", Asia, Some("GEL"), "+995", false, false),
+    ("GF", "GUF", "French Guiana", SouthAmerica, Some("EUR"), "+594", false, false),
+    ("GG", "GGY", "Guernsey", Europe, Some("GBP"), "+44", false, false),
+    ("GH", "GHA", "Ghana", Africa, Some("GHS"), "+233", false, false),
+    ("GI", "GIB", "Gibraltar", Europe, Some("GIP"), "+350", false, false),
+    ("GL", "GRL", "Greenland", NorthAmerica, Some("DKK"), "+299", false, false),
+    ("GM", "GMB", "Gambia", Africa, Some("GMD"), "+220", false, false),
+    ("GN", "GIN", "Guinea", Africa, Some("GNF"), "+224", false, false),
+    ("GP", "GLP", "Guadeloupe", NorthAmerica, Some("EUR"), "+590", false, false),
+    ("GQ", "GNQ", "Equatorial Guinea", Africa, Some("XAF"), "+240", false, false),
+    ("GR", "GRC", "Greece", Europe, Some("EUR"), "+30", true, true),
+    ("GS", "SGS", "South Georgia and the South Sandwich Islands", Antarctica, Some("GBP"), "+500", false, false),
+    ("GT", "GTM", "Guatemala", NorthAmerica, Some("GTQ"), "+502", false, false),
+    ("GU", "GUM", "Guam", Oceania, Some("USD"), "+1671", false, false),
+    ("GW", "GNB", "Guinea-Bissau", Africa, Some("XOF"), "+245", false, false),
+    ("GY", "GUY", "Guyana", SouthAmerica, Some("GYD"), "+592", false, false),
+    ("HK", "HKG", "Hong Kong", Asia, Some("HKD"), "+852", false, false),
+    ("HM", "HMD", "Heard Island and McDonald Islands", Antarctica, Some("AUD"), "+672", false, false),
+    ("HN", "HND", "Honduras", NorthAmerica, Some("HNL"), "+504", false, false),
+    ("HR", "HRV", "Croatia", Europe, Some("EUR"), "+385", true, true),
+    ("HT", "HTI", "Haiti", NorthAmerica, Some("HTG"), "+509", false, false),
+    ("HU", "HUN", "Hungary", Europe, Some("HUF"), "+36", true, true),
+    ("ID", "IDN", "Indonesia", Asia, Some("IDR"), "+62", false, false),
+    ("IE", "IRL", "Ireland", Europe, Some("EUR"), "+353", true, true),
+    ("IL", "ISR", "Israel", Asia, Some("ILS"), "+972", false, false),
+    ("IM", "IMN", "Isle of Man", Europe, Some("GBP"), "+44", false, false),
+    ("IN", "IND", "India", Asia, Some("INR"), "+91", false, false),
+    ("IO", "IOT", "British Indian Ocean Territory", Asia, Some("USD"), "+246", false, false),
+    ("IQ", "IRQ", "Iraq", Asia, Some("IQD"), "+964", false, false),
+    ("IR", "IRN", "Iran", Asia, Some("IRR"), "+98", false, false),
+    ("IS", "ISL", "Iceland", Europe, Some("ISK"), "+354", false, true),
+    ("IT", "ITA", "Italy", Europe, Some("EUR"), "+39", true, true),
+    ("JE", "JEY", "Jersey", Europe, Some("GBP"), "+44", false, false),
+    ("JM", "JAM", "Jamaica", NorthAmerica, Some("JMD"), "+1876", false, false),
+    ("JO", "JOR", "Jordan", Asia, Some("JOD"), "+962", false, false),
+    ("JP", "JPN", "Japan", Asia, Some("JPY"), "+81", false, false),
+    ("KE", "KEN", "Kenya", Africa, Some("KES"), "+254", false, false),
+    ("KG", "KGZ", "Kyrgyzstan", Asia, Some("KGS"), "+996", false, false),
+    ("KH", "KHM", "Cambodia", Asia, Some("KHR"), "+855", false, false),
+    ("KI", "KIR", "Kiribati", Oceania, Some("AUD"), "+686", false, false),
+    ("KM", "COM", "Comoros", Africa, Some("KMF"), "+269", false, false),
+    ("KN", "KNA", "Saint Kitts and Nevis", NorthAmerica, Some("XCD"), "+1869", false, false),
+    ("KP", "PRK", "North Korea", Asia, Some("KPW"), "+850", false, false),
+    ("KR", "KOR", "South Korea", Asia, Some("KRW"), "+82", false, false),
+    ("KW", "KWT", "Kuwait", Asia, Some("KWD"), "+965", false, false),
+    ("KY", "CYM", "Cayman Islands", NorthAmerica, Some("KYD"), "+1345", false, false),
+    ("KZ", "KAZ", "Kazakhstan", Asia, Some("KZT"), "+7", false, false),
+    ("LA", "LAO", "Laos", Asia, Some("LAK"), "+856", false, false),
+    ("LB", "LBN", "Lebanon", Asia, Some("LBP"), "+961", false, false),
+    ("LC", "LCA", "Saint Lucia", NorthAmerica, Some("XCD"), "+1758", false, false),
+    ("LI", "LIE", "Liechtenstein", Europe, Some("CHF"), "+423", false, true),
+    ("LK", "LKA", "Sri Lanka", Asia, Some("LKR"), "+94", false, false),
+    ("LR", "LBR", "Liberia", Africa, Some("LRD"), "+231", false, false),
+    ("LS", "LSO", "Lesotho", Africa, Some("LSL"), "+266", false, false),
+    ("LT", "LTU", "Lithuania", Europe, Some("EUR"), "+370", true, true),
+    ("LU", "LUX", "Luxembourg", Europe, Some("EUR"), "+352", true, true),
+    ("LV", "LVA", "Latvia", Europe, Some("EUR"), "+371", true, true),
+    ("LY", "LBY", "Libya", Africa, Some("LYD"), "+218", false, false),
+    ("MA", "MAR", "Morocco", Africa, Some("MAD"), "+212", false, false),
+    ("MC", "MCO", "Monaco", Europe, Some("EUR"), "+377", false, false),
+    ("MD", "MDA", "Moldova", Europe, Some("MDL"), "+373", false, false),
+    ("ME", "MNE", "Montenegro", Europe, Some("EUR"), "+382", false, false),
+    ("MF", "MAF", "Saint Martin", NorthAmerica, Some("EUR"), "+590", false, false),
+    ("MG", "MDG", "Madagascar", Africa, Some("MGA"), "+261", false, false),
+    ("MH", "MHL", "Marshall Islands", Oceania, Some("USD"), "+692", false, false),
+    ("MK", "MKD", "North Macedonia", Europe, Some("MKD"), "+389", false, false),
+    ("ML", "MLI", "Mali", Africa, Some("XOF"), "+223", false, false),
+    ("MM", "MMR", "Myanmar", Asia, Some("MMK"), "+95", false, false),
+    ("MN", "MNG", "Mongolia", Asia, Some("MNT"), "+976", false, false),
+    ("MO", "MAC", "Macao", Asia, Some("MOP"), "+853", false, false),
+    ("MP", "MNP", "Northern Mariana Islands", Oceania, Some("USD"), "+1670", false, false),
+    ("MQ", "MTQ", "Martinique", NorthAmerica, Some("EUR"), "+596", false, false),
+    ("MR", "MRT", "Mauritania", Africa, Some("MRU"), "+222", false, false),
+    ("MS", "MSR", "Montserrat", NorthAmerica, Some("XCD"), "+1664", false, false),
+    ("MT", "MLT", "Malta", Europe, Some("EUR"), "+356", true, true),
+    ("MU", "MUS", "Mauritius", Africa, Some("MUR"), "+230", false, false),
+    ("MV", "MDV", "Maldives", Asia, Some("MVR"), "+960", false, false),
+    ("MW", "MWI", "Malawi", Africa, Some("MWK"), "+265", false, false),
+    ("MX", "MEX", "Mexico", NorthAmerica, Some("MXN"), "+52", false, false),
+    ("MY", "MYS", "Malaysia", Asia, Some("MYR"), "+60", false, false),
+    ("MZ", "MOZ", "Mozambique", Africa, Some("MZN"), "+258", false, false),
+    ("NA", "NAM", "Namibia", Africa, Some("NAD"), "+264", false, false),
+    ("NC", "NCL", "New Caledonia", Oceania, Some("XPF"), "+687", false, false),
+    ("NE", "NER", "Niger", Africa, Some("XOF"), "+227", false, false),
+    ("NF", "NFK", "Norfolk Island", Oceania, Some("AUD"), "+672", false, false),
+    ("NG", "NGA", "Nigeria", Africa, Some("NGN"), "+234", false, false),
+    ("NI", "NIC", "Nicaragua", NorthAmerica, Some("NIO"), "+505", false, false),
+    ("NL", "NLD", "Netherlands", Europe, Some("EUR"), "+31", true, true),
+    ("NO", "NOR", "Norway", Europe, Some("NOK"), "+47", false, true),
+    ("NP", "NPL", "Nepal", Asia, Some("NPR"), "+977", false, false),
+    ("NR", "NRU", "Nauru", Oceania, Some("AUD"), "+674", false, false),
+    ("NU", "NIU", "Niue", Oceania, Some("NZD"), "+683", false, false),
+    ("NZ", "NZL", "New Zealand", Oceania, Some("NZD"), "+64", false, false),
+    ("OM", "OMN", "Oman", Asia, Some("OMR"), "+968", false, false),
+    ("PA", "PAN", "Panama", NorthAmerica, Some("PAB"), "+507", false, false),
+    ("PE", "PER", "Peru", SouthAmerica, Some("PEN"), "+51", false, false),
+    ("PF", "PYF", "French Polynesia", Oceania, Some("XPF"), "+689", false, false),
+    ("PG", "PNG", "Papua New Guinea", Oceania, Some("PGK"), "+675", false, false),
+    ("PH", "PHL", "Philippines", Asia, Some("PHP"), "+63", false, false),
+    ("PK", "PAK", "Pakistan", Asia, Some("PKR"), "+92", false, false),
+    ("PL", "POL", "Poland", Europe, Some("PLN"), "+48", true, true),
+    ("PM", "SPM", "Saint Pierre and Miquelon", NorthAmerica, Some("EUR"), "+508", false, false),
+    ("PN", "PCN", "Pitcairn", Oceania, Some("NZD"), "+64", false, false),
+    ("PR", "PRI", "Puerto Rico", NorthAmerica, Some("USD"), "+1787", false, false),
+    ("PS", "PSE", "Palestine", Asia, Some("ILS"), "+970", false, false),
+    ("PT", "PRT", "Portugal", Europe, Some("EUR"), "+351", true, true),
+    ("PW", "PLW", "Palau", Oceania, Some("USD"), "+680", false, false),
+    ("PY", "PRY", "Paraguay", SouthAmerica, Some("PYG"), "+595", false, false),
+    ("QA", "QAT", "Qatar", Asia, Some("QAR"), "+974", false, false),
+    ("RE", "REU", "Réunion", Africa, Some("EUR"), "+262", false, false),
+    ("RO", "ROU", "Romania", Europe, Some("RON"), "+40", true, true),
+    ("RS", "SRB", "Serbia", Europe, Some("RSD"), "+381", false, false),
+    ("RU", "RUS", "Russia", Europe, Some("RUB"), "+7", false, false),
+    ("RW", "RWA", "Rwanda", Africa, Some("RWF"), "+250", false, false),
+    ("SA", "SAU", "Saudi Arabia", Asia, Some("SAR"), "+966", false, false),
+    ("SB", "SLB", "Solomon Islands", Oceania, Some("SBD"), "+677", false, false),
+    ("SC", "SYC", "Seychelles", Africa, Some("SCR"), "+248", false, false),
+    ("SD", "SDN", "Sudan", Africa, Some("SDG"), "+249", false, false),
+    ("SE", "SWE", "Sweden", Europe, Some("SEK"), "+46", true, true),
+    ("SG", "SGP", "Singapore", Asia, Some("SGD"), "+65", false, false),
+    ("SH", "SHN", "Saint Helena, Ascension and Tristan da Cunha", Africa, Some("SHP"), "+290", false, false),
+    ("SI", "SVN", "Slovenia", Europe, Some("EUR"), "+386", true, true),
+    ("SJ", "SJM", "Svalbard and Jan Mayen", Europe, Some("NOK"), "+47", false, false),
+    ("SK", "SVK", "Slovakia", Europe, Some("EUR"), "+421", true, true),
+    ("SL", "SLE", "Sierra Leone", Africa, Some("SLE"), "+232", false, false),
+    ("SM", "SMR", "San Marino", Europe, Some("EUR"), "+378", false, false),
+    ("SN", "SEN", "Senegal", Africa, Some("XOF"), "+221", false, false),
+    ("SO", "SOM", "Somalia", Africa, Some("SOS"), "+252", false, false),
+    ("SR", "SUR", "Suriname", SouthAmerica, Some("SRD"), "+597", false, false),
+    ("SS", "SSD", "South Sudan", Africa, Some("SSP"), "+211", false, false),
+    ("ST", "STP", "São Tomé and Príncipe", Africa, Some("STN"), "+239", false, false),
+    ("SV", "SLV", "El Salvador", NorthAmerica, Some("USD"), "+503", false, false),
+    ("SX", "SXM", "Sint Maarten", NorthAmerica, Some("ANG"), "+1721", false, false),
+    ("SY", "SYR", "Syria", Asia, Some("SYP"), "+963", false, false),
+    ("SZ", "SWZ", "Eswatini", Africa, Some("SZL"), "+268", false, false),
+    ("TC", "TCA", "Turks and Caicos Islands", NorthAmerica, Some("USD"), "+1649", false, false),
+    ("TD", "TCD", "Chad", Africa, Some("XAF"), "+235", false, false),
+    ("TF", "ATF", "French Southern Territories", Antarctica, Some("EUR"), "+262", false, false),
+    ("TG", "TGO", "Togo", Africa, Some("XOF"), "+228", false, false),
+    ("TH", "THA", "Thailand", Asia, Some("THB"), "+66", false, false),
+    ("TJ", "TJK", "Tajikistan", Asia, Some("TJS"), "+992", false, false),
+    ("TK", "TKL", "Tokelau", Oceania, Some("NZD"), "+690", false, false),
+    ("TL", "TLS", "Timor-Leste", Asia, Some("USD"), "+670", false, false),
+    ("TM", "TKM", "Turkmenistan", Asia, Some("TMT"), "+993", false, false),
+    ("TN", "TUN", "Tunisia", Africa, Some("TND"), "+216", false, false),
+    ("TO", "TON", "Tonga", Oceania, Some("TOP"), "+676", false, false),
+    ("TR", "TUR", "Türkiye", Asia, Some("TRY"), "+90", false, false),
+    ("TT", "TTO", "Trinidad and Tobago", NorthAmerica, Some("TTD"), "+1868", false, false),
+    ("TV", "TUV", "Tuvalu", Oceania, Some("AUD"), "+688", false, false),
+    ("TW", "TWN", "Taiwan", Asia, Some("TWD"), "+886", false, false),
+    ("TZ", "TZA", "Tanzania", Africa, Some("TZS"), "+255", false, false),
+    ("UA", "UKR", "Ukraine", Europe, Some("UAH"), "+380", false, false),
+    ("UG", "UGA", "Uganda", Africa, Some("UGX"), "+256", false, false),
+    ("UM", "UMI", "United States Minor Outlying Islands", Oceania, Some("USD"), "+1", false, false),
+    ("US", "USA", "United States", NorthAmerica, Some("USD"), "+1", false, false),
+    ("UY", "URY", "Uruguay", SouthAmerica, Some("UYU"), "+598", false, false),
+    ("UZ", "UZB", "Uzbekistan", Asia, Some("UZS"), "+998", false, false),
+    ("VA", "VAT", "Vatican City", Europe, Some("EUR"), "+379", false, false),
+    ("VC", "VCT", "Saint Vincent and the Grenadines", NorthAmerica, Some("XCD"), "+1784", false, false),
+    ("VE", "VEN", "Venezuela", SouthAmerica, Some("VES"), "+58", false, false),
+    ("VG", "VGB", "British Virgin Islands", NorthAmerica, Some("USD"), "+1284", false, false),
+    ("VI", "VIR", "U.S. Virgin Islands", NorthAmerica, Some("USD"), "+1340", false, false),
+    ("VN", "VNM", "Vietnam", Asia, Some("VND"), "+84", false, false),
+    ("VU", "VUT", "Vanuatu", Oceania, Some("VUV"), "+678", false, false),
+    ("WF", "WLF", "Wallis and Futuna", Oceania, Some("XPF"), "+681", false, false),
+    ("WS", "WSM", "Samoa", Oceania, Some("WST"), "+685", false, false),
+    ("YE", "YEM", "Yemen", Asia, Some("YER"), "+967", false, false),
+    ("YT", "MYT", "Mayotte", Africa, Some("EUR"), "+262", false, false),
+    ("ZA", "ZAF", "South Africa", Africa, Some("ZAR"), "+27", false, false),
+    ("ZM", "ZMB", "Zambia", Africa, Some("ZMW"), "+260", false, false),
+    ("ZW", "ZWE", "Zimbabwe", Africa, Some("ZWL"), "+263", false, false),
+];
+
+/// Other names services use for countries, with the alpha-2 code they stand for.
+const COUNTRY_ALIASES: &[(&str, &str)] = &[
+    ("Aland Islands", "AX"),
+    ("Bolivia, Plurinational State of", "BO"),
+    ("Bonaire, Sint Eustatius, and Saba", "BQ"),
+    ("Bonaire, Sint Eustatius and Saba", "BQ"),
+    ("Brunei Darussalam", "BN"),
+    ("Burma", "MM"),
+    ("Cape Verde", "CV"),
+    ("Congo Republic", "CG"),
+    ("Republic of the Congo", "CG"),
+    ("Congo, The Democratic Republic of the", "CD"),
+    ("Democratic Republic of the Congo", "CD"),
+    ("Cote d'Ivoire", "CI"),
+    ("Ivory Coast", "CI"),
+    ("Curacao", "CW"),
+    ("Czech Republic", "CZ"),
+    ("Federated States of Micronesia", "FM"),
+    ("Micronesia, Federated States of", "FM"),
+    ("Falkland Islands (Malvinas)", "FK"),
+    ("Holy See (Vatican City State)", "VA"),
+    ("Hong Kong SAR", "HK"),
+    ("Iran, Islamic Republic of", "IR"),
+    ("Korea, Democratic People's Republic of", "KP"),
+    ("Korea, Republic of", "KR"),
+    ("Republic of Korea", "KR"),
+    ("Lao People's Democratic Republic", "LA"),
+    ("Libyan Arab Jamahiriya", "LY"),
+    ("Macau", "MO"),
+    ("Macao SAR", "MO"),
+    ("Macedonia", "MK"),
+    ("Macedonia, the Former Yugoslav Republic of", "MK"),
+    ("Moldova, Republic of", "MD"),
+    ("Palestinian Territory", "PS"),
+    ("Palestine, State of", "PS"),
+    ("Reunion", "RE"),
+    ("Russian Federation", "RU"),
+    ("Saint Martin (French part)", "MF"),
+    ("Sint Maarten (Dutch part)", "SX"),
+    ("Sao Tome and Principe", "ST"),
+    ("Swaziland", "SZ"),
+    ("Syrian Arab Republic", "SY"),
+    ("Taiwan, Province of China", "TW"),
+    ("Tanzania, United Republic of", "TZ"),
+    ("The Netherlands", "NL"),
+    ("Turkey", "TR"),
+    ("United Kingdom of Great Britain and Northern Ireland", "GB"),
+    ("United States of America", "US"),
+    ("Vatican", "VA"),
+    ("Holy See", "VA"),
+    ("Venezuela, Bolivarian Republic of", "VE"),
+    ("Viet Nam", "VN"),
+    ("Virgin Islands, British", "VG"),
+    ("Virgin Islands, U.S.", "VI"),
+];
+
+/// ISO 3166-2 subdivisions for the countries that are bundled.
+pub const SUBDIVISIONS: &[Subdivision] = subdivisions![
+    ("AU-ACT", "Australian Capital Territory"),
+    ("AU-NSW", "New South Wales"),
+    ("AU-NT", "Northern Territory"),
+    ("AU-QLD", "Queensland"),
+    ("AU-SA", "South Australia"),
+    ("AU-TAS", "Tasmania"),
+    ("AU-VIC", "Victoria"),
+    ("AU-WA", "Western Australia"),
+    ("BR-AC", "Acre"),
+    ("BR-AL", "Alagoas"),
+    ("BR-AP", "Amapá"),
+    ("BR-AM", "Amazonas"),
+    ("BR-BA", "Bahia"),
+    ("BR-CE", "Ceará"),
+    ("BR-DF", "Distrito Federal"),
+    ("BR-ES", "Espírito Santo"),
+    ("BR-GO", "Goiás"),
+    ("BR-MA", "Maranhão"),
+    ("BR-MT", "Mato Grosso"),
+    ("BR-MS", "Mato Grosso do Sul"),
+    ("BR-MG", "Minas Gerais"),
+    ("BR-PA", "Pará"),
+    ("BR-PB", "Paraíba"),
+    ("BR-PR", "Paraná"),
+    ("BR-PE", "Pernambuco"),
+    ("BR-PI", "Piauí"),
+    ("BR-RJ", "Rio de Janeiro"),
+    ("BR-RN", "Rio Grande do Norte"),
+    ("BR-RS", "Rio Grande do Sul"),
+    ("BR-RO", "Rondônia"),
+    ("BR-RR", "Roraima"),
+    ("BR-SC", "Santa Catarina"),
+    ("BR-SP", "São Paulo"),
+    ("BR-SE", "Sergipe"),
+    ("BR-TO", "Tocantins"),
+    ("CA-AB", "Alberta"),
+    ("CA-BC", "British Columbia"),
+    ("CA-MB", "Manitoba"),
+    ("CA-NB", "New Brunswick"),
+    ("CA-NL", "Newfoundland and Labrador"),
+    ("CA-NS", "Nova Scotia"),
+    ("CA-NT", "Northwest Territories"),
+    ("CA-NU", "Nunavut"),
+    ("CA-ON", "Ontario"),
+    ("CA-PE", "Prince Edward Island"),
+    ("CA-QC", "Quebec"),
+    ("CA-SK", "Saskatchewan"),
+    ("CA-YT", "Yukon"),
+    ("DE-BW", "Baden-Württemberg"),
+    ("DE-BY", "Bavaria"),
+    ("DE-BE", "Berlin"),
+    ("DE-BB", "Brandenburg"),
+    ("DE-HB", "Bremen"),
+    ("DE-HH", "Hamburg"),
+    ("DE-HE", "Hesse"),
+    ("DE-MV", "Mecklenburg-Vorpommern"),
+    ("DE-NI", "Lower Saxony"),
+    ("DE-NW", "North Rhine-Westphalia"),
+    ("DE-RP", "Rhineland-Palatinate"),
+    ("DE-SL", "Saarland"),
+    ("DE-SN", "Saxony"),
+    ("DE-ST", "Saxony-Anhalt"),
+    ("DE-SH", "Schleswig-Holstein"),
+    ("DE-TH", "Thuringia"),
+    ("MX-AGU", "Aguascalientes"),
+    ("MX-BCN", "Baja California"),
+    ("MX-BCS", "Baja California Sur"),
+    ("MX-CAM", "Campeche"),
+    ("MX-CHP", "Chiapas"),
+    ("MX-CHH", "Chihuahua"),
+    ("MX-CMX", "Mexico City"),
+    ("MX-COA", "Coahuila"),
+    ("MX-COL", "Colima"),
+    ("MX-DUR", "Durango"),
+    ("MX-GUA", "Guanajuato"),
+    ("MX-GRO", "Guerrero"),
+    ("MX-HID", "Hidalgo"),
+    ("MX-JAL", "Jalisco"),
+    ("MX-MEX", "México"),
+    ("MX-MIC", "Michoacán"),
+    ("MX-MOR", "Morelos"),
+    ("MX-NAY", "Nayarit"),
+    ("MX-NLE", "Nuevo León"),
+    ("MX-OAX", "Oaxaca"),
+    ("MX-PUE", "Puebla"),
+    ("MX-QUE", "Querétaro"),
+    ("MX-ROO", "Quintana Roo"),
+    ("MX-SLP", "San Luis Potosí"),
+    ("MX-SIN", "Sinaloa"),
+    ("MX-SON", "Sonora"),
+    ("MX-TAB", "Tabasco"),
+    ("MX-TAM", "Tamaulipas"),
+    ("MX-TLA", "Tlaxcala"),
+    ("MX-VER", "Veracruz"),
+    ("MX-YUC", "Yucatán"),
+    ("MX-ZAC", "Zacatecas"),
+    ("US-AL", "Alabama"),
+    ("US-AK", "Alaska"),
+    ("US-AZ", "Arizona"),
+    ("US-AR", "Arkansas"),
+    ("US-CA", "California"),
+    ("US-CO", "Colorado"),
+    ("US-CT", "Connecticut"),
+    ("US-DE", "Delaware"),
+    ("US-DC", "District of Columbia"),
+    ("US-FL", "Florida"),
+    ("US-GA", "Georgia"),
+    ("US-HI", "Hawaii"),
+    ("US-ID", "Idaho"),
+    ("US-IL", "Illinois"),
+    ("US-IN", "Indiana"),
+    ("US-IA", "Iowa"),
+    ("US-KS", "Kansas"),
+    ("US-KY", "Kentucky"),
+    ("US-LA", "Louisiana"),
+    ("US-ME", "Maine"),
+    ("US-MD", "Maryland"),
+    ("US-MA", "Massachusetts"),
+    ("US-MI", "Michigan"),
+    ("US-MN", "Minnesota"),
+    ("US-MS", "Mississippi"),
+    ("US-MO", "Missouri"),
+    ("US-MT", "Montana"),
+    ("US-NE", "Nebraska"),
+    ("US-NV", "Nevada"),
+    ("US-NH", "New Hampshire"),
+    ("US-NJ", "New Jersey"),
+    ("US-NM", "New Mexico"),
+    ("US-NY", "New York"),
+    ("US-NC", "North Carolina"),
+    ("US-ND", "North Dakota"),
+    ("US-OH", "Ohio"),
+    ("US-OK", "Oklahoma"),
+    ("US-OR", "Oregon"),
+    ("US-PA", "Pennsylvania"),
+    ("US-RI", "Rhode Island"),
+    ("US-SC", "South Carolina"),
+    ("US-SD", "South Dakota"),
+    ("US-TN", "Tennessee"),
+    ("US-TX", "Texas"),
+    ("US-UT", "Utah"),
+    ("US-VT", "Vermont"),
+    ("US-VA", "Virginia"),
+    ("US-WA", "Washington"),
+    ("US-WV", "West Virginia"),
+    ("US-WI", "Wisconsin"),
+    ("US-WY", "Wyoming"),
+];
+
+/// Finds a country by its alpha-2 code, alpha-3 code or name, ignoring case.
+pub fn country(code_or_name: &str) -> Option<&'static Country> {
+    let code_or_name = code_or_name.trim();
+    if code_or_name.is_empty() {
+        return None;
+    }
+
+    let found = COUNTRIES.iter().find(|country| {
+        country.alpha2.eq_ignore_ascii_case(code_or_name)
+            || country.alpha3.eq_ignore_ascii_case(code_or_name)
+            || country.name.eq_ignore_ascii_case(code_or_name)
+    });
+
+    found.or_else(|| {
+        let (_, alpha2) = COUNTRY_ALIASES
+            .iter()
+            .find(|(alias, _)| alias.eq_ignore_ascii_case(code_or_name))?;
+        COUNTRIES.iter().find(|country| country.alpha2 == *alpha2)
+    })
+}
+
+/// Finds a subdivision of the country with alpha-2 code `country` by its code (with or
+/// without the country prefix) or name, ignoring case.
+///
+/// Only finds subdivisions in [`SUBDIVISIONS`].
+pub fn subdivision(country: &str, code_or_name: &str) -> Option<&'static Subdivision> {
+    let code_or_name = code_or_name.trim();
+
+    SUBDIVISIONS
+        .iter()
+        .filter(|subdivision| {
+            subdivision
+                .code
+                .get(..2)
+                .is_some_and(|prefix| prefix.eq_ignore_ascii_case(country))
+        })
+        .find(|subdivision| {
+            subdivision.code.eq_ignore_ascii_case(code_or_name)
+                || subdivision.code[3..].eq_ignore_ascii_case(code_or_name)
+                || subdivision.name.eq_ignore_ascii_case(code_or_name)
+        })
+}
+
+/// Fills in the ISO codes of `locator` and replaces codes in its display names.
+///
+/// Codes the service sent are kept, with the country prefix added to a bare region code.
+pub fn normalize(locator: &mut Locator) {
+    let country = match country(&locator.country_code).or_else(|| country(&locator.country)) {
+        Some(country) => country,
+        None => return,
+    };
+
+    locator.country_code = country.alpha2.to_string();
+    locator.country_code3 = country.alpha3.to_string();
+
+    // Some services send the country's code where its name should be.
+    if locator.country.is_empty()
+        || locator.country.eq_ignore_ascii_case(country.alpha2)
+        || locator.country.eq_ignore_ascii_case(country.alpha3)
+    {
+        locator.country = country.name.to_string();
+    }
+
+    let found = subdivision(country.alpha2, &locator.region_code)
+        .or_else(|| subdivision(country.alpha2, &locator.region));
+
+    match found {
+        Some(found) => {
+            locator.region_code = found.code.to_string();
+            if locator.region.is_empty() || found.code[3..].eq_ignore_ascii_case(&locator.region) {
+                locator.region = found.name.to_string();
+            }
+        }
+        None if !locator.region_code.is_empty() && !locator.region_code.contains('-') => {
+            locator.region_code =
+                format!("{}-{}", country.alpha2, locator.region_code).to_ascii_uppercase();
+        }
+        None => (),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn normalized(country: &str, region: &str, region_code: &str) -> Locator {
+        let mut locator = Locator {
+            country: country.to_string(),
+            region: region.to_string(),
+            region_code: region_code.to_string(),
+            ..Locator::default()
+        };
+        normalize(&mut locator);
+        locator
+    }
+
+    #[test]
+    fn replaces_country_codes_with_names() {
+        // ipinfo.io sends the alpha-2 code as the country.
+        let locator = normalized("US", "California", "");
+
+        assert_eq!(locator.country, "United States");
+        assert_eq!(locator.country_code, "US");
+        assert_eq!(locator.country_code3, "USA");
+        assert_eq!(locator.region_code, "US-CA");
+    }
+
+    #[test]
+    fn finds_countries_by_alias() {
+        let locator = normalized(
+            "United Kingdom of Great Britain and Northern Ireland",
+            "",
+            "",
+        );
+
+        assert_eq!(locator.country_code, "GB");
+        assert_eq!(locator.country_code3, "GBR");
+    }
+
+    #[test]
+    fn expands_region_codes() {
+        // ip-api.com sends the subdivision code without the country prefix.
+        let locator = normalized("Canada", "", "ON");
+        assert_eq!(locator.region_code, "CA-ON");
+        assert_eq!(locator.region, "Ontario");
+
+        // Outside the bundled subdivisions the service's code is prefixed as it is.
+        let locator = normalized("France", "Ile-de-France", "idf");
+        assert_eq!(locator.region_code, "FR-IDF");
+        assert_eq!(locator.region, "Ile-de-France");
+
+        // With no code from the service, unbundled regions stay unknown.
+        let locator = normalized("France", "Ile-de-France", "");
+        assert_eq!(locator.region_code, "");
+    }
+
+    #[test]
+    fn leaves_unknown_countries_alone() {
+        let locator = normalized("Atlantis", "Poseidonia", "PO");
+
+        assert_eq!(locator.country, "Atlantis");
+        assert_eq!(locator.country_code, "");
+        assert_eq!(locator.region_code, "PO");
+    }
+}
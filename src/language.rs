@@ -0,0 +1,129 @@
+//! Picking a language for place names.
+//!
+//! A [`Client`](crate::Client) can be given a list of BCP 47 language tags in order of
+//! preference with [`Client::languages`](crate::Client::languages). Each service, and each
+//! offline database, only has names in a few languages, so the first preference it supports
+//! is used and everything else falls back to English.
+
+/// Languages ip-api.com can return place names in.
+pub const IPAPI_LANGUAGES: &[&str] = &["en", "de", "es", "pt-BR", "fr", "ja", "zh-CN", "ru"];
+
+/// Languages ipwhois can return place names in.
+pub const IPWHOIS_LANGUAGES: &[&str] = &["en", "ru", "de", "es", "pt-BR", "fr", "zh-CN", "ja"];
+
+/// Scripts implied by a language and region, for tags that don't name one (`zh-CN` is
+/// written in Simplified Chinese, `zh-TW` in Traditional).
+const IMPLIED_SCRIPTS: &[(&str, &str, &str)] = &[
+    ("zh", "CN", "Hans"),
+    ("zh", "SG", "Hans"),
+    ("zh", "TW", "Hant"),
+    ("zh", "HK", "Hant"),
+    ("zh", "MO", "Hant"),
+];
+
+/// Picks the first language in `preferred` that's in `available`.
+///
+/// Tags are compared case-insensitively, and a tag that isn't available is retried with its
+/// last subtag removed (`de-CH` falls back to `de`). Failing that, a tag naming a script is
+/// matched by language and script (`zh-Hans-SG` picks `zh-CN`), and a bare language by its
+/// primary subtag alone (`pt` picks `pt-BR`). A tag for another script or region isn't
+/// matched to a different one: `zh-Hant-TW` doesn't pick `zh-CN`, nor `pt-PT` `pt-BR`.
+pub fn negotiate<'a, S: AsRef<str>>(preferred: &[S], available: &[&'a str]) -> Option<&'a str> {
+    for original in preferred {
+        let original = original.as_ref();
+        let mut tag = original;
+
+        loop {
+            if let Some(found) = available
+                .iter()
+                .find(|language| language.eq_ignore_ascii_case(tag))
+            {
+                return Some(found);
+            }
+
+            match tag.rfind('-') {
+                Some(end) => tag = &tag[..end],
+                None => break,
+            }
+        }
+
+        let language = primary(original);
+        let found = match script(original) {
+            Some(wanted) => available.iter().find(|candidate| {
+                primary(candidate).eq_ignore_ascii_case(language)
+                    && script(candidate).is_some_and(|script| script.eq_ignore_ascii_case(wanted))
+            }),
+            None if language == original => available
+                .iter()
+                .find(|candidate| primary(candidate).eq_ignore_ascii_case(language)),
+            None => None,
+        };
+
+        if let Some(found) = found {
+            return Some(found);
+        }
+    }
+
+    None
+}
+
+fn primary(tag: &str) -> &str {
+    tag.split('-').next().unwrap_or(tag)
+}
+
+/// The script `tag` names, like `Hant` in `zh-Hant-TW`, or failing that the one its language
+/// and region imply (see [`IMPLIED_SCRIPTS`]).
+fn script(tag: &str) -> Option<&str> {
+    let mut subtags = tag.split('-').skip(1);
+    let second = subtags.next()?;
+
+    if second.len() == 4 && second.chars().all(|letter| letter.is_ascii_alphabetic()) {
+        return Some(second);
+    }
+
+    IMPLIED_SCRIPTS
+        .iter()
+        .find(|(language, region, _)| {
+            primary(tag).eq_ignore_ascii_case(language) && second.eq_ignore_ascii_case(region)
+        })
+        .map(|(_, _, script)| *script)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn picks_first_available_preference() {
+        assert_eq!(negotiate(&["it", "fr", "de"], IPAPI_LANGUAGES), Some("fr"));
+        assert_eq!(negotiate(&["ZH-cn"], IPAPI_LANGUAGES), Some("zh-CN"));
+    }
+
+    #[test]
+    fn falls_back_to_broader_tags() {
+        assert_eq!(negotiate(&["de-CH"], IPAPI_LANGUAGES), Some("de"));
+        assert_eq!(negotiate(&["pt"], IPWHOIS_LANGUAGES), Some("pt-BR"));
+        assert_eq!(negotiate(&["zh"], IPWHOIS_LANGUAGES), Some("zh-CN"));
+    }
+
+    #[test]
+    fn matches_scripts() {
+        assert_eq!(negotiate(&["zh-Hans"], IPWHOIS_LANGUAGES), Some("zh-CN"));
+        assert_eq!(negotiate(&["zh-hans-SG"], IPWHOIS_LANGUAGES), Some("zh-CN"));
+        assert_eq!(negotiate(&["zh-SG"], IPWHOIS_LANGUAGES), Some("zh-CN"));
+    }
+
+    #[test]
+    fn keeps_scripts_and_regions_apart() {
+        assert_eq!(negotiate(&["zh-Hant-TW"], IPWHOIS_LANGUAGES), None);
+        assert_eq!(negotiate(&["zh-TW"], IPWHOIS_LANGUAGES), None);
+        assert_eq!(negotiate(&["pt-PT"], IPWHOIS_LANGUAGES), None);
+        assert_eq!(negotiate(&["pt-PT", "fr"], IPWHOIS_LANGUAGES), Some("fr"));
+    }
+
+    #[test]
+    fn gives_up_without_a_match() {
+        assert_eq!(negotiate(&["it", "nl"], IPAPI_LANGUAGES), None);
+        assert_eq!(negotiate::<&str>(&[], IPAPI_LANGUAGES), None);
+    }
+}
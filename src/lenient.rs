@@ -0,0 +1,121 @@
+//! Partial results when a service leaves fields out.
+//!
+//! Services don't always send every field: ipapi.co sends `"city": null` for many addresses.
+//! By default a missing field fails the lookup. With
+//! [`Client::lenient`](crate::Client::lenient), only the [`Field`]s named essential do; the
+//! others are left empty and a [`Warning`] is added to
+//! [`Locator::warnings`](crate::Locator::warnings) for each.
+
+use crate::Service;
+use std::fmt;
+
+/// A [`Locator`](crate::Locator) field that services are expected to send.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Field {
+    Latitude,
+    Longitude,
+    City,
+    Region,
+    Country,
+    Timezone,
+    Isp,
+}
+
+impl fmt::Display for Field {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Field::Latitude => write!(f, "latitude"),
+            Field::Longitude => write!(f, "longitude"),
+            Field::City => write!(f, "city"),
+            Field::Region => write!(f, "region"),
+            Field::Country => write!(f, "country"),
+            Field::Timezone => write!(f, "timezone"),
+            Field::Isp => write!(f, "isp"),
+        }
+    }
+}
+
+/// The fields a result is of little use without: the coordinates and the country.
+pub const ESSENTIAL: &[Field] = &[Field::Latitude, Field::Longitude, Field::Country];
+
+/// A field a service didn't send, which was left empty.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Warning {
+    pub field: Field,
+    pub service: Service,
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} didn't send {}", self.service, self.field)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{decode, ipapico, Client, GeoError, Locator};
+    use serde_json::{json, Value};
+
+    // ipapi.co sends `"city": null` for addresses it only places in a region.
+    fn cityless() -> Value {
+        json!({
+            "ip": "203.0.113.7",
+            "city": null,
+            "region": "Queensland",
+            "region_code": "QLD",
+            "country_name": "Australia",
+            "country_code": "AU",
+            "latitude": -27.4766,
+            "longitude": 153.0166,
+            "timezone": "Australia/Brisbane",
+            "asn": "AS13335",
+            "org": "CLOUDFLARENET"
+        })
+    }
+
+    fn locate(body: &Value, client: &Client) -> std::result::Result<Locator, GeoError> {
+        let response: ipapico::Response = decode(body, "ipapi.co").unwrap();
+        response.locator("203.0.113.7", client)
+    }
+
+    #[test]
+    fn fails_on_missing_field_by_default() {
+        match locate(&cityless(), &Client::new(Service::IpApiCo)) {
+            Err(GeoError::ParseError(error)) => assert!(error.contains("city")),
+            other => panic!("expected a missing city, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn warns_on_missing_field_when_lenient() {
+        let client = Client::new(Service::IpApiCo).lenient(ESSENTIAL);
+        let location = locate(&cityless(), &client).unwrap();
+
+        assert_eq!(location.city, "");
+        assert_eq!(location.region, "Queensland");
+        assert_eq!(
+            location.warnings,
+            vec![Warning {
+                field: Field::City,
+                service: Service::IpApiCo,
+            }]
+        );
+        assert_eq!(location.warnings[0].to_string(), "ipapico didn't send city");
+    }
+
+    #[test]
+    fn fails_on_missing_essential_field_when_lenient() {
+        let mut body = cityless();
+        body["latitude"] = Value::Null;
+
+        let client = Client::new(Service::IpApiCo).lenient(ESSENTIAL);
+        match locate(&body, &client) {
+            Err(GeoError::ParseError(error)) => assert!(error.contains("latitude")),
+            other => panic!("expected a missing latitude, got {:?}", other),
+        }
+
+        let client = Client::new(Service::IpApiCo).lenient(&[Field::City]);
+        assert!(locate(&cityless(), &client).is_err());
+    }
+}
@@ -1,7 +1,7 @@
 //! # ipgeolocate
 //! Get IP address geolocation information freely.
 //!
-//! ```
+//! ```toml
 //! ipgeolocate = "0.3.5"
 //! ```
 //! Add to `Cargo.toml`.
@@ -40,13 +40,511 @@
 //! | [freegeoip.app](https://ipwhois.app/)     | 15,000/hour               |
 //! | [ip-api.com](https://ip-api.com/)         | 45/minute                 |
 //! | [ipapi.co](https://ipapi.co/)             | 1,000/day (30,000/month)  |
+//! | [ipinfo.io](https://ipinfo.io/)           | 50,000/month              |
 //!
 //! You can use each of these just by running the function of the same name.
 //!
 //! freegeoip.app is not recommended because it has issues reliably getting the correct latitude and longitude for IP addresses.
 //!
+//! ## Paid Plans
+//! ip-api.com, ipwhois and ipapi.co sell plans with higher quotas and HTTPS. Give a [`Client`] the
+//! plan's key with [`Client::api_key`] and it switches to the paid endpoint:
+//! ```no_run
+//! use ipgeolocate::{Client, Service};
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let client = Client::new(Service::IpApi).api_key("YOUR_KEY");
+//!
+//!     match client.get("1.1.1.1").await {
+//!         Ok(ip) => println!("{} - {} ({})", ip.ip, ip.city, ip.country),
+//!         Err(error) => println!("Error: {}", error),
+//!     };
+//! }
+//! ```
+//!
+//! ## Transport Security
+//! The free ip-api.com and ipwhois endpoints are plain HTTP, so the addresses you look up can be
+//! read on the way. A [`TransportPolicy`] can refuse plaintext requests, trust extra root
+//! certificates and pin the certificates each service may present:
+//! ```no_run
+//! use ipgeolocate::{Client, Service, TransportPolicy};
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let policy = TransportPolicy::new()
+//!         .https_only(true)
+//!         .pin_hex(
+//!             Service::IpInfo,
+//!             "8d:02:53:6c:88:7d:86:45:b2:8a:a3:8a:f2:c1:49:2b:0f:d4:a0:a5:9f:a5:d8:b5:ed:e8:b6:15:f3:c8:b4:a2",
+//!         )
+//!         .unwrap();
+//!
+//!     let client = Client::new(Service::IpInfo).transport(policy);
+//!
+//!     match client.get("1.1.1.1").await {
+//!         Ok(ip) => println!("{} - {} ({})", ip.ip, ip.city, ip.country),
+//!         Err(error) => println!("Error: {}", error),
+//!     };
+//! }
+//! ```
+//!
+//! With `https_only`, ip-api.com and ipwhois fail with [`GeoError::InsecureTransport`] unless
+//! they're given an API key.
+//!
+//! ## ip-api.com Fields
+//! ip-api.com can send more than the fields on [`Locator`], like whether an address is a proxy or
+//! belongs to a hosting provider. Select the fields you want with [`Client::ipapi_fields`]; the
+//! extra ones are in [`Locator::ipapi`](Locator#structfield.ipapi):
+//! ```no_run
+//! use ipgeolocate::ipapi::Field;
+//! use ipgeolocate::{Client, Service};
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let client = Client::new(Service::IpApi).ipapi_fields(&[Field::Country, Field::Proxy]);
+//!
+//!     match client.get("1.1.1.1").await {
+//!         Ok(ip) => println!("{} - proxy: {:?}", ip.country, ip.ipapi.unwrap().proxy),
+//!         Err(error) => println!("Error: {}", error),
+//!     };
+//! }
+//! ```
+//!
+//! Fields you leave out are empty on the [`Locator`]. [`ipapi::LOCATION`], [`ipapi::NETWORK`] and
+//! [`ipapi::ALL`] are ready-made selections.
+//!
+//! ## Languages
+//! ip-api.com and ipwhois can name places in a few languages other than English. Give a
+//! [`Client`] your preferences as BCP 47 tags and it uses the first one the service supports:
+//! ```no_run
+//! use ipgeolocate::{Client, Service};
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let client = Client::new(Service::IpApi).languages(&["de-CH", "fr", "en"]);
+//!
+//!     match client.get("1.1.1.1").await {
+//!         Ok(ip) => println!("{} - {} ({})", ip.ip, ip.city, ip.country),
+//!         Err(error) => println!("Error: {}", error),
+//!     };
+//! }
+//! ```
+//!
+//! Offline databases pick names the same way with [`Database::lookup_localized`].
+//!
+//! ## ipinfo.io
+//! [`Service::IpInfo`] takes an access token through [`Client::api_key`]. Paid plans return
+//! ASN, company, privacy and abuse details, which are kept in [`Locator::ipinfo`], and up to
+//! 1,000 addresses can be looked up in one request with [`Client::get_batch`]:
+//! ```no_run
+//! use ipgeolocate::{Client, Service};
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let client = Client::new(Service::IpInfo).api_key("YOUR_TOKEN");
+//!
+//!     match client.get_batch(&["1.1.1.1", "8.8.8.8"]).await {
+//!         Ok(locations) => {
+//!             for (address, location) in locations {
+//!                 match location {
+//!                     Ok(ip) => println!("{} - {} ({})", address, ip.city, ip.country),
+//!                     Err(error) => println!("{} - Error: {}", address, error),
+//!                 }
+//!             }
+//!         }
+//!         Err(error) => println!("Error: {}", error),
+//!     };
+//! }
+//! ```
+//!
+//! ## Offline Databases
+//! IP addresses can also be looked up in a MaxMind-format (`.mmdb`) city or country database with
+//! [`Locator::get_offline`]. City databases also give the
+//! [`accuracy_radius`](Locator::accuracy_radius) in kilometres, which says how much to trust the
+//! city, and the postal and metro codes. A [`Database`] can be swapped for a newer file while it's
+//! in use, either by calling [`Database::reload`] or by polling the file with [`Database::watch`]:
+//! ```no_run
+//! use ipgeolocate::{Database, Locator};
+//! use std::time::Duration;
+//!
+//! let database = Database::open("GeoLite2-City.mmdb").unwrap();
+//! let _watcher = database.watch(Duration::from_secs(60));
+//!
+//! match Locator::get_offline("1.1.1.1".parse().unwrap(), &database) {
+//!     Ok(ip) => println!("{} - {} ({}), within {:?} km", ip.ip, ip.city, ip.country, ip.accuracy_radius),
+//!     Err(error) => println!("Error: {}", error),
+//! };
+//! ```
+//!
+//! New versions of a database can be downloaded with an [`Updater`], which checks the
+//! published SHA-256 checksum and only downloads when the remote file has changed:
+//! ```no_run
+//! use ipgeolocate::{Database, Updater};
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let database = Database::open("GeoLite2-City.mmdb").unwrap();
+//!
+//!     let updater = Updater::new(
+//!         "https://download.maxmind.com/app/geoip_download?edition_id=GeoLite2-City&suffix=tar.gz",
+//!         "GeoLite2-City.mmdb",
+//!     )
+//!     .license_key("YOUR_LICENSE_KEY");
+//!
+//!     match updater.update_database(&database).await {
+//!         Ok(status) => println!("{:?}", status),
+//!         Err(error) => println!("Error: {}", error),
+//!     };
+//! }
+//! ```
+//!
+//! CSV archives hold several files, so name the one to install with [`Updater::entry`]. Downloads
+//! follow the [`TransportPolicy`] given to [`Updater::transport`].
+//!
+//! ## Overrides
+//! Ranges that services get wrong, like internal or VPN networks, can be pinned to a location
+//! with an [`Overrides`] table loaded from a TOML, JSON or CSV file:
+//! ```toml
+//! [[override]]
+//! cidr = "10.0.0.0/8"
+//! latitude = "39.7392"
+//! longitude = "-104.9903"
+//! city = "Denver"
+//! region = "Colorado"
+//! country = "United States"
+//! timezone = "America/Denver"
+//! isp = "Internal"
+//!
+//! [[override]]
+//! cidr = "203.0.113.0/24"
+//! isp = "Corporate Egress"
+//! ```
+//! Ranges with every field set are answered without contacting a service; the rest are merged
+//! onto the service's result. The most specific matching range wins. Special-purpose addresses
+//! (see below) are never sent to a service, so a partial override on one is the whole result,
+//! with [`category`](Locator::category) saying what kind of address it is.
+//! ```no_run
+//! use ipgeolocate::{Client, Overrides, Service};
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let overrides = Overrides::from_file("overrides.toml").unwrap();
+//!     let client = Client::new(Service::IpApi)
+//!         .api_key("YOUR_KEY")
+//!         .overrides(overrides);
+//!
+//!     match client.get("203.0.113.7").await {
+//!         Ok(ip) => println!("{} - {} ({:?})", ip.ip, ip.isp, ip.category),
+//!         Err(error) => println!("Error: {}", error),
+//!     };
+//! }
+//! ```
+//!
+//! [`Locator::get_with_overrides`] does the same with a default client.
+//!
+//! ## Special-Purpose Addresses
+//! Private, loopback, link-local, documentation and other special-purpose addresses from the
+//! IANA registries are recognised locally. Looking one up fails with
+//! [`GeoError::SpecialAddress`] instead of being sent to a service, unless an override covers
+//! it, and [`special::classify`] can be used to check an address up front.
+//!
+//! ## IPv6 Transition Addresses
+//! IPv4-mapped (`::ffff:a.b.c.d`), 6to4, Teredo, NAT64 and ISATAP addresses are looked up through
+//! the IPv4 address embedded in them, and [`Locator::transition`] records how it was extracted.
+//! The IPv6 address is checked for special-purpose ranges first, so a link-local ISATAP address
+//! is refused rather than looked up. A [`Client`] can turn this off:
+//! ```no_run
+//! use ipgeolocate::{Client, Service};
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let client = Client::new(Service::IpApi).embedded_ipv4(false);
+//!
+//!     match client.get("2002:0101:0101::1").await {
+//!         Ok(ip) => println!("{} - {} ({})", ip.ip, ip.city, ip.country),
+//!         Err(error) => println!("Error: {}", error),
+//!     };
+//! }
+//! ```
+//!
+//! ## Hostnames
+//! [`Locator::get_host`] resolves a hostname's A and AAAA records and looks up every address it
+//! finds. A [`Client`] can be given a [`Resolver`] that queries specific name servers instead
+//! of the system's:
+//! ```no_run
+//! use ipgeolocate::{Client, Resolver, Service};
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let resolver = Resolver::with_name_servers(&["1.1.1.1:53".parse().unwrap()]);
+//!     let client = Client::new(Service::IpApi).resolver(resolver);
+//!
+//!     match client.get_host("example.com").await {
+//!         Ok(locations) => {
+//!             for (address, location) in locations {
+//!                 match location {
+//!                     Ok(ip) => println!("{} - {} ({})", address, ip.city, ip.country),
+//!                     Err(error) => println!("{} - Error: {}", address, error),
+//!                 }
+//!             }
+//!         }
+//!         Err(error) => println!("Error: {}", error),
+//!     };
+//! }
+//! ```
+//!
+//! ## Where Am I?
+//! [`Locator::get_self`] finds the host's public address by asking several echo services and
+//! requiring them to agree, then looks it up. A [`Discovery`] controls which echo services are
+//! used and how many have to agree:
+//! ```no_run
+//! use ipgeolocate::{Client, Discovery, Service};
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let discovery = Discovery::new()
+//!         .echo_services(&["https://icanhazip.com", "http://ip-api.com/line/?fields=query"])
+//!         .quorum(2);
+//!     let client = Client::new(Service::IpApi).discovery(discovery);
+//!
+//!     match client.get_self().await {
+//!         Ok(ip) => println!("{} - {} ({})", ip.ip, ip.city, ip.country),
+//!         Err(error) => println!("Error: {}", error),
+//!     };
+//! }
+//! ```
+//!
+//! Echo services are asked under the client's [`TransportPolicy`], so an `https_only` client
+//! skips plain HTTP ones like ip-api.com's.
+//!
+//! HTTP echo services see a proxy's address instead of the host's when requests go through one.
+//! [STUN](stun) servers answer over UDP instead and can be used alongside or instead of them:
+//! ```no_run
+//! use ipgeolocate::stun::DEFAULT_STUN_SERVERS;
+//! use ipgeolocate::Discovery;
+//!
+//! let discovery = Discovery::new()
+//!     .echo_services(&[])
+//!     .stun_servers(DEFAULT_STUN_SERVERS);
+//! ```
+//!
+//! ## Network Ownership
+//! [`Locator::network`](Locator#structfield.network) holds the AS number, AS name, organisation
+//! and announced prefix of an address, from whichever of those the service sent. An offline ASN
+//! database (like GeoLite2-ASN) can fill in the rest:
+//! ```no_run
+//! use ipgeolocate::{Client, Database, Service};
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let asn = Database::open("GeoLite2-ASN.mmdb").unwrap();
+//!     let client = Client::new(Service::IpWhois).asn_database(asn);
+//!
+//!     match client.get("1.1.1.1").await {
+//!         Ok(ip) => println!("{} - {:?}", ip.ip, ip.network),
+//!         Err(error) => println!("Error: {}", error),
+//!     };
+//! }
+//! ```
+//!
+//! ## Proxies, VPNs and Tor
+//! [`Locator::security`](Locator#structfield.security) says whether an address is a proxy, VPN,
+//! Tor exit or hosting provider. Each flag is yes, no or unknown, and records which service set
+//! it; only ip-api.com (when the fields are selected), ipinfo.io and the ipwhois and ipapi.co paid
+//! plans report them:
+//! ```no_run
+//! use ipgeolocate::ipapi::Field;
+//! use ipgeolocate::{Client, Service};
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let client = Client::new(Service::IpApi).ipapi_fields(&[Field::Proxy, Field::Hosting]);
+//!
+//!     match client.get("1.1.1.1").await {
+//!         Ok(ip) => println!("proxy: {}, hosting: {}", ip.security.proxy.flag, ip.security.hosting.flag),
+//!         Err(error) => println!("Error: {}", error),
+//!     };
+//! }
+//! ```
+//!
+//! ## Tor and VPN Lists
+//! Without a paid plan, proxies can still be caught with offline lists: the Tor Project's exit
+//! node list and your own VPN or proxy range files (plain text or CSV). Lookups on a list are
+//! flagged with the list's file name as the source, and the files can be polled for changes:
+//! ```no_run
+//! use ipgeolocate::anonymizers::Anonymizers;
+//! use ipgeolocate::{Client, Service};
+//! use std::time::Duration;
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let anonymizers = Anonymizers::new()
+//!         .tor_exits("exit-addresses")
+//!         .unwrap()
+//!         .vpn_ranges("vpn-ranges.txt")
+//!         .unwrap();
+//!     let _watcher = anonymizers.watch(Duration::from_secs(300));
+//!
+//!     let client = Client::new(Service::IpApi).anonymizers(anonymizers);
+//!
+//!     match client.get("185.220.101.1").await {
+//!         Ok(ip) => println!("tor: {} ({:?})", ip.security.tor.flag, ip.security.tor.source),
+//!         Err(error) => println!("Error: {}", error),
+//!     };
+//! }
+//! ```
+//!
+//! ## Cloud Providers
+//! Addresses in AWS, Google Cloud, Azure, Cloudflare or Oracle Cloud are better described by their
+//! cloud region than by a city. Load the range files the providers publish, and results in them get
+//! the provider, service and region in [`cloud`](Locator::cloud) and are flagged as hosting. With
+//! [`locate`](cloud::CloudRanges::locate), the location is replaced by the region's documented one:
+//! ```no_run
+//! use ipgeolocate::cloud::CloudRanges;
+//! use ipgeolocate::{Client, Service};
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let ranges = CloudRanges::new()
+//!         .aws("ip-ranges.json")
+//!         .unwrap()
+//!         .gcp("cloud.json")
+//!         .unwrap()
+//!         .cloudflare("ips-v4")
+//!         .unwrap()
+//!         .locate(true);
+//!
+//!     let client = Client::new(Service::IpApi).cloud_ranges(ranges);
+//!
+//!     match client.get("3.5.140.2").await {
+//!         Ok(ip) => {
+//!             if let Some(cloud) = ip.cloud {
+//!                 println!("{} {:?} in {:?} ({})", cloud.provider, cloud.service, cloud.region, ip.city);
+//!             }
+//!         }
+//!         Err(error) => println!("Error: {}", error),
+//!     };
+//! }
+//! ```
+//!
+//! ## Time Zones
+//! Results carry the service's time zone name in [`timezone`](Locator::timezone) and the same
+//! zone parsed against a bundled tz database in [`timezone_info`](Locator::timezone_info),
+//! which gives the current offset, daylight saving time and local time. Names the database
+//! doesn't know are left out of `timezone_info`, and the parse error is kept in
+//! [`timezone_error`](Locator::timezone_error):
+//! ```no_run
+//! use ipgeolocate::{Locator, Service};
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let ip = Locator::get("1.1.1.1", Service::IpApi).await.unwrap();
+//!
+//!     if let Some(zone) = ip.timezone_info {
+//!         println!("{} is UTC{} (DST: {}), local time {}", zone, zone.offset(), zone.is_dst(), zone.now());
+//!     }
+//! }
+//! ```
+//!
+//! ## Country Details
+//! Every result carries ISO country codes, and [`Locator::country_info`] looks up the country's
+//! continent, currency, calling code, EU/EEA membership and flag in a bundled table, so they're
+//! the same whichever service answered:
+//! ```no_run
+//! use ipgeolocate::{Locator, Service};
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let ip = Locator::get("1.1.1.1", Service::IpApi).await.unwrap();
+//!
+//!     if let Some(country) = ip.country_info() {
+//!         println!("{} {} ({}, {:?})", country.flag(), country.name, country.continent, country.currency);
+//!     }
+//! }
+//! ```
+//!
+//! ## Raw Responses
+//! Results only have the fields this crate knows how to read. To read anything else a service
+//! sends, have the client keep the raw response: the decoded JSON, the HTTP status and the rate
+//! limit headers are then in [`raw`](Locator::raw), and [`Raw::get`](raw::Raw::get) reads a key (or
+//! a JSON pointer into nested objects) as any type:
+//! ```no_run
+//! use ipgeolocate::{Client, Service};
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let client = Client::new(Service::IpWhois).keep_raw(true);
+//!
+//!     match client.get("1.1.1.1").await {
+//!         Ok(ip) => {
+//!             if let Some(raw) = ip.raw {
+//!                 let flag: Option<String> = raw.get("/flag/emoji");
+//!                 println!("{} {:?} ({:?})", raw.status, flag, raw.header("content-type"));
+//!             }
+//!         }
+//!         Err(error) => println!("Error: {}", error),
+//!     };
+//! }
+//! ```
+//!
+//! Each service's response is also modelled as a `Response` struct in its module
+//! ([`ipapi`], [`ipwhois`], [`ipapico`], [`freegeoip`] and [`ipinfo`]), which the raw body can be
+//! deserialized into; the paid ipwhois.pro has its own [`ProResponse`](ipwhois::ProResponse).
+//! When a service changes its format, the lookup fails with the path of the field that didn't
+//! match and the type that was expected.
+//!
+//! ## Partial Results
+//! By default a lookup fails if the service leaves out a field, which ipapi.co does with the city
+//! of many addresses. A [`lenient`](Client::lenient) client only fails when an essential field is
+//! missing; the others are left empty and listed in [`warnings`](Locator::warnings):
+//! ```no_run
+//! use ipgeolocate::{lenient, Client, Service};
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let client = Client::new(Service::IpApiCo).lenient(lenient::ESSENTIAL);
+//!
+//!     match client.get("1.1.1.1").await {
+//!         Ok(ip) => {
+//!             println!("{}, {} ({})", ip.latitude, ip.longitude, ip.country);
+//!             for warning in ip.warnings {
+//!                 println!("warning: {}", warning);
+//!             }
+//!         }
+//!         Err(error) => println!("Error: {}", error),
+//!     };
+//! }
+//! ```
+//!
+//! ## Capabilities
+//! The services don't all send the same fields, and differ in quota, HTTPS and batching.
+//! [`Service::capabilities`] describes each one, and [`Client::for_fields`] picks the first
+//! service that supplies everything you need on its free plan:
+//! ```no_run
+//! use ipgeolocate::capabilities::Field;
+//! use ipgeolocate::{Client, Service};
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     println!("ipinfo.io: {}", Service::IpInfo.capabilities().quota);
+//!
+//!     let client = Client::for_fields(&[Field::City, Field::Postal, Field::MetroCode], true).unwrap();
+//!
+//!     match client.get("1.1.1.1").await {
+//!         Ok(ip) => println!("{} {} ({:?})", ip.city, ip.postal, ip.metro_code),
+//!         Err(error) => println!("Error: {}", error),
+//!     };
+//! }
+//! ```
+//!
+//! If no service can, it fails with [`GeoError::NoService`]. ip-api.com only sends security
+//! flags when they're selected ([`Capabilities::selected_fields`]); [`Client::for_fields`]
+//! selects them for you.
+//!
 //! ## Fields
-//! The API can get these fields about IP addresses.
+//! The API can get these fields about IP addresses. Country and region codes are ISO 3166, whichever
+//! service answered (see [`iso`]).
 //!
 //! - [`ip`](crate::Locator::ip)
 //! - [`latitude`](crate::Locator::latitude)
@@ -54,7 +552,21 @@
 //! - [`city`](crate::Locator::city)
 //! - [`region`](crate::Locator::region)
 //! - [`country`](crate::Locator::country)
+//! - [`country_code`](crate::Locator::country_code)
+//! - [`country_code3`](crate::Locator::country_code3)
+//! - [`region_code`](crate::Locator::region_code)
 //! - [`timezone`](crate::Locator::timezone)
+//! - [`timezone_info`](crate::Locator::timezone_info)
+//! - [`timezone_error`](crate::Locator::timezone_error)
+//! - [`isp`](crate::Locator::isp)
+//! - [`network`](crate::Locator#structfield.network)
+//! - [`security`](crate::Locator#structfield.security)
+//! - [`cloud`](crate::Locator::cloud)
+//! - [`postal`](crate::Locator::postal)
+//! - [`metro_code`](crate::Locator::metro_code)
+//! - [`accuracy_radius`](crate::Locator::accuracy_radius)
+//! - [`raw`](crate::Locator::raw)
+//! - [`warnings`](crate::Locator::warnings)
 //!
 //! ## Credits
 //! Grant Handy <grantshandy@gmail.com>
@@ -64,23 +576,71 @@
 //! - [freegeoip.app](https://ipwhois.app/)
 //! - [ip-api.com](https://ip-api.com/)
 //! - [ipapi.co](https://ipapi.co/)
+//! - [ipinfo.io](https://ipinfo.io/)
 //!
 //! Written with love, in Rust.
 //!
 
-use reqwest::get;
+pub mod anonymizers;
+pub mod capabilities;
+pub mod client;
+pub mod cloud;
+pub mod discovery;
+pub mod dns;
+pub mod freegeoip;
+pub mod ipapi;
+pub mod ipapico;
+pub mod ipinfo;
+pub mod ipwhois;
+pub mod iso;
+pub mod language;
+pub mod lenient;
+pub mod network;
+pub mod offline;
+pub mod overrides;
+pub mod raw;
+pub mod security;
+pub mod special;
+pub mod stun;
+pub mod threat;
+pub mod timezone;
+pub mod transition;
+pub mod update;
+
+pub use capabilities::Capabilities;
+pub use client::Client;
+pub use cloud::Cloud;
+pub use discovery::{Discovery, PublicAddresses};
+pub use dns::Resolver;
+pub use network::Network;
+pub use offline::Database;
+pub use overrides::{Override, Overrides};
+pub use raw::Raw;
+pub use security::TransportPolicy;
+pub use special::AddressCategory;
+pub use threat::Security;
+pub use timezone::TimeZone;
+pub use transition::Transition;
+pub use update::{UpdateStatus, Updater};
+
+use lenient::Warning;
+use reqwest::header::HeaderMap;
+use reqwest::StatusCode;
+use serde::de::DeserializeOwned;
 use serde_json::Value;
+use std::collections::BTreeMap;
 use std::fmt;
 use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
-use tracing::debug;
+use tracing::{debug, warn};
 
 /// Services (apis) that can be used for accessing geolocation data.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Service {
     IpWhois,
     IpApi,
     IpApiCo,
     FreeGeoIp,
+    IpInfo,
 }
 
 impl fmt::Display for Service {
@@ -98,15 +658,36 @@ impl fmt::Display for Service {
             Service::FreeGeoIp => {
                 write!(f, "freegeoip")
             }
+            Service::IpInfo => {
+                write!(f, "ipinfo")
+            }
         }
     }
 }
 
+impl Service {
+    /// What this service can do: the fields it supplies, IPv6 and HTTPS support, batching,
+    /// its free quota and whether it needs a key (see [`capabilities`]).
+    pub fn capabilities(&self) -> &'static Capabilities {
+        capabilities::of(*self)
+    }
+}
+
 #[derive(Debug, Clone)]
 /// A general ipgeolocate error for requests.
 pub enum GeoError {
     HttpError(String),
     ParseError(String),
+    DatabaseError(String),
+    /// The address is in a special-purpose range (private, loopback, etc.) and has no location.
+    SpecialAddress(IpAddr, AddressCategory),
+    DnsError(String),
+    DiscoveryError(String),
+    /// The [`TransportPolicy`] forbids reaching the service over plain HTTP.
+    InsecureTransport(String),
+    TransportError(String),
+    /// No service can supply the requested fields (see [`Client::for_fields`]).
+    NoService(String),
 }
 
 impl std::error::Error for GeoError {}
@@ -120,12 +701,33 @@ impl fmt::Display for GeoError {
             GeoError::ParseError(error) => {
                 write!(f, "JSON Parsing Error: {}", error)
             }
+            GeoError::DatabaseError(error) => {
+                write!(f, "Offline Database Error: {}", error)
+            }
+            GeoError::SpecialAddress(ip, category) => {
+                write!(f, "Special-Purpose Address: {} is {}", ip, category)
+            }
+            GeoError::DnsError(error) => {
+                write!(f, "DNS Resolution Error: {}", error)
+            }
+            GeoError::DiscoveryError(error) => {
+                write!(f, "Public Address Discovery Error: {}", error)
+            }
+            GeoError::InsecureTransport(error) => {
+                write!(f, "Insecure Transport Error: {}", error)
+            }
+            GeoError::TransportError(error) => {
+                write!(f, "Transport Security Error: {}", error)
+            }
+            GeoError::NoService(error) => {
+                write!(f, "No Suitable Service: {}", error)
+            }
         }
     }
 }
 
 /// This is the main struct for making requests to the APIs.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct Locator {
     /// Returns the IP address.
     pub ip: String,
@@ -139,19 +741,97 @@ pub struct Locator {
     pub region: String,
     /// Country of the IP address.
     pub country: String,
+    /// ISO 3166-1 alpha-2 code of the country, like `US`.
+    pub country_code: String,
+    /// ISO 3166-1 alpha-3 code of the country, like `USA`.
+    pub country_code3: String,
+    /// ISO 3166-2 code of the region, like `US-CA`, if it's known (see [`iso`]).
+    ///
+    /// Subdivisions are only bundled for Australia, Brazil, Canada, Germany, Mexico and the
+    /// US (see [`iso::SUBDIVISIONS`]). Elsewhere the code is only known when the source sends
+    /// one, as ip-api.com, ipapi.co, freegeoip.app and offline databases do, so it's empty for
+    /// ipwhois and ipinfo.io results outside those countries.
+    pub region_code: String,
     /// Timezone of the IP address.
     pub timezone: String,
+    /// [`timezone`](Locator::timezone) parsed against the tz database. `None` if the service
+    /// didn't send one or sent a name the database doesn't know.
+    pub timezone_info: Option<TimeZone>,
+    /// Why [`timezone`](Locator::timezone) couldn't be parsed, when the service sent a name
+    /// the tz database doesn't know. `None` if it parsed or there was none to parse.
+    pub timezone_error: Option<GeoError>,
     /// ISP of the IP address
     pub isp: String,
+    /// The autonomous system and organisation the address belongs to, if any source knew.
+    pub network: Option<Network>,
+    /// Whether the address is a proxy, VPN, Tor exit or hosting provider, as far as is known.
+    pub security: Security,
+    /// The cloud provider, service and region the address belongs to, if it's in one of the
+    /// ranges given to [`Client::cloud_ranges`].
+    pub cloud: Option<Cloud>,
+    /// Postal code of the IP address.
+    pub postal: String,
+    /// Nielsen DMA code of the IP address, for addresses in the US.
+    pub metro_code: Option<u16>,
+    /// How far from [`latitude`](Locator::latitude) and [`longitude`](Locator::longitude) the
+    /// address may be, in kilometres. Only offline databases report it; the wider it is, the
+    /// less a city-level answer can be trusted.
+    pub accuracy_radius: Option<u32>,
+    /// The special-purpose range the address is in, for results answered by an override
+    /// (see [`Client::overrides`]). Other lookups of special-purpose addresses fail with
+    /// [`GeoError::SpecialAddress`].
+    pub category: Option<AddressCategory>,
+    /// Set when an IPv6 transition address was looked up through its embedded IPv4 address,
+    /// which is then what [`ip`](Locator::ip) holds.
+    pub transition: Option<Transition>,
+    /// Extra fields returned by [`Service::IpApi`], when they're selected with
+    /// [`Client::ipapi_fields`].
+    pub ipapi: Option<ipapi::Details>,
+    /// Extra fields returned by [`Service::IpInfo`].
+    pub ipinfo: Option<ipinfo::Details>,
+    /// The response the service sent, if the client was set to
+    /// [`keep_raw`](Client::keep_raw). Always `None` for offline lookups.
+    pub raw: Option<Raw>,
+    /// Fields the service didn't send, which were left empty because the client is
+    /// [`lenient`](Client::lenient).
+    pub warnings: Vec<Warning>,
 }
 
 impl Locator {
+    /// Fills in the fields that are worked out from what the service sent: ISO codes and
+    /// the typed time zone.
+    pub(crate) fn normalize(&mut self) {
+        iso::normalize(self);
+
+        self.timezone_info = None;
+        self.timezone_error = None;
+
+        if !self.timezone.is_empty() {
+            match TimeZone::parse(&self.timezone) {
+                Ok(time_zone) => self.timezone_info = Some(time_zone),
+                Err(error) => {
+                    warn!("{}", error);
+                    self.timezone_error = Some(error);
+                }
+            }
+        }
+    }
+
+    /// Bundled details about the country of the IP address, like its continent, currency and
+    /// calling code. `None` if the country couldn't be identified.
+    pub fn country_info(&self) -> Option<&'static iso::Country> {
+        iso::country(&self.country_code)
+    }
+
     /// Gets IP information from an [`Ipv4Addr`]
     pub async fn get_ipv4(ip: Ipv4Addr, service: Service) -> std::result::Result<Self, GeoError> {
         Locator::get(&ip.to_string(), service).await
     }
 
     /// Gets IP information from an [`Ipv6Addr`]
+    ///
+    /// Transition addresses (IPv4-mapped, 6to4, Teredo, NAT64 and ISATAP) are looked up
+    /// through the IPv4 address embedded in them. Use a [`Client`] to turn this off.
     pub async fn get_ipv6(ip: Ipv6Addr, service: Service) -> std::result::Result<Self, GeoError> {
         Locator::get(&ip.to_string(), service).await
     }
@@ -164,21 +844,62 @@ impl Locator {
     /// [`IpAddr`]: std::net::IpAddr
     /// [`Ipv4Addr`]: std::net::Ipv4Addr
     /// [`Ipv6Addr`]: std::net::Ipv6Addr
-
+    ///
     /// Gets IP information from just a string (not recommended for most uses)
+    ///
+    /// Special-purpose addresses (see [`special`]) fail with [`GeoError::SpecialAddress`]
+    /// without contacting `service`.
     pub async fn get(ip: &str, service: Service) -> std::result::Result<Self, GeoError> {
-        match service {
-            Service::IpWhois => Locator::ipwhois(ip).await,
-            Service::IpApi => Locator::ipapi(ip).await,
-            Service::IpApiCo => Locator::ipapico(ip).await,
-            Service::FreeGeoIp => Locator::freegeoip(ip).await,
-        }
+        Client::new(service).get(ip).await
+    }
+
+    /// Resolves `host` and gets IP information for each of its addresses.
+    pub async fn get_host(
+        host: &str,
+        service: Service,
+    ) -> std::result::Result<BTreeMap<IpAddr, std::result::Result<Self, GeoError>>, GeoError> {
+        Client::new(service).get_host(host).await
+    }
+
+    /// Gets IP information for several addresses, in one request where the service allows it.
+    ///
+    /// See [`Client::get_batch`].
+    pub async fn get_batch(
+        ips: &[&str],
+        service: Service,
+    ) -> std::result::Result<BTreeMap<String, std::result::Result<Self, GeoError>>, GeoError> {
+        Client::new(service).get_batch(ips).await
+    }
+
+    /// Finds this host's public IP address and gets IP information for it.
+    ///
+    /// See [`Client::get_self`].
+    pub async fn get_self(service: Service) -> std::result::Result<Self, GeoError> {
+        Client::new(service).get_self().await
     }
 
-    async fn freegeoip(ip: &str) -> std::result::Result<Self, GeoError> {
+    /// Gets IP information from an offline [`Database`] instead of a service.
+    pub fn get_offline(ip: IpAddr, database: &Database) -> std::result::Result<Self, GeoError> {
+        database.lookup(ip)
+    }
+
+    /// Gets IP information, using the matching entry in `overrides` before asking `service`.
+    pub async fn get_with_overrides(
+        ip: &str,
+        service: Service,
+        overrides: &Overrides,
+    ) -> std::result::Result<Self, GeoError> {
+        overrides.get(ip, service).await
+    }
+
+    pub(crate) async fn freegeoip(
+        ip: &str,
+        client: &Client,
+    ) -> std::result::Result<Self, GeoError> {
         let url = format!("https://freegeoip.app/json/{}", ip);
+        let http = client.http(&url)?;
 
-        let response = match get(&url).await.unwrap().text().await {
+        let (status, headers, response) = match fetch(&http, &url).await {
             Ok(response) => response,
             Err(_) => {
                 return Err(GeoError::HttpError(
@@ -198,95 +919,34 @@ impl Locator {
             }
         };
 
-        // Get latitude from parsed_json
-        let latitude = match &parsed_json["latitude"] {
-            Value::Number(latitude) => latitude,
-            _ => {
-                return Err(GeoError::ParseError(
-                    "Unable to find latitude in parsed JSON".to_string(),
-                ))
-            }
-        };
-
-        // Get longitude from parsed_json
-        let longitude = match &parsed_json["longitude"] {
-            Value::Number(longitude) => longitude,
-            _ => {
-                return Err(GeoError::ParseError(
-                    "Unable to find longitude in parsed JSON".to_string(),
-                ));
-            }
-        };
-
-        let city = match &parsed_json["city"] {
-            Value::String(city_str) => city_str,
-            _ => {
-                return Err(GeoError::ParseError(
-                    "Unable to find city in parsed JSON".to_string(),
-                ));
-            }
-        };
+        let response: freegeoip::Response = decode(&parsed_json, "freegeoip.app")?;
 
-        let region = match &parsed_json["region_name"] {
-            Value::String(region_str) => region_str,
-            _ => {
-                return Err(GeoError::ParseError(
-                    "Unable to find region in parsed JSON".to_string(),
-                ));
-            }
-        };
+        let mut result = response.locator(ip, client)?;
+        result.raw = client.raw(status, &headers, &parsed_json);
 
-        let country = match &parsed_json["country_name"] {
-            Value::String(country_str) => country_str,
-            _ => {
-                return Err(GeoError::ParseError(
-                    "Unable to find country in parsed JSON".to_string(),
-                ));
-            }
-        };
+        Ok(result)
+    }
 
-        let timezone = match &parsed_json["time_zone"] {
-            Value::String(timezone_str) => timezone_str,
-            _ => {
-                return Err(GeoError::ParseError(
-                    "Unable to find timezone in parsed JSON".to_string(),
-                ));
-            }
+    pub(crate) async fn ipwhois(ip: &str, client: &Client) -> std::result::Result<Self, GeoError> {
+        // Paid plans use ipwhois.pro, which is also the only one served over HTTPS.
+        let (url, host) = match client.key() {
+            Some(key) => (
+                format!("https://ipwhois.pro/{}?key={}", ip, key),
+                "ipwhois.pro",
+            ),
+            None => (format!("http://ipwhois.app/json/{}", ip), "ipwhois.app"),
         };
 
-        let ip = ip.to_string();
-        let latitude = latitude.to_string();
-        let longitude = longitude.to_string();
-        let city = city.to_string();
-        let region = region.to_string();
-        let country = country.to_string();
-        let timezone = timezone.to_string();
-        let isp = String::default();
-
-        let result = Locator {
-            ip,
-            latitude,
-            longitude,
-            city,
-            region,
-            country,
-            timezone,
-            isp,
+        let url = match client.language(language::IPWHOIS_LANGUAGES) {
+            Some(lang) => with_query(&url, "lang", lang),
+            None => url,
         };
 
-        Ok(result)
-    }
-
-    async fn ipwhois(ip: &str) -> std::result::Result<Self, GeoError> {
-        let url = format!("http://ipwhois.app/json/{}", ip);
+        let http = client.http(&url)?;
 
-        let response = match get(&url).await.unwrap().text().await {
+        let (status, headers, response) = match fetch(&http, &url).await {
             Ok(response) => response,
-            Err(_) => {
-                return Err(GeoError::HttpError(
-                    "Couldn't connect to ipwhois.app".to_string(),
-                ))
-            }
+            Err(_) => return Err(GeoError::HttpError(format!("Couldn't connect to {}", host))),
         };
 
         // Turn the data into parsed_json
@@ -300,104 +960,52 @@ impl Locator {
             }
         };
 
-        let success = match &parsed_json["success"] {
-            Value::Bool(latitude_str) => latitude_str,
-            _ => {
-                return Err(GeoError::ParseError(
-                    "Cannot find success in JSON".to_string(),
-                ));
+        // e.g. "Invalid API key" on ipwhois.pro
+        let failed = |message: Option<String>| match message {
+            Some(message) => {
+                GeoError::HttpError(format!("{} returned an error: {}", host, message))
             }
+            None => GeoError::ParseError("You've hit the monthly limit".to_string()),
         };
 
-        if !success {
-            return Err(GeoError::ParseError(
-                "You've hit the monthly limit".to_string(),
-            ));
-        }
-
-        // Get latitude from parsed_json
-        let latitude_str = match &parsed_json["latitude"] {
-            Value::String(latitude_str) => latitude_str,
-            _ => {
-                return Err(GeoError::ParseError(
-                    "Unable to find latitude in parsed JSON".to_string(),
-                ));
+        let mut result = if client.key().is_some() {
+            let response: ipwhois::ProResponse = decode(&parsed_json, host)?;
+            if !response.success {
+                return Err(failed(response.message));
             }
-        };
-
-        // Get longitude from parsed_json
-        let longitude_str = match &parsed_json["longitude"] {
-            Value::String(longitude_str) => longitude_str,
-            _ => {
-                return Err(GeoError::ParseError(
-                    "Unable to find longitude in parsed JSON".to_string(),
-                ));
-            }
-        };
-
-        let city_str = match &parsed_json["city"] {
-            Value::String(city_str) => city_str,
-            _ => {
-                return Err(GeoError::ParseError(
-                    "Unable to find city in parsed JSON".to_string(),
-                ));
-            }
-        };
-
-        let region_str = match &parsed_json["region"] {
-            Value::String(region_str) => region_str,
-            _ => {
-                return Err(GeoError::ParseError(
-                    "Unable to find region in parsed JSON".to_string(),
-                ));
+            response.locator(ip, client)?
+        } else {
+            let response: ipwhois::Response = decode(&parsed_json, host)?;
+            if !response.success {
+                return Err(failed(response.message));
             }
+            response.locator(ip, client)?
         };
+        result.raw = client.raw(status, &headers, &parsed_json);
 
-        let country_str = match &parsed_json["country"] {
-            Value::String(country_str) => country_str,
-            _ => {
-                return Err(GeoError::ParseError(
-                    "Unable to find country in parsed JSON".to_string(),
-                ));
-            }
-        };
+        Ok(result)
+    }
 
-        let timezone_str = match &parsed_json["timezone"] {
-            Value::String(timezone_str) => timezone_str,
-            _ => {
-                return Err(GeoError::ParseError(
-                    "Unable to find timezone in parsed JSON".to_string(),
-                ));
-            }
+    pub(crate) async fn ipapi(ip: &str, client: &Client) -> std::result::Result<Self, GeoError> {
+        // The free endpoint is HTTP only; pro.ip-api.com takes a key and supports HTTPS.
+        let mut url = match client.key() {
+            Some(key) => format!("https://pro.ip-api.com/json/{}?key={}", ip, key),
+            None => format!("http://ip-api.com/json/{}", ip),
         };
 
-        let ip = ip.to_string();
-        let latitude = latitude_str.to_string();
-        let longitude = longitude_str.to_string();
-        let city = city_str.to_string();
-        let region = region_str.to_string();
-        let country = country_str.to_string();
-        let timezone = timezone_str.to_string();
-
-        let result = Locator {
-            ip,
-            latitude,
-            longitude,
-            city,
-            region,
-            country,
-            timezone,
-            isp: String::default(),
-        };
+        let fields = client.fields();
+        if let Some(fields) = fields {
+            url = with_query(&url, "fields", &ipapi::query(fields));
+        }
 
-        Ok(result)
-    }
+        if let Some(lang) = client.language(language::IPAPI_LANGUAGES) {
+            url = with_query(&url, "lang", lang);
+        }
 
-    async fn ipapi(ip: &str) -> std::result::Result<Self, GeoError> {
-        let url = format!("http://ip-api.com/json/{}", ip);
+        let http = client.http(&url)?;
 
-        let response = match get(&url).await {
-            Ok(resp) => resp,
+        let (status, headers, response_body) = match fetch(&http, &url).await {
+            Ok(response) => response,
             Err(_) => {
                 return Err(GeoError::HttpError(
                     "Couldn't connect to ip-api.com".to_string(),
@@ -405,14 +1013,11 @@ impl Locator {
             }
         };
 
-        let response_body = match response.text().await {
-            Ok(response_body) => response_body,
-            Err(_) => {
-                return Err(GeoError::HttpError(
-                    "Couldn't read data from ip-api.com".to_string(),
-                ))
-            }
-        };
+        if status == StatusCode::FORBIDDEN {
+            return Err(GeoError::HttpError(
+                "ip-api.com rejected the API key".to_string(),
+            ));
+        }
 
         // Turn the data into parsed_json
         let parsed_json: Value = match serde_json::from_str(&response_body) {
@@ -427,98 +1032,38 @@ impl Locator {
 
         debug!("ipgeolocate return object looks like: {}", parsed_json);
 
-        // Get latitude from parsed_json
-        let latitude = match &parsed_json["lat"] {
-            Value::Number(latitude) => latitude,
-            _ => {
-                return Err(GeoError::ParseError(
-                    "Unable to find latitude in parsed JSON".to_string(),
-                ));
-            }
-        };
-
-        // Get longitude from parsed_json
-        let longitude = match &parsed_json["lon"] {
-            Value::Number(longitude) => longitude,
-            _ => {
-                return Err(GeoError::ParseError(
-                    "Unable to find longitude in parsed JSON".to_string(),
-                ));
-            }
-        };
-
-        let city = match &parsed_json["city"] {
-            Value::String(city_str) => city_str,
-            _ => {
-                return Err(GeoError::ParseError(
-                    "Unable to find city in parsed JSON".to_string(),
-                ));
-            }
-        };
-
-        let region = match &parsed_json["regionName"] {
-            Value::String(region_str) => region_str,
-            _ => {
-                return Err(GeoError::ParseError(
-                    "Unable to find region in parsed JSON".to_string(),
-                ));
-            }
-        };
-
-        let country = match &parsed_json["country"] {
-            Value::String(country_str) => country_str,
-            _ => {
-                return Err(GeoError::ParseError(
-                    "Unable to find country in parsed JSON".to_string(),
-                ));
-            }
-        };
-
-        let timezone = match &parsed_json["timezone"] {
-            Value::String(timezone_str) => timezone_str,
-            _ => {
-                return Err(GeoError::ParseError(
-                    "Unable to find timezone in parsed JSON".to_string(),
-                ));
-            }
-        };
+        let response: ipapi::Response = decode(&parsed_json, "ip-api.com")?;
+
+        // Failed lookups (reserved ranges, invalid queries) come back with a message
+        if response.status.as_deref() == Some("fail") {
+            return match response.message {
+                Some(message) => Err(GeoError::HttpError(format!(
+                    "ip-api.com returned an error: {}",
+                    message
+                ))),
+                None => Err(GeoError::HttpError(
+                    "ip-api.com returned an error".to_string(),
+                )),
+            };
+        }
 
-        let isp = match &parsed_json["isp"] {
-            Value::String(isp) => isp,
-            _ => {
-                return Err(GeoError::ParseError(
-                    "Unable to find timezone in parsed JSON".to_string(),
-                ));
-            }
-        };
+        let details = ipapi::details(&parsed_json, client)?;
 
-        let ip = ip.to_string();
-        let latitude = latitude.to_string();
-        let longitude = longitude.to_string();
-        let city = city.to_string();
-        let region = region.to_string();
-        let country = country.to_string();
-        let timezone = timezone.to_string();
-        let isp = isp.to_string();
-
-        let result = Locator {
-            ip,
-            latitude,
-            longitude,
-            city,
-            region,
-            country,
-            timezone,
-            isp,
-        };
+        let mut result = response.locator(ip, client)?;
+        result.ipapi = details;
+        result.raw = client.raw(status, &headers, &parsed_json);
 
         Ok(result)
     }
 
-    async fn ipapico(ip: &str) -> std::result::Result<Self, GeoError> {
-        let url = format!("https://ipapi.co/{}/json/", ip);
+    pub(crate) async fn ipapico(ip: &str, client: &Client) -> std::result::Result<Self, GeoError> {
+        let url = match client.key() {
+            Some(key) => format!("https://ipapi.co/{}/json/?key={}", ip, key),
+            None => format!("https://ipapi.co/{}/json/", ip),
+        };
+        let http = client.http(&url)?;
 
-        let response = match get(&url).await.unwrap().text().await {
+        let (status, headers, response) = match fetch(&http, &url).await {
             Ok(response) => response,
             Err(_) => {
                 return Err(GeoError::HttpError(
@@ -538,82 +1083,45 @@ impl Locator {
             }
         };
 
-        // Get latitude from parsed_json
-        let latitude = match &parsed_json["latitude"] {
-            Value::Number(latitude) => latitude,
-            _ => {
-                return Err(GeoError::ParseError(
-                    "Unable to find latitude in parsed JSON".to_string(),
-                ))
-            }
-        };
-
-        // Get longitude from parsed_json
-        let longitude = match &parsed_json["longitude"] {
-            Value::Number(longitude) => longitude,
-            _ => {
-                return Err(GeoError::ParseError(
-                    "Unable to find longitude in parsed JSON".to_string(),
-                ));
-            }
-        };
-
-        let city = match &parsed_json["city"] {
-            Value::String(city_str) => city_str,
-            _ => {
-                return Err(GeoError::ParseError(
-                    "Unable to find city in parsed JSON".to_string(),
-                ));
-            }
-        };
+        let response: ipapico::Response = decode(&parsed_json, "ipapi.co")?;
 
-        let region = match &parsed_json["region"] {
-            Value::String(region_str) => region_str,
-            _ => {
-                return Err(GeoError::ParseError(
-                    "Unable to find region in parsed JSON".to_string(),
-                ));
-            }
-        };
+        let mut result = response.locator(ip, client)?;
+        result.raw = client.raw(status, &headers, &parsed_json);
 
-        let country = match &parsed_json["country_name"] {
-            Value::String(country_str) => country_str,
-            _ => {
-                return Err(GeoError::ParseError(
-                    "Unable to find country in parsed JSON".to_string(),
-                ));
-            }
-        };
+        Ok(result)
+    }
+}
 
-        let timezone = match &parsed_json["timezone"] {
-            Value::String(timezone_str) => timezone_str,
-            _ => {
-                return Err(GeoError::ParseError(
-                    "Unable to find timezone in parsed JSON".to_string(),
-                ));
-            }
-        };
+/// Sends a GET request to `url`, returning the response's status and headers with its body.
+async fn fetch(
+    http: &reqwest::Client,
+    url: &str,
+) -> std::result::Result<(StatusCode, HeaderMap, String), reqwest::Error> {
+    let response = http.get(url).send().await?;
+    let status = response.status();
+    let headers = response.headers().clone();
 
-        let ip = ip.to_string();
-        let latitude = latitude.to_string();
-        let longitude = longitude.to_string();
-        let city = city.to_string();
-        let region = region.to_string();
-        let country = country.to_string();
-        let timezone = timezone.to_string();
-        let isp: String = String::default();
-
-        let result = Locator {
-            ip,
-            latitude,
-            longitude,
-            city,
-            region,
-            country,
-            timezone,
-            isp,
-        };
+    Ok((status, headers, response.text().await?))
+}
 
-        Ok(result)
+/// Deserializes a service's response, naming the field that didn't match on failure.
+pub(crate) fn decode<T: DeserializeOwned>(
+    parsed_json: &Value,
+    service: &str,
+) -> std::result::Result<T, GeoError> {
+    match serde_path_to_error::deserialize(parsed_json) {
+        Ok(response) => Ok(response),
+        Err(error) => Err(GeoError::ParseError(format!(
+            "Unexpected {} response at `{}`: {}",
+            service,
+            error.path(),
+            error.inner()
+        ))),
     }
 }
+
+/// Appends a query parameter to `url`.
+fn with_query(url: &str, name: &str, value: &str) -> String {
+    let separator = if url.contains('?') { '&' } else { '?' };
+    format!("{}{}{}={}", url, separator, name, value)
+}
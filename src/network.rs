@@ -0,0 +1,131 @@
+//! Who owns the network an address is in.
+//!
+//! Services report the autonomous system (AS) announcing an address in different shapes:
+//! ip-api.com sends `AS13335 Cloudflare, Inc.`, ipinfo.io an `asn` object, and ipapi.co a
+//! bare `AS13335`. They're all collected into a [`Network`] on
+//! [`Locator::network`](crate::Locator#structfield.network). An offline ASN database can fill
+//! in whatever the service left out (see [`Client::asn_database`](crate::Client::asn_database)).
+
+use ipnet::IpNet;
+
+/// The autonomous system and organisation an address belongs to.
+///
+/// Each field is `None` when no source knew it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Network {
+    /// The autonomous system number, like `13335`.
+    pub asn: Option<u32>,
+    /// The name of the autonomous system, like `CLOUDFLARENET`.
+    pub as_name: Option<String>,
+    /// The organisation the address is registered to, like `Cloudflare, Inc.`.
+    pub organization: Option<String>,
+    /// The announced prefix containing the address, like `1.1.1.0/24`.
+    pub prefix: Option<IpNet>,
+}
+
+impl Network {
+    /// Whether no field is known.
+    pub fn is_empty(&self) -> bool {
+        self.asn.is_none()
+            && self.as_name.is_none()
+            && self.organization.is_none()
+            && self.prefix.is_none()
+    }
+
+    /// Fills the fields this network doesn't know from `other`.
+    pub fn merge(&mut self, other: Network) {
+        self.asn = self.asn.or(other.asn);
+        self.as_name = self.as_name.take().or(other.as_name);
+        self.organization = self.organization.take().or(other.organization);
+        self.prefix = self.prefix.or(other.prefix);
+    }
+
+    /// `Some(self)`, or `None` if no field is known.
+    pub(crate) fn non_empty(self) -> Option<Network> {
+        if self.is_empty() {
+            None
+        } else {
+            Some(self)
+        }
+    }
+}
+
+/// Parses an AS number written as `AS13335`, `13335` or `AS13335 Cloudflare, Inc.`.
+pub(crate) fn parse_asn(value: &str) -> Option<u32> {
+    let number = value.split_whitespace().next()?;
+    let number = number
+        .strip_prefix("AS")
+        .or_else(|| number.strip_prefix("as"))
+        .unwrap_or(number);
+
+    number.parse().ok()
+}
+
+/// Splits `AS13335 Cloudflare, Inc.` into the number and the name after it.
+pub(crate) fn split_as(value: &str) -> (Option<u32>, Option<String>) {
+    let asn = parse_asn(value);
+
+    let name = match value.trim().split_once(' ') {
+        Some((_, name)) if asn.is_some() => Some(name.trim().to_string()),
+        _ => None,
+    };
+
+    (asn, name.filter(|name| !name.is_empty()))
+}
+
+/// `Some` with the string, unless it's empty.
+pub(crate) fn non_empty(value: &str) -> Option<String> {
+    let value = value.trim();
+    if value.is_empty() {
+        None
+    } else {
+        Some(value.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_asn() {
+        assert_eq!(parse_asn("AS13335"), Some(13335));
+        assert_eq!(parse_asn("as13335"), Some(13335));
+        assert_eq!(parse_asn("13335"), Some(13335));
+        assert_eq!(parse_asn("AS13335 Cloudflare, Inc."), Some(13335));
+        assert_eq!(parse_asn("Cloudflare"), None);
+        assert_eq!(parse_asn(""), None);
+    }
+
+    #[test]
+    fn splits_as() {
+        assert_eq!(
+            split_as("AS13335 Cloudflare, Inc."),
+            (Some(13335), Some("Cloudflare, Inc.".to_string()))
+        );
+        assert_eq!(split_as("AS13335"), (Some(13335), None));
+        assert_eq!(split_as(" AS13335  "), (Some(13335), None));
+        assert_eq!(split_as("Cloudflare, Inc."), (None, None));
+    }
+
+    #[test]
+    fn drops_empty_values() {
+        assert_eq!(non_empty("  Cloudflare "), Some("Cloudflare".to_string()));
+        assert_eq!(non_empty("   "), None);
+
+        assert_eq!(Network::default().non_empty(), None);
+
+        let mut network = Network {
+            asn: Some(13335),
+            ..Network::default()
+        };
+        network.merge(Network {
+            asn: Some(1),
+            organization: Some("Cloudflare, Inc.".to_string()),
+            ..Network::default()
+        });
+        assert_eq!(network.asn, Some(13335));
+        assert_eq!(network.organization.as_deref(), Some("Cloudflare, Inc."));
+        assert!(network.non_empty().is_some());
+    }
+}
@@ -0,0 +1,764 @@
+//! Offline geolocation from MaxMind-format (`.mmdb`) database files.
+//!
+//! A [`Database`] handle can be cloned and shared between tasks. Calling
+//! [`Database::reload`] (or running a [`Watcher`]) swaps in a new file
+//! atomically: lookups already running keep using the version they started
+//! with, and a file that fails validation never replaces the loaded one.
+
+use crate::{language, network, GeoError, Locator, Network};
+use ipnet::IpNet;
+use maxminddb::{geoip2, MaxMindDBError, Reader};
+use std::fmt;
+use std::fs;
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, RwLock};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::{debug, warn};
+
+/// A loaded version of an offline database.
+struct Loaded {
+    reader: Reader<Vec<u8>>,
+    path: PathBuf,
+    modified: Option<SystemTime>,
+}
+
+/// A handle to an offline geolocation database that can be reloaded while in use.
+#[derive(Clone)]
+pub struct Database {
+    current: Arc<RwLock<Arc<Loaded>>>,
+}
+
+impl fmt::Debug for Database {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let loaded = self.snapshot();
+
+        f.debug_struct("Database")
+            .field("path", &loaded.path)
+            .field("database_type", &loaded.reader.metadata.database_type)
+            .finish()
+    }
+}
+
+impl Database {
+    /// Opens and validates the database at `path`.
+    pub fn open<P: AsRef<Path>>(path: P) -> std::result::Result<Self, GeoError> {
+        let loaded = Loaded::open(path.as_ref())?;
+
+        Ok(Database {
+            current: Arc::new(RwLock::new(Arc::new(loaded))),
+        })
+    }
+
+    /// Reloads the database from the file it was last loaded from.
+    pub fn reload(&self) -> std::result::Result<(), GeoError> {
+        let path = self.snapshot().path.clone();
+        self.reload_from(path)
+    }
+
+    /// Validates the database at `path` and, if it's usable, switches to it.
+    ///
+    /// The previously loaded version stays in place if validation fails.
+    pub fn reload_from<P: AsRef<Path>>(&self, path: P) -> std::result::Result<(), GeoError> {
+        let loaded = Loaded::open(path.as_ref())?;
+
+        debug!(
+            "switching offline database to {} (built {})",
+            loaded.path.display(),
+            loaded.reader.metadata.build_epoch
+        );
+
+        *self.current.write().unwrap() = Arc::new(loaded);
+
+        Ok(())
+    }
+
+    /// Reloads the database if its file has been modified since it was loaded.
+    ///
+    /// Returns whether a new version was loaded.
+    pub fn reload_if_changed(&self) -> std::result::Result<bool, GeoError> {
+        let loaded = self.snapshot();
+
+        if modified(&loaded.path) == loaded.modified {
+            return Ok(false);
+        }
+
+        self.reload_from(&loaded.path)?;
+        Ok(true)
+    }
+
+    /// Polls the database file every `interval` and reloads it when it changes.
+    ///
+    /// Polling stops when the returned [`Watcher`] is dropped.
+    pub fn watch(&self, interval: Duration) -> Watcher {
+        let database = self.clone();
+        Watcher::spawn(interval, "offline database", move || {
+            database.reload_if_changed()
+        })
+    }
+
+    /// The path of the currently loaded database file.
+    pub fn path(&self) -> PathBuf {
+        self.snapshot().path.clone()
+    }
+
+    /// The date the currently loaded database was built.
+    pub fn build_date(&self) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(self.snapshot().reader.metadata.build_epoch)
+    }
+
+    /// The type of the currently loaded database (e.g. `GeoLite2-City`).
+    pub fn database_type(&self) -> String {
+        self.snapshot().reader.metadata.database_type.clone()
+    }
+
+    /// Looks up an IP address in the currently loaded database.
+    ///
+    /// An ASN database only fills in [`Locator::network`](crate::Locator#structfield.network),
+    /// and an ISP database that and [`Locator::isp`](crate::Locator#structfield.isp).
+    pub fn lookup(&self, ip: IpAddr) -> std::result::Result<Locator, GeoError> {
+        self.lookup_localized(ip, &["en"])
+    }
+
+    /// Looks up the autonomous system an IP address belongs to in an ASN (or ISP) database.
+    pub fn network(&self, ip: IpAddr) -> std::result::Result<Network, GeoError> {
+        let loaded = self.snapshot();
+
+        let (asn, prefix_length) = match loaded.reader.lookup_prefix::<geoip2::Asn>(ip) {
+            Ok(found) => found,
+            Err(MaxMindDBError::AddressNotFoundError(_)) => {
+                return Err(GeoError::DatabaseError(format!(
+                    "{} isn't in the offline database",
+                    ip
+                )));
+            }
+            Err(error) => {
+                return Err(GeoError::DatabaseError(format!(
+                    "Couldn't read offline database: {}",
+                    error
+                )));
+            }
+        };
+
+        let network = Network {
+            asn: asn.autonomous_system_number,
+            organization: asn
+                .autonomous_system_organization
+                .and_then(network::non_empty),
+            prefix: IpNet::new(ip, prefix_length as u8)
+                .ok()
+                .map(|prefix| prefix.trunc()),
+            ..Network::default()
+        };
+
+        if network.asn.is_none() {
+            return Err(GeoError::DatabaseError(format!(
+                "The offline database has no autonomous system for {}",
+                ip
+            )));
+        }
+
+        Ok(network)
+    }
+
+    /// Looks up an IP address, naming the city, region and country in the first of
+    /// `languages` (BCP 47 tags) the database has names in, or English.
+    pub fn lookup_localized<S: AsRef<str>>(
+        &self,
+        ip: IpAddr,
+        languages: &[S],
+    ) -> std::result::Result<Locator, GeoError> {
+        let loaded = self.snapshot();
+
+        let database_type = &loaded.reader.metadata.database_type;
+        if database_type.contains("ASN") || database_type.contains("ISP") {
+            let isp = match loaded.reader.lookup::<geoip2::Isp>(ip) {
+                Ok(isp) if database_type.contains("ISP") => isp.isp.unwrap_or_default(),
+                _ => "",
+            };
+
+            return Ok(Locator {
+                ip: ip.to_string(),
+                isp: isp.to_string(),
+                network: Some(self.network(ip)?),
+                ..Locator::default()
+            });
+        }
+
+        let city: geoip2::City = match loaded.reader.lookup(ip) {
+            Ok(city) => city,
+            Err(MaxMindDBError::AddressNotFoundError(_)) => {
+                return Err(GeoError::DatabaseError(format!(
+                    "{} isn't in the offline database",
+                    ip
+                )));
+            }
+            Err(error) => {
+                return Err(GeoError::DatabaseError(format!(
+                    "Couldn't read offline database: {}",
+                    error
+                )));
+            }
+        };
+
+        let location = city.location.as_ref();
+
+        let latitude = location
+            .and_then(|location| location.latitude)
+            .map(|latitude| latitude.to_string())
+            .unwrap_or_default();
+        let longitude = location
+            .and_then(|location| location.longitude)
+            .map(|longitude| longitude.to_string())
+            .unwrap_or_default();
+        let timezone = location
+            .and_then(|location| location.time_zone)
+            .unwrap_or_default()
+            .to_string();
+        let metro_code = location.and_then(|location| location.metro_code);
+        // MaxMind databases give the radius in kilometres already.
+        let accuracy_radius = location
+            .and_then(|location| location.accuracy_radius)
+            .map(u32::from);
+
+        let postal = city
+            .postal
+            .as_ref()
+            .and_then(|postal| postal.code)
+            .unwrap_or_default()
+            .to_string();
+
+        let city_name = localized_name(
+            city.city.as_ref().and_then(|city| city.names.as_ref()),
+            languages,
+        );
+        let region = localized_name(
+            city.subdivisions
+                .as_ref()
+                .and_then(|subdivisions| subdivisions.first())
+                .and_then(|subdivision| subdivision.names.as_ref()),
+            languages,
+        );
+        let country = localized_name(
+            city.country
+                .as_ref()
+                .and_then(|country| country.names.as_ref()),
+            languages,
+        );
+
+        let country_code = city
+            .country
+            .as_ref()
+            .and_then(|country| country.iso_code)
+            .unwrap_or_default()
+            .to_string();
+        let region_code = city
+            .subdivisions
+            .as_ref()
+            .and_then(|subdivisions| subdivisions.first())
+            .and_then(|subdivision| subdivision.iso_code)
+            .unwrap_or_default()
+            .to_string();
+
+        let mut result = Locator {
+            ip: ip.to_string(),
+            latitude,
+            longitude,
+            city: city_name,
+            region,
+            country,
+            country_code,
+            region_code,
+            timezone,
+            isp: String::default(),
+            postal,
+            metro_code,
+            accuracy_radius,
+            ..Locator::default()
+        };
+
+        result.normalize();
+
+        Ok(result)
+    }
+
+    fn snapshot(&self) -> Arc<Loaded> {
+        self.current.read().unwrap().clone()
+    }
+}
+
+/// Stops polling a [`Database`] (or other file-backed data) for changes when dropped.
+pub struct Watcher {
+    stop: mpsc::Sender<()>,
+}
+
+impl Watcher {
+    /// Calls `reload_if_changed` every `interval` on a background thread until the watcher is
+    /// dropped. Failures are logged and leave the previous `what` in place.
+    pub(crate) fn spawn<F>(interval: Duration, what: &'static str, reload_if_changed: F) -> Self
+    where
+        F: Fn() -> std::result::Result<bool, GeoError> + Send + 'static,
+    {
+        let (stop, stopped) = mpsc::channel();
+
+        // Waiting on the channel rather than sleeping lets a dropped watcher stop the thread
+        // straight away instead of after the current interval.
+        thread::spawn(move || {
+            while let Err(RecvTimeoutError::Timeout) = stopped.recv_timeout(interval) {
+                if let Err(error) = reload_if_changed() {
+                    warn!("keeping previous {}: {}", what, error);
+                }
+            }
+        });
+
+        Watcher { stop }
+    }
+}
+
+impl Drop for Watcher {
+    fn drop(&mut self) {
+        let _ = self.stop.send(());
+    }
+}
+
+impl Loaded {
+    fn open(path: &Path) -> std::result::Result<Self, GeoError> {
+        let modified = modified(path);
+
+        let bytes = match fs::read(path) {
+            Ok(bytes) => bytes,
+            Err(error) => {
+                return Err(GeoError::DatabaseError(format!(
+                    "Couldn't read {}: {}",
+                    path.display(),
+                    error
+                )));
+            }
+        };
+
+        let reader = match Reader::from_source(bytes) {
+            Ok(reader) => reader,
+            Err(error) => {
+                return Err(GeoError::DatabaseError(format!(
+                    "{} isn't a valid database: {}",
+                    path.display(),
+                    error
+                )));
+            }
+        };
+
+        let database_type = &reader.metadata.database_type;
+        if !database_type.contains("City")
+            && !database_type.contains("Country")
+            && !database_type.contains("ASN")
+            && !database_type.contains("ISP")
+        {
+            return Err(GeoError::DatabaseError(format!(
+                "{} is a {} database, not a city, country, ASN or ISP database",
+                path.display(),
+                database_type
+            )));
+        }
+
+        // Walk the search tree once so a truncated file is caught before it's used.
+        match reader.lookup::<geoip2::Country>(IpAddr::from([1, 1, 1, 1])) {
+            Ok(_) | Err(MaxMindDBError::AddressNotFoundError(_)) => (),
+            Err(error) => {
+                return Err(GeoError::DatabaseError(format!(
+                    "{} is corrupt: {}",
+                    path.display(),
+                    error
+                )));
+            }
+        }
+
+        Ok(Loaded {
+            reader,
+            path: path.to_path_buf(),
+            modified,
+        })
+    }
+}
+
+/// When the file at `path` was last modified, if that can be read.
+pub(crate) fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+fn localized_name<S: AsRef<str>>(
+    names: Option<&std::collections::BTreeMap<&str, &str>>,
+    languages: &[S],
+) -> String {
+    let names = match names {
+        Some(names) => names,
+        None => return String::default(),
+    };
+
+    let available: Vec<&str> = names.keys().copied().collect();
+    let language = language::negotiate(languages, &available).unwrap_or("en");
+
+    names.get(language).unwrap_or(&"").to_string()
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::fs::File;
+
+    /// A value in a MaxMind DB data section.
+    pub(crate) enum Value {
+        String(&'static str),
+        Uint(u32),
+        Double(f64),
+        Array(Vec<Value>),
+        Map(Vec<(&'static str, Value)>),
+    }
+
+    impl Value {
+        fn encode(&self, out: &mut Vec<u8>) {
+            match self {
+                Value::String(string) => {
+                    control(out, 2, string.len());
+                    out.extend_from_slice(string.as_bytes());
+                }
+                Value::Uint(number) => {
+                    let bytes = number.to_be_bytes();
+                    let start = bytes.iter().position(|&byte| byte != 0).unwrap_or(4);
+                    control(out, 6, 4 - start);
+                    out.extend_from_slice(&bytes[start..]);
+                }
+                Value::Double(number) => {
+                    control(out, 3, 8);
+                    out.extend_from_slice(&number.to_be_bytes());
+                }
+                Value::Array(values) => {
+                    control(out, 11, values.len());
+                    for value in values {
+                        value.encode(out);
+                    }
+                }
+                Value::Map(pairs) => {
+                    control(out, 7, pairs.len());
+                    for (key, value) in pairs {
+                        Value::String(key).encode(out);
+                        value.encode(out);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Writes a control byte, with the extended type byte for types above 7 and one size byte
+    /// for sizes from 29.
+    fn control(out: &mut Vec<u8>, kind: u8, size: usize) {
+        assert!(size < 285, "fixture values are kept short");
+        let bits = size.min(29) as u8;
+
+        if kind > 7 {
+            out.push(bits);
+            out.push(kind - 7);
+        } else {
+            out.push(kind << 5 | bits);
+        }
+
+        if size >= 29 {
+            out.push((size - 29) as u8);
+        }
+    }
+
+    /// A MaxMind DB file of `database_type` with 24-bit records and an IPv4 search tree,
+    /// holding `records` under non-overlapping IPv4 prefixes.
+    pub(crate) fn mmdb(database_type: &'static str, records: Vec<(&str, Value)>) -> Vec<u8> {
+        // Each node's left and right record: another node, or data at an offset.
+        enum Record {
+            Empty,
+            Node(usize),
+            Data(usize),
+        }
+
+        let mut nodes = vec![[Record::Empty, Record::Empty]];
+        let mut data = Vec::new();
+
+        for (prefix, value) in records {
+            let prefix: ipnet::Ipv4Net = prefix.parse().unwrap();
+            let address = u32::from(prefix.network());
+
+            let mut node = 0;
+            for bit in 0..prefix.prefix_len() {
+                let side = (address >> (31 - bit) & 1) as usize;
+
+                if bit + 1 == prefix.prefix_len() {
+                    nodes[node][side] = Record::Data(data.len());
+                } else {
+                    node = match nodes[node][side] {
+                        Record::Node(next) => next,
+                        _ => {
+                            nodes.push([Record::Empty, Record::Empty]);
+                            nodes[node][side] = Record::Node(nodes.len() - 1);
+                            nodes.len() - 1
+                        }
+                    };
+                }
+            }
+
+            value.encode(&mut data);
+        }
+
+        let node_count = nodes.len();
+        let mut file = Vec::new();
+        for records in &nodes {
+            for record in records {
+                let value = match record {
+                    Record::Empty => node_count,
+                    Record::Node(next) => *next,
+                    Record::Data(offset) => node_count + 16 + offset,
+                };
+                file.extend_from_slice(&(value as u32).to_be_bytes()[1..]);
+            }
+        }
+
+        file.extend_from_slice(&[0; 16]);
+        file.extend_from_slice(&data);
+        file.extend_from_slice(b"\xab\xcd\xefMaxMind.com");
+
+        Value::Map(vec![
+            ("binary_format_major_version", Value::Uint(2)),
+            ("binary_format_minor_version", Value::Uint(0)),
+            ("build_epoch", Value::Uint(1_704_067_200)),
+            ("database_type", Value::String(database_type)),
+            (
+                "description",
+                Value::Map(vec![("en", Value::String("Test"))]),
+            ),
+            ("ip_version", Value::Uint(4)),
+            ("languages", Value::Array(vec![Value::String("en")])),
+            ("node_count", Value::Uint(node_count as u32)),
+            ("record_size", Value::Uint(24)),
+        ])
+        .encode(&mut file);
+
+        file
+    }
+
+    /// A city database with a single city in `prefix`.
+    pub(crate) fn city_database(prefix: &str, city: &'static str) -> Vec<u8> {
+        let names = |name| Value::Map(vec![("en", Value::String(name))]);
+
+        mmdb(
+            "GeoLite2-City",
+            vec![(
+                prefix,
+                Value::Map(vec![
+                    ("city", Value::Map(vec![("names", names(city))])),
+                    (
+                        "country",
+                        Value::Map(vec![
+                            ("iso_code", Value::String("DE")),
+                            ("names", names("Germany")),
+                        ]),
+                    ),
+                    (
+                        "location",
+                        Value::Map(vec![
+                            ("accuracy_radius", Value::Uint(20)),
+                            ("latitude", Value::Double(50.11)),
+                            ("longitude", Value::Double(8.68)),
+                            ("time_zone", Value::String("Europe/Berlin")),
+                        ]),
+                    ),
+                    ("postal", Value::Map(vec![("code", Value::String("60311"))])),
+                    (
+                        "subdivisions",
+                        Value::Array(vec![Value::Map(vec![
+                            ("iso_code", Value::String("HE")),
+                            ("names", names("Hesse")),
+                        ])]),
+                    ),
+                ]),
+            )],
+        )
+    }
+
+    fn directory(test: &str) -> PathBuf {
+        let directory = std::env::temp_dir().join(format!(
+            "ipgeolocate-offline-{}-{}",
+            std::process::id(),
+            test
+        ));
+        let _ = fs::remove_dir_all(&directory);
+        fs::create_dir_all(&directory).unwrap();
+        directory
+    }
+
+    fn ip(ip: &str) -> IpAddr {
+        ip.parse().unwrap()
+    }
+
+    #[test]
+    fn looks_up_cities() {
+        let directory = directory("city");
+        let path = directory.join("GeoLite2-City.mmdb");
+        fs::write(&path, city_database("192.0.2.0/24", "Frankfurt")).unwrap();
+
+        let database = Database::open(&path).unwrap();
+        assert_eq!(database.database_type(), "GeoLite2-City");
+
+        let locator = database.lookup(ip("192.0.2.7")).unwrap();
+        assert_eq!(locator.city, "Frankfurt");
+        assert_eq!(locator.region, "Hesse");
+        assert_eq!(locator.region_code, "DE-HE");
+        assert_eq!(locator.country_code, "DE");
+        assert_eq!(locator.timezone, "Europe/Berlin");
+        assert_eq!(locator.postal, "60311");
+        assert_eq!(locator.accuracy_radius, Some(20));
+
+        match database.lookup(ip("198.51.100.1")) {
+            Err(GeoError::DatabaseError(error)) => assert!(error.contains("isn't in")),
+            other => panic!("expected a missing address, got {:?}", other),
+        }
+
+        let _ = fs::remove_dir_all(&directory);
+    }
+
+    #[test]
+    fn looks_up_networks() {
+        let directory = directory("network");
+
+        let asn = directory.join("GeoLite2-ASN.mmdb");
+        fs::write(
+            &asn,
+            mmdb(
+                "GeoLite2-ASN",
+                vec![(
+                    "192.0.2.0/24",
+                    Value::Map(vec![
+                        ("autonomous_system_number", Value::Uint(64496)),
+                        ("autonomous_system_organization", Value::String("Example")),
+                    ]),
+                )],
+            ),
+        )
+        .unwrap();
+
+        let network = Database::open(&asn)
+            .unwrap()
+            .network(ip("192.0.2.7"))
+            .unwrap();
+        assert_eq!(network.asn, Some(64496));
+        assert_eq!(network.organization.as_deref(), Some("Example"));
+        assert_eq!(network.prefix, Some("192.0.2.0/24".parse().unwrap()));
+
+        let isp = directory.join("GeoIP2-ISP.mmdb");
+        fs::write(
+            &isp,
+            mmdb(
+                "GeoIP2-ISP",
+                vec![(
+                    "192.0.2.0/24",
+                    Value::Map(vec![
+                        ("autonomous_system_number", Value::Uint(64496)),
+                        ("autonomous_system_organization", Value::String("Example")),
+                        ("isp", Value::String("Example Broadband")),
+                    ]),
+                )],
+            ),
+        )
+        .unwrap();
+
+        let locator = Database::open(&isp)
+            .unwrap()
+            .lookup(ip("192.0.2.7"))
+            .unwrap();
+        assert_eq!(locator.isp, "Example Broadband");
+        assert_eq!(locator.network.unwrap().asn, Some(64496));
+
+        let _ = fs::remove_dir_all(&directory);
+    }
+
+    #[test]
+    fn keeps_loaded_database_on_failed_reload() {
+        let directory = directory("reload");
+        let path = directory.join("GeoLite2-City.mmdb");
+        fs::write(&path, city_database("192.0.2.0/24", "Frankfurt")).unwrap();
+        let database = Database::open(&path).unwrap();
+
+        let newer = directory.join("newer.mmdb");
+        fs::write(&newer, city_database("192.0.2.0/24", "Hamburg")).unwrap();
+        database.reload_from(&newer).unwrap();
+        assert_eq!(database.path(), newer);
+        assert_eq!(database.lookup(ip("192.0.2.7")).unwrap().city, "Hamburg");
+
+        let garbage = directory.join("garbage.mmdb");
+        fs::write(&garbage, b"not a database").unwrap();
+        match database.reload_from(&garbage) {
+            Err(GeoError::DatabaseError(error)) => assert!(error.contains("isn't a valid")),
+            other => panic!("expected an invalid database, got {:?}", other),
+        }
+
+        let unsupported = directory.join("connection-type.mmdb");
+        fs::write(&unsupported, mmdb("GeoIP2-Connection-Type", Vec::new())).unwrap();
+        match database.reload_from(&unsupported) {
+            Err(GeoError::DatabaseError(error)) => {
+                assert!(error.contains("GeoIP2-Connection-Type database"))
+            }
+            other => panic!("expected an unsupported database, got {:?}", other),
+        }
+
+        assert!(database
+            .reload_from(directory.join("missing.mmdb"))
+            .is_err());
+
+        assert_eq!(database.path(), newer);
+        assert_eq!(database.lookup(ip("192.0.2.7")).unwrap().city, "Hamburg");
+
+        let _ = fs::remove_dir_all(&directory);
+    }
+
+    #[test]
+    fn watches_for_changes() {
+        let directory = directory("watch");
+        let path = directory.join("GeoLite2-City.mmdb");
+        fs::write(&path, city_database("192.0.2.0/24", "Frankfurt")).unwrap();
+
+        let database = Database::open(&path).unwrap();
+        assert!(!database.reload_if_changed().unwrap());
+
+        let watcher = database.watch(Duration::from_millis(10));
+
+        fs::write(&path, city_database("192.0.2.0/24", "Hamburg")).unwrap();
+        let later = SystemTime::now() + Duration::from_secs(5);
+        File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(later)
+            .unwrap();
+
+        for _ in 0..200 {
+            if database.lookup(ip("192.0.2.7")).unwrap().city == "Hamburg" {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(database.lookup(ip("192.0.2.7")).unwrap().city, "Hamburg");
+
+        // Once the watcher is gone, changes are left alone.
+        drop(watcher);
+        thread::sleep(Duration::from_millis(20));
+        fs::write(&path, city_database("192.0.2.0/24", "Berlin")).unwrap();
+        File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(later + Duration::from_secs(5))
+            .unwrap();
+        thread::sleep(Duration::from_millis(100));
+        assert_eq!(database.lookup(ip("192.0.2.7")).unwrap().city, "Hamburg");
+
+        let _ = fs::remove_dir_all(&directory);
+    }
+}
@@ -0,0 +1,295 @@
+//! Site-specific location overrides for address ranges.
+//!
+//! An [`Overrides`] table maps CIDR ranges to fully or partially specified locations.
+//! It's consulted before any [`Service`]: a range with every field set is answered
+//! without a request, and a range with only some fields set has those fields merged
+//! onto whatever the service returns. Special-purpose addresses (see [`special`]) are never
+//! sent to a service, so for them a partial override is the whole result.
+
+use crate::{special, Client, GeoError, Locator, Service};
+use ipnet::IpNet;
+use serde::Deserialize;
+use std::fs;
+use std::net::IpAddr;
+use std::path::Path;
+
+/// Location fields to use for every address in a range. Unset fields come from the service.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Override {
+    /// The range the override applies to.
+    pub cidr: IpNet,
+    pub latitude: Option<String>,
+    pub longitude: Option<String>,
+    pub city: Option<String>,
+    pub region: Option<String>,
+    pub country: Option<String>,
+    pub timezone: Option<String>,
+    pub isp: Option<String>,
+}
+
+impl Override {
+    /// Whether every location field is set, so no service needs to be asked.
+    pub fn is_complete(&self) -> bool {
+        self.latitude.is_some()
+            && self.longitude.is_some()
+            && self.city.is_some()
+            && self.region.is_some()
+            && self.country.is_some()
+            && self.timezone.is_some()
+            && self.isp.is_some()
+    }
+
+    /// Replaces the fields of `locator` that this override sets.
+    ///
+    /// ISO codes and the typed time zone are worked out again from the overridden fields.
+    pub fn apply(&self, mut locator: Locator) -> Locator {
+        if self.country.is_some() {
+            locator.country_code.clear();
+            locator.country_code3.clear();
+        }
+        if self.country.is_some() || self.region.is_some() {
+            locator.region_code.clear();
+        }
+        if self.country.is_some() || self.region.is_some() || self.city.is_some() {
+            locator.postal.clear();
+            locator.metro_code = None;
+        }
+        if self.latitude.is_some() || self.longitude.is_some() {
+            locator.accuracy_radius = None;
+        }
+
+        let fields = [
+            (&mut locator.latitude, &self.latitude),
+            (&mut locator.longitude, &self.longitude),
+            (&mut locator.city, &self.city),
+            (&mut locator.region, &self.region),
+            (&mut locator.country, &self.country),
+            (&mut locator.timezone, &self.timezone),
+            (&mut locator.isp, &self.isp),
+        ];
+
+        for (field, value) in fields {
+            if let Some(value) = value {
+                *field = value.clone();
+            }
+        }
+
+        locator.normalize();
+
+        locator
+    }
+
+    /// A result made up of this override alone.
+    pub(crate) fn locator(&self, ip: &str) -> Locator {
+        self.apply(Locator {
+            ip: ip.to_string(),
+            category: ip.parse().ok().and_then(special::classify),
+            ..Locator::default()
+        })
+    }
+}
+
+/// A table of [`Override`]s. The most specific range containing an address wins.
+#[derive(Debug, Clone, Default)]
+pub struct Overrides {
+    entries: Vec<Override>,
+}
+
+#[derive(Deserialize)]
+struct TomlOverrides {
+    #[serde(rename = "override", default)]
+    entries: Vec<Override>,
+}
+
+impl Overrides {
+    /// Creates an empty table.
+    pub fn new() -> Self {
+        Overrides::default()
+    }
+
+    /// Loads a table from a `.toml`, `.json` or `.csv` file.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> std::result::Result<Self, GeoError> {
+        let path = path.as_ref();
+
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(error) => {
+                return Err(GeoError::ParseError(format!(
+                    "Couldn't read {}: {}",
+                    path.display(),
+                    error
+                )))
+            }
+        };
+
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("toml") => Overrides::from_toml(&contents),
+            Some("json") => Overrides::from_json(&contents),
+            Some("csv") => Overrides::from_csv(&contents),
+            _ => Err(GeoError::ParseError(format!(
+                "{} isn't a .toml, .json or .csv file",
+                path.display()
+            ))),
+        }
+    }
+
+    /// Parses a JSON array of overrides, e.g. `[{"cidr": "10.0.0.0/8", "city": "Denver"}]`.
+    pub fn from_json(json: &str) -> std::result::Result<Self, GeoError> {
+        match serde_json::from_str(json) {
+            Ok(entries) => Ok(Overrides { entries }),
+            Err(error) => Err(GeoError::ParseError(format!(
+                "Couldn't parse overrides: {}",
+                error
+            ))),
+        }
+    }
+
+    /// Parses `[[override]]` tables, each with a `cidr` key and the fields to set.
+    pub fn from_toml(toml: &str) -> std::result::Result<Self, GeoError> {
+        match toml::from_str::<TomlOverrides>(toml) {
+            Ok(overrides) => Ok(Overrides {
+                entries: overrides.entries,
+            }),
+            Err(error) => Err(GeoError::ParseError(format!(
+                "Couldn't parse overrides: {}",
+                error
+            ))),
+        }
+    }
+
+    /// Parses CSV with a header row naming `cidr` and the fields to set. Empty cells are left unset.
+    pub fn from_csv(csv: &str) -> std::result::Result<Self, GeoError> {
+        let mut entries = Vec::new();
+
+        for record in csv::Reader::from_reader(csv.as_bytes()).deserialize() {
+            let mut entry: Override = match record {
+                Ok(entry) => entry,
+                Err(error) => {
+                    return Err(GeoError::ParseError(format!(
+                        "Couldn't parse overrides: {}",
+                        error
+                    )))
+                }
+            };
+
+            let fields = [
+                &mut entry.latitude,
+                &mut entry.longitude,
+                &mut entry.city,
+                &mut entry.region,
+                &mut entry.country,
+                &mut entry.timezone,
+                &mut entry.isp,
+            ];
+
+            for field in fields {
+                if field.as_deref() == Some("") {
+                    *field = None;
+                }
+            }
+
+            entries.push(entry);
+        }
+
+        Ok(Overrides { entries })
+    }
+
+    /// Adds an override to the table.
+    pub fn insert(&mut self, entry: Override) {
+        self.entries.push(entry);
+    }
+
+    /// Finds the most specific override containing `ip`.
+    pub fn find(&self, ip: IpAddr) -> Option<&Override> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.cidr.contains(&ip))
+            .max_by_key(|entry| entry.cidr.prefix_len())
+    }
+
+    /// Gets IP information, consulting the table before `service`.
+    ///
+    /// Complete overrides are returned without contacting `service`; partial ones are
+    /// merged onto its result. To use a configured [`Client`], give it the table with
+    /// [`Client::overrides`] instead.
+    pub async fn get(&self, ip: &str, service: Service) -> std::result::Result<Locator, GeoError> {
+        Client::new(service).get_overridden(ip, Some(self)).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AddressCategory;
+
+    const TABLE: &str = r#"
+[[override]]
+cidr = "10.0.0.0/8"
+latitude = "39.7392"
+longitude = "-104.9903"
+city = "Denver"
+region = "Colorado"
+country = "United States"
+timezone = "America/Denver"
+isp = "Internal"
+
+[[override]]
+cidr = "10.1.0.0/16"
+city = "Boulder"
+
+[[override]]
+cidr = "203.0.113.0/24"
+isp = "Corporate Egress"
+"#;
+
+    #[test]
+    fn finds_most_specific_range() {
+        let overrides = Overrides::from_toml(TABLE).unwrap();
+
+        let entry = overrides.find("10.1.2.3".parse().unwrap()).unwrap();
+        assert_eq!(entry.city.as_deref(), Some("Boulder"));
+
+        let entry = overrides.find("10.2.0.1".parse().unwrap()).unwrap();
+        assert!(entry.is_complete());
+
+        assert!(overrides.find("1.1.1.1".parse().unwrap()).is_none());
+    }
+
+    #[tokio::test]
+    async fn answers_partial_override_on_special_address() {
+        let client = Client::new(Service::IpApi).overrides(Overrides::from_toml(TABLE).unwrap());
+
+        let location = client.get("203.0.113.7").await.unwrap();
+        assert_eq!(location.isp, "Corporate Egress");
+        assert_eq!(location.city, "");
+        assert_eq!(location.category, Some(AddressCategory::Documentation));
+
+        let location = client.get("10.1.2.3").await.unwrap();
+        assert_eq!(location.city, "Boulder");
+        assert_eq!(location.category, Some(AddressCategory::Private));
+    }
+
+    #[tokio::test]
+    async fn answers_complete_override() {
+        let overrides = Overrides::from_toml(TABLE).unwrap();
+
+        let location = overrides.get("10.2.0.1", Service::IpApi).await.unwrap();
+        assert_eq!(location.city, "Denver");
+        assert_eq!(location.country_code, "US");
+        assert_eq!(location.category, Some(AddressCategory::Private));
+    }
+
+    #[tokio::test]
+    async fn still_rejects_uncovered_special_address() {
+        let client = Client::new(Service::IpApi)
+            .embedded_ipv4(false)
+            .overrides(Overrides::from_toml(TABLE).unwrap());
+
+        match client.get("::ffff:10.0.0.1").await {
+            Err(GeoError::SpecialAddress(_, category)) => {
+                assert_eq!(category, AddressCategory::Ipv4Mapped)
+            }
+            other => panic!("expected a special address, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,145 @@
+//! The raw response a service sent.
+//!
+//! [`Locator`](crate::Locator) only has the fields this crate knows how to read. With
+//! [`Client::keep_raw`](crate::Client::keep_raw), every result also keeps the decoded JSON
+//! response and the HTTP status and headers it came with in
+//! [`Locator::raw`](crate::Locator::raw), so fields a service adds can be read right away.
+
+use reqwest::header::HeaderMap;
+use reqwest::StatusCode;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// Headers that are kept: rate limits and what the response is.
+///
+/// `X-Rl` and `X-Ttl` are ip-api.com's requests left and seconds until they reset. Headers
+/// starting with `x-ratelimit-` are kept too.
+pub const HEADERS: &[&str] = &["content-type", "date", "retry-after", "x-rl", "x-ttl"];
+
+/// A service's decoded response, with its HTTP status and the headers in [`HEADERS`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Raw {
+    /// The HTTP status code, like `200`.
+    pub status: u16,
+    /// Header values by lowercase name.
+    pub headers: BTreeMap<String, String>,
+    /// The decoded JSON. For a batch lookup, just the part about this address.
+    pub body: Value,
+}
+
+impl Raw {
+    pub(crate) fn new(status: StatusCode, headers: &HeaderMap, body: &Value) -> Self {
+        let headers = headers
+            .iter()
+            .filter(|(name, _)| {
+                HEADERS.contains(&name.as_str()) || name.as_str().starts_with("x-ratelimit-")
+            })
+            .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())))
+            .collect();
+
+        Raw {
+            status: status.as_u16(),
+            headers,
+            body: body.clone(),
+        }
+    }
+
+    /// The value at `key`: either a top-level key like `org`, or a JSON pointer like
+    /// `/connection/asn` to reach into objects and arrays.
+    pub fn value(&self, key: &str) -> Option<&Value> {
+        if key.starts_with('/') {
+            self.body.pointer(key)
+        } else {
+            self.body.get(key)
+        }
+    }
+
+    /// The value at `key` (see [`value`](Raw::value)) as a `T`. `None` if it's missing, `null`
+    /// or doesn't have the right type.
+    pub fn get<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        match self.value(key)? {
+            Value::Null => None,
+            value => T::deserialize(value).ok(),
+        }
+    }
+
+    /// The value of the header `name`, ignoring case, if it was kept.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .get(&name.to_ascii_lowercase())
+            .map(String::as_str)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{decode, ipwhois, Client, Service};
+    use reqwest::header::HeaderValue;
+    use serde_json::json;
+
+    fn body() -> Value {
+        json!({
+            "ip": "8.8.8.8",
+            "success": true,
+            "latitude": "37.3860517",
+            "longitude": "-122.0838511",
+            "city": "Mountain View",
+            "region": "California",
+            "country": "United States",
+            "country_code": "US",
+            "timezone": "America/Los_Angeles",
+            "isp": "Google LLC",
+            "flag": {"emoji": "🇺🇸"},
+            "currency_rates": [1.0, 0.92]
+        })
+    }
+
+    fn headers() -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert("content-type", HeaderValue::from_static("application/json"));
+        headers.insert("x-ratelimit-remaining", HeaderValue::from_static("9999"));
+        headers.insert("set-cookie", HeaderValue::from_static("session=1"));
+        headers
+    }
+
+    #[test]
+    fn keeps_body_next_to_locator() {
+        let body = body();
+        let client = Client::new(Service::IpWhois).keep_raw(true);
+
+        let response: ipwhois::Response = decode(&body, "ipwhois.app").unwrap();
+        let mut location = response.locator("8.8.8.8", &client).unwrap();
+        location.raw = client.raw(StatusCode::OK, &headers(), &body);
+
+        assert_eq!(location.city, "Mountain View");
+        let raw = location.raw.unwrap();
+        assert_eq!(raw.status, 200);
+        assert_eq!(raw.body, body);
+        assert_eq!(raw.get::<String>("/flag/emoji").as_deref(), Some("🇺🇸"));
+
+        let client = Client::new(Service::IpWhois);
+        assert_eq!(client.raw(StatusCode::OK, &headers(), &body), None);
+    }
+
+    #[test]
+    fn keeps_listed_headers() {
+        let raw = Raw::new(StatusCode::TOO_MANY_REQUESTS, &headers(), &body());
+
+        assert_eq!(raw.status, 429);
+        assert_eq!(raw.header("Content-Type"), Some("application/json"));
+        assert_eq!(raw.header("x-ratelimit-remaining"), Some("9999"));
+        assert_eq!(raw.header("set-cookie"), None);
+    }
+
+    #[test]
+    fn reads_keys_and_pointers() {
+        let raw = Raw::new(StatusCode::OK, &HeaderMap::new(), &body());
+
+        assert_eq!(raw.get::<String>("isp").as_deref(), Some("Google LLC"));
+        assert_eq!(raw.get::<f64>("/currency_rates/1"), Some(0.92));
+        assert_eq!(raw.get::<u32>("isp"), None);
+        assert_eq!(raw.value("missing"), None);
+    }
+}
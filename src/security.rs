@@ -0,0 +1,311 @@
+//! Transport security for requests to services.
+//!
+//! The addresses being looked up are user data, so a [`TransportPolicy`] can refuse
+//! services reached over plain HTTP, trust extra root certificates (for TLS-inspecting
+//! proxies) and pin the certificates a service is allowed to present.
+//!
+//! Extra roots and pins are checked on top of the operating system's trusted roots. The
+//! bundled Mozilla roots are only used if the system's can't be read.
+
+use crate::{GeoError, Service};
+use rustls::client::{ServerCertVerified, ServerCertVerifier, WebPkiVerifier};
+use rustls::{Certificate, ClientConfig, OwnedTrustAnchor, RootCertStore, ServerName};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock};
+use std::time::SystemTime;
+use tracing::{debug, warn};
+
+/// How a [`Client`](crate::Client) is allowed to connect to services.
+#[derive(Debug, Clone, Default)]
+pub struct TransportPolicy {
+    https_only: bool,
+    root_certificates: Vec<Certificate>,
+    pins: HashMap<Service, Vec<[u8; 32]>>,
+}
+
+impl TransportPolicy {
+    /// Creates a policy that allows plain HTTP and trusts the system's root certificates.
+    pub fn new() -> Self {
+        TransportPolicy::default()
+    }
+
+    /// Refuses to send lookups over plain HTTP.
+    ///
+    /// The free tiers of [`Service::IpApi`] and [`Service::IpWhois`] are only served over HTTP,
+    /// so they fail with [`GeoError::InsecureTransport`] unless they're given an API key.
+    pub fn https_only(mut self, https_only: bool) -> Self {
+        self.https_only = https_only;
+        self
+    }
+
+    /// Trusts the PEM-encoded root certificates in `pem` as well as the system's.
+    pub fn add_root_certificates_pem(mut self, pem: &[u8]) -> std::result::Result<Self, GeoError> {
+        let certificates = match rustls_pemfile::certs(&mut &pem[..]) {
+            Ok(certificates) if !certificates.is_empty() => certificates,
+            Ok(_) => {
+                return Err(GeoError::TransportError(
+                    "No certificates found in PEM data".to_string(),
+                ))
+            }
+            Err(error) => {
+                return Err(GeoError::TransportError(format!(
+                    "Couldn't read PEM certificates: {}",
+                    error
+                )))
+            }
+        };
+
+        self.root_certificates
+            .extend(certificates.into_iter().map(Certificate));
+        Ok(self)
+    }
+
+    /// Trusts a DER-encoded root certificate as well as the system's.
+    pub fn add_root_certificate_der(mut self, der: &[u8]) -> Self {
+        self.root_certificates.push(Certificate(der.to_vec()));
+        self
+    }
+
+    /// Only accepts connections to `service` whose certificate chain includes a certificate
+    /// with this SHA-256 fingerprint (of its DER encoding).
+    ///
+    /// Pinning an intermediate rather than the leaf certificate survives routine renewals.
+    /// Several pins can be added for the same service; any one of them matching is enough.
+    pub fn pin(mut self, service: Service, sha256: [u8; 32]) -> Self {
+        self.pins.entry(service).or_default().push(sha256);
+        self
+    }
+
+    /// Like [`pin`](TransportPolicy::pin), taking the fingerprint as hex (colons are ignored).
+    pub fn pin_hex(self, service: Service, sha256: &str) -> std::result::Result<Self, GeoError> {
+        let digits: Vec<u8> = sha256.bytes().filter(|byte| *byte != b':').collect();
+
+        let mut fingerprint = [0; 32];
+        let valid = digits.len() == 64
+            && digits
+                .chunks(2)
+                .zip(fingerprint.iter_mut())
+                .all(|(pair, byte)| {
+                    match std::str::from_utf8(pair).map(|pair| u8::from_str_radix(pair, 16)) {
+                        Ok(Ok(value)) => {
+                            *byte = value;
+                            true
+                        }
+                        _ => false,
+                    }
+                });
+
+        if !valid {
+            return Err(GeoError::TransportError(format!(
+                "{} isn't a SHA-256 fingerprint",
+                sha256
+            )));
+        }
+
+        Ok(self.pin(service, fingerprint))
+    }
+
+    /// Fails if this policy forbids sending a request to `url`.
+    pub(crate) fn check(&self, url: &str) -> std::result::Result<(), GeoError> {
+        if self.https_only && !url.starts_with("https://") {
+            let host = url
+                .split("://")
+                .nth(1)
+                .and_then(|rest| rest.split('/').next())
+                .unwrap_or(url);
+
+            return Err(GeoError::InsecureTransport(format!(
+                "{} would be reached over plain HTTP",
+                host
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Builds an HTTP client that enforces this policy, with `service`'s pins if there is one.
+    pub(crate) fn http_client(
+        &self,
+        service: Option<Service>,
+    ) -> std::result::Result<reqwest::Client, GeoError> {
+        match self.builder(service)?.build() {
+            Ok(client) => Ok(client),
+            Err(error) => Err(GeoError::TransportError(format!(
+                "Couldn't create HTTP client: {}",
+                error
+            ))),
+        }
+    }
+
+    /// Starts an HTTP client that enforces this policy, with `service`'s pins if there is one.
+    pub(crate) fn builder(
+        &self,
+        service: Option<Service>,
+    ) -> std::result::Result<reqwest::ClientBuilder, GeoError> {
+        let pins = service
+            .and_then(|service| self.pins.get(&service).cloned())
+            .unwrap_or_default();
+
+        let mut builder = reqwest::Client::builder().https_only(self.https_only);
+
+        if !self.root_certificates.is_empty() || !pins.is_empty() {
+            builder = builder.use_preconfigured_tls(self.tls_config(pins)?);
+        }
+
+        Ok(builder)
+    }
+
+    fn tls_config(&self, pins: Vec<[u8; 32]>) -> std::result::Result<ClientConfig, GeoError> {
+        let mut roots = system_roots();
+
+        for certificate in &self.root_certificates {
+            if let Err(error) = roots.add(certificate) {
+                return Err(GeoError::TransportError(format!(
+                    "Invalid root certificate: {}",
+                    error
+                )));
+            }
+        }
+
+        let verifier = PinnedVerifier {
+            inner: WebPkiVerifier::new(roots, None),
+            pins,
+        };
+
+        Ok(ClientConfig::builder()
+            .with_safe_defaults()
+            .with_custom_certificate_verifier(Arc::new(verifier))
+            .with_no_client_auth())
+    }
+}
+
+/// The operating system's trusted root certificates, or the bundled Mozilla roots if they
+/// can't be read. They're only read from the system once.
+fn system_roots() -> RootCertStore {
+    static ROOTS: OnceLock<RootCertStore> = OnceLock::new();
+
+    ROOTS.get_or_init(load_system_roots).clone()
+}
+
+fn load_system_roots() -> RootCertStore {
+    let mut roots = RootCertStore::empty();
+
+    match rustls_native_certs::load_native_certs() {
+        Ok(certificates) => {
+            let certificates: Vec<Vec<u8>> = certificates
+                .into_iter()
+                .map(|certificate| certificate.0)
+                .collect();
+            let (_, ignored) = roots.add_parsable_certificates(&certificates);
+            if ignored > 0 {
+                debug!("ignored {} unparsable system root certificates", ignored);
+            }
+        }
+        Err(error) => warn!("couldn't load system root certificates: {}", error),
+    }
+
+    if roots.is_empty() {
+        warn!("no system root certificates found, using the bundled Mozilla roots");
+        roots.add_trust_anchors(webpki_roots::TLS_SERVER_ROOTS.iter().map(|anchor| {
+            OwnedTrustAnchor::from_subject_spki_name_constraints(
+                anchor.subject,
+                anchor.spki,
+                anchor.name_constraints,
+            )
+        }));
+    }
+
+    roots
+}
+
+/// Verifies certificates normally, then checks the chain against the pins.
+struct PinnedVerifier {
+    inner: WebPkiVerifier,
+    pins: Vec<[u8; 32]>,
+}
+
+impl ServerCertVerifier for PinnedVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &Certificate,
+        intermediates: &[Certificate],
+        server_name: &ServerName,
+        scts: &mut dyn Iterator<Item = &[u8]>,
+        ocsp_response: &[u8],
+        now: SystemTime,
+    ) -> std::result::Result<ServerCertVerified, rustls::Error> {
+        let verified = self.inner.verify_server_cert(
+            end_entity,
+            intermediates,
+            server_name,
+            scts,
+            ocsp_response,
+            now,
+        )?;
+
+        if self.pins.is_empty() {
+            return Ok(verified);
+        }
+
+        let pinned = std::iter::once(end_entity)
+            .chain(intermediates)
+            .any(|certificate| {
+                let fingerprint: [u8; 32] = Sha256::digest(&certificate.0).into();
+                self.pins.contains(&fingerprint)
+            });
+
+        if pinned {
+            Ok(verified)
+        } else {
+            warn!("certificate for {:?} doesn't match any pin", server_name);
+            Err(rustls::Error::General(
+                "certificate doesn't match any pin".to_string(),
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FINGERPRINT: &str =
+        "8d:02:53:6c:88:7d:86:45:b2:8a:a3:8a:f2:c1:49:2b:0f:d4:a0:a5:9f:a5:d8:b5:ed:e8:b6:15:f3:c8:b4:a2";
+
+    #[test]
+    fn parses_hex_pins() {
+        let policy = TransportPolicy::new()
+            .pin_hex(Service::IpInfo, FINGERPRINT)
+            .unwrap();
+        assert_eq!(policy.pins[&Service::IpInfo][0][..2], [0x8d, 0x02]);
+
+        assert!(TransportPolicy::new()
+            .pin_hex(Service::IpInfo, "8d:02")
+            .is_err());
+        assert!(TransportPolicy::new()
+            .pin_hex(Service::IpInfo, &FINGERPRINT.replace("8d", "zz"))
+            .is_err());
+    }
+
+    #[test]
+    fn keeps_system_roots_with_pins() {
+        assert!(!system_roots().is_empty());
+
+        let policy = TransportPolicy::new()
+            .pin_hex(Service::IpInfo, FINGERPRINT)
+            .unwrap();
+        assert!(policy.http_client(Some(Service::IpInfo)).is_ok());
+    }
+
+    #[test]
+    fn refuses_plain_http() {
+        let policy = TransportPolicy::new().https_only(true);
+
+        assert!(policy.check("https://ipinfo.io/1.1.1.1").is_ok());
+        match policy.check("http://ip-api.com/json/1.1.1.1") {
+            Err(GeoError::InsecureTransport(error)) => assert!(error.starts_with("ip-api.com")),
+            other => panic!("expected an insecure transport error, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,245 @@
+//! Local classification of special-purpose addresses.
+//!
+//! Addresses from the IANA IPv4 and IPv6 special-purpose registries (private ranges,
+//! loopback, link-local, documentation and so on) don't have a geographic location, so
+//! they're recognised here and never sent to a service.
+
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+/// The kind of special-purpose range an address belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AddressCategory {
+    /// `0.0.0.0/8` ("this network") or `::/128`.
+    Unspecified,
+    /// `127.0.0.0/8` or `::1/128`.
+    Loopback,
+    /// RFC 1918 private ranges: `10.0.0.0/8`, `172.16.0.0/12` and `192.168.0.0/16`.
+    Private,
+    /// `100.64.0.0/10`, used for carrier-grade NAT.
+    SharedAddressSpace,
+    /// `169.254.0.0/16` or `fe80::/10`.
+    LinkLocal,
+    /// `192.0.2.0/24`, `198.51.100.0/24`, `203.0.113.0/24`, `2001:db8::/32` or `3fff::/20`.
+    Documentation,
+    /// `198.18.0.0/15` or `2001:2::/48`.
+    Benchmarking,
+    /// `192.0.0.0/24` or `2001::/23`, reserved for IETF protocol assignments.
+    ProtocolAssignment,
+    /// `224.0.0.0/4` or `ff00::/8`.
+    Multicast,
+    /// `255.255.255.255/32`.
+    Broadcast,
+    /// `fc00::/7` unique local addresses.
+    UniqueLocal,
+    /// `100::/64`, discarded by routers.
+    DiscardOnly,
+    /// `::ffff:0:0/96`, an IPv4 address written as IPv6. Looked up through the IPv4 address
+    /// unless [`Client::embedded_ipv4`](crate::Client::embedded_ipv4) is turned off.
+    Ipv4Mapped,
+    /// `64:ff9b:1::/48`, for IPv4/IPv6 translation inside a network.
+    LocalTranslation,
+    /// Other reserved or deprecated ranges, like `240.0.0.0/4` and `fec0::/10`.
+    Reserved,
+}
+
+impl fmt::Display for AddressCategory {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AddressCategory::Unspecified => write!(f, "an unspecified address"),
+            AddressCategory::Loopback => write!(f, "a loopback address"),
+            AddressCategory::Private => write!(f, "a private address"),
+            AddressCategory::SharedAddressSpace => {
+                write!(f, "a shared (carrier-grade NAT) address")
+            }
+            AddressCategory::LinkLocal => write!(f, "a link-local address"),
+            AddressCategory::Documentation => write!(f, "a documentation address"),
+            AddressCategory::Benchmarking => write!(f, "a benchmarking address"),
+            AddressCategory::ProtocolAssignment => {
+                write!(f, "an IETF protocol assignment address")
+            }
+            AddressCategory::Multicast => write!(f, "a multicast address"),
+            AddressCategory::Broadcast => write!(f, "the broadcast address"),
+            AddressCategory::UniqueLocal => write!(f, "a unique local address"),
+            AddressCategory::DiscardOnly => write!(f, "a discard-only address"),
+            AddressCategory::Ipv4Mapped => write!(f, "an IPv4-mapped address"),
+            AddressCategory::LocalTranslation => write!(f, "a local-use translation address"),
+            AddressCategory::Reserved => write!(f, "a reserved address"),
+        }
+    }
+}
+
+// Globally reachable exceptions come before the ranges that contain them.
+const IPV4_RANGES: &[(u32, u8, Option<AddressCategory>)] = &[
+    (0x0000_0000, 8, Some(AddressCategory::Unspecified)),
+    (0x0a00_0000, 8, Some(AddressCategory::Private)),
+    (0x6440_0000, 10, Some(AddressCategory::SharedAddressSpace)),
+    (0x7f00_0000, 8, Some(AddressCategory::Loopback)),
+    (0xa9fe_0000, 16, Some(AddressCategory::LinkLocal)),
+    (0xac10_0000, 12, Some(AddressCategory::Private)),
+    (0xc000_0009, 32, None),
+    (0xc000_000a, 32, None),
+    (0xc000_0000, 24, Some(AddressCategory::ProtocolAssignment)),
+    (0xc000_0200, 24, Some(AddressCategory::Documentation)),
+    (0xc058_6300, 24, Some(AddressCategory::Reserved)),
+    (0xc0a8_0000, 16, Some(AddressCategory::Private)),
+    (0xc612_0000, 15, Some(AddressCategory::Benchmarking)),
+    (0xc633_6400, 24, Some(AddressCategory::Documentation)),
+    (0xcb00_7100, 24, Some(AddressCategory::Documentation)),
+    (0xe000_0000, 4, Some(AddressCategory::Multicast)),
+    (0xffff_ffff, 32, Some(AddressCategory::Broadcast)),
+    (0xf000_0000, 4, Some(AddressCategory::Reserved)),
+];
+
+const IPV6_RANGES: &[(u128, u8, Option<AddressCategory>)] = &[
+    (0, 128, Some(AddressCategory::Unspecified)),
+    (1, 128, Some(AddressCategory::Loopback)),
+    (0xffff << 32, 96, Some(AddressCategory::Ipv4Mapped)),
+    (
+        (0x0064 << 112) | (0xff9b << 96) | (0x0001 << 80),
+        48,
+        Some(AddressCategory::LocalTranslation),
+    ),
+    (0x0100 << 112, 64, Some(AddressCategory::DiscardOnly)),
+    // Teredo, 2001:1::1, 2001:1::2, 2001:3::/32, 2001:4:112::/48 and ORCHIDv2 are global.
+    (0x2001 << 112, 32, None),
+    ((0x2001 << 112) | (0x0001 << 96) | 1, 128, None),
+    ((0x2001 << 112) | (0x0001 << 96) | 2, 128, None),
+    ((0x2001 << 112) | (0x0003 << 96), 32, None),
+    ((0x2001 << 112) | (0x0004 << 96) | (0x0112 << 80), 48, None),
+    ((0x2001 << 112) | (0x0020 << 96), 28, None),
+    (
+        (0x2001 << 112) | (0x0002 << 96),
+        48,
+        Some(AddressCategory::Benchmarking),
+    ),
+    (
+        (0x2001 << 112) | (0x0db8 << 96),
+        32,
+        Some(AddressCategory::Documentation),
+    ),
+    (0x2001 << 112, 23, Some(AddressCategory::ProtocolAssignment)),
+    (0x3fff << 112, 20, Some(AddressCategory::Documentation)),
+    (0xfc00 << 112, 7, Some(AddressCategory::UniqueLocal)),
+    (0xfe80 << 112, 10, Some(AddressCategory::LinkLocal)),
+    (0xfec0 << 112, 10, Some(AddressCategory::Reserved)),
+    (0xff00 << 112, 8, Some(AddressCategory::Multicast)),
+];
+
+/// Finds the special-purpose range an address belongs to, if any.
+///
+/// Returns `None` for globally reachable addresses that can be geolocated.
+pub fn classify(ip: IpAddr) -> Option<AddressCategory> {
+    match ip {
+        IpAddr::V4(ip) => classify_ipv4(ip),
+        IpAddr::V6(ip) => classify_ipv6(ip),
+    }
+}
+
+/// Finds the special-purpose range an [`Ipv4Addr`] belongs to, if any.
+pub fn classify_ipv4(ip: Ipv4Addr) -> Option<AddressCategory> {
+    let address = u32::from(ip);
+
+    IPV4_RANGES
+        .iter()
+        .find(|(network, prefix, _)| {
+            let mask = u32::MAX.checked_shl(32 - *prefix as u32).unwrap_or(0);
+            address & mask == *network
+        })
+        .and_then(|(_, _, category)| *category)
+}
+
+/// Finds the special-purpose range an [`Ipv6Addr`] belongs to, if any.
+pub fn classify_ipv6(ip: Ipv6Addr) -> Option<AddressCategory> {
+    let address = u128::from(ip);
+
+    IPV6_RANGES
+        .iter()
+        .find(|(network, prefix, _)| {
+            let mask = u128::MAX.checked_shl(128 - *prefix as u32).unwrap_or(0);
+            address & mask == *network
+        })
+        .and_then(|(_, _, category)| *category)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn category(ip: &str) -> Option<AddressCategory> {
+        classify(ip.parse().unwrap())
+    }
+
+    #[test]
+    fn classifies_ipv4_ranges() {
+        assert_eq!(category("10.1.2.3"), Some(AddressCategory::Private));
+        assert_eq!(category("172.31.255.255"), Some(AddressCategory::Private));
+        assert_eq!(category("172.32.0.0"), None);
+        assert_eq!(category("192.168.0.1"), Some(AddressCategory::Private));
+        assert_eq!(
+            category("100.64.0.1"),
+            Some(AddressCategory::SharedAddressSpace)
+        );
+        assert_eq!(category("127.0.0.1"), Some(AddressCategory::Loopback));
+        assert_eq!(
+            category("203.0.113.7"),
+            Some(AddressCategory::Documentation)
+        );
+        assert_eq!(
+            category("255.255.255.255"),
+            Some(AddressCategory::Broadcast)
+        );
+        assert_eq!(category("240.0.0.1"), Some(AddressCategory::Reserved));
+        assert_eq!(category("1.1.1.1"), None);
+    }
+
+    #[test]
+    fn keeps_globally_reachable_exceptions() {
+        assert_eq!(category("192.0.0.9"), None);
+        assert_eq!(category("192.0.0.10"), None);
+        assert_eq!(
+            category("192.0.0.11"),
+            Some(AddressCategory::ProtocolAssignment)
+        );
+        assert_eq!(category("2001::1"), None);
+        assert_eq!(category("2001:4:112::1"), None);
+        assert_eq!(category("2001:20::1"), None);
+        assert_eq!(
+            category("2001:5::1"),
+            Some(AddressCategory::ProtocolAssignment)
+        );
+    }
+
+    #[test]
+    fn classifies_ipv6_ranges() {
+        assert_eq!(category("::"), Some(AddressCategory::Unspecified));
+        assert_eq!(category("::1"), Some(AddressCategory::Loopback));
+        assert_eq!(category("fe80::1"), Some(AddressCategory::LinkLocal));
+        assert_eq!(category("fd00::1"), Some(AddressCategory::UniqueLocal));
+        assert_eq!(
+            category("2001:db8::1"),
+            Some(AddressCategory::Documentation)
+        );
+        assert_eq!(category("ff02::1"), Some(AddressCategory::Multicast));
+        assert_eq!(category("100::1"), Some(AddressCategory::DiscardOnly));
+        assert_eq!(category("2606:4700:4700::1111"), None);
+    }
+
+    #[test]
+    fn classifies_embedded_ipv4_ranges() {
+        assert_eq!(
+            category("::ffff:10.0.0.1"),
+            Some(AddressCategory::Ipv4Mapped)
+        );
+        assert_eq!(
+            category("::ffff:1.1.1.1"),
+            Some(AddressCategory::Ipv4Mapped)
+        );
+        assert_eq!(
+            category("64:ff9b:1::a00:1"),
+            Some(AddressCategory::LocalTranslation)
+        );
+        // The well-known NAT64 prefix is globally reachable.
+        assert_eq!(category("64:ff9b::101:101"), None);
+    }
+}
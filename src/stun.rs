@@ -0,0 +1,422 @@
+//! Public address discovery with STUN (RFC 5389).
+//!
+//! A STUN server replies to a binding request with the address and port it saw the
+//! request come from. Unlike HTTP echo services this goes over UDP, so an HTTP proxy
+//! between the host and the internet can't answer in its place.
+
+use crate::GeoError;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::time::Duration;
+use tokio::net::{lookup_host, UdpSocket};
+use tokio::time::timeout;
+use tracing::debug;
+
+/// Public STUN servers that can be passed to [`Discovery::stun_servers`](crate::Discovery::stun_servers).
+pub const DEFAULT_STUN_SERVERS: &[&str] = &[
+    "stun.l.google.com:19302",
+    "stun.cloudflare.com:3478",
+    "stun.nextcloud.com:3478",
+];
+
+const MAGIC_COOKIE: u32 = 0x2112_a442;
+const BINDING_REQUEST: u16 = 0x0001;
+const BINDING_SUCCESS: u16 = 0x0101;
+const MAPPED_ADDRESS: u16 = 0x0001;
+const XOR_MAPPED_ADDRESS: u16 = 0x0020;
+
+/// How many times a request is sent before giving up, doubling the wait each time.
+const ATTEMPTS: u32 = 3;
+
+/// Sends a binding request to `server` (`host:port`) and returns the reflexive address it saw.
+///
+/// The request goes out over IPv4 or IPv6 depending on `family`, which only has to be an
+/// address of the right family (e.g. [`Ipv4Addr::UNSPECIFIED`]).
+pub async fn binding(
+    server: &str,
+    family: IpAddr,
+    wait: Duration,
+) -> std::result::Result<SocketAddr, GeoError> {
+    let server_address = match lookup_host(server).await {
+        Ok(mut addresses) => addresses.find(|address| address.is_ipv4() == family.is_ipv4()),
+        Err(error) => {
+            return Err(GeoError::DiscoveryError(format!(
+                "Couldn't resolve STUN server {}: {}",
+                server, error
+            )))
+        }
+    };
+
+    let server_address = match server_address {
+        Some(server_address) => server_address,
+        None => {
+            return Err(GeoError::DiscoveryError(format!(
+                "STUN server {} has no {} address",
+                server,
+                if family.is_ipv4() { "IPv4" } else { "IPv6" }
+            )))
+        }
+    };
+
+    let local_address = match family {
+        IpAddr::V4(_) => SocketAddr::from((Ipv4Addr::UNSPECIFIED, 0)),
+        IpAddr::V6(_) => SocketAddr::from((Ipv6Addr::UNSPECIFIED, 0)),
+    };
+
+    let socket = match UdpSocket::bind(local_address).await {
+        Ok(socket) => socket,
+        Err(error) => {
+            return Err(GeoError::DiscoveryError(format!(
+                "Couldn't open UDP socket: {}",
+                error
+            )))
+        }
+    };
+
+    let transaction_id = transaction_id();
+    let request = request(&transaction_id);
+
+    let mut wait = wait;
+    for _ in 0..ATTEMPTS {
+        if let Err(error) = socket.send_to(&request, server_address).await {
+            return Err(GeoError::DiscoveryError(format!(
+                "Couldn't send to STUN server {}: {}",
+                server, error
+            )));
+        }
+
+        let mut buffer = [0; 576];
+        loop {
+            let received = match timeout(wait, socket.recv_from(&mut buffer)).await {
+                Ok(Ok((length, from))) if from == server_address => length,
+                // Ignore stray datagrams from anyone else.
+                Ok(Ok(_)) => continue,
+                Ok(Err(error)) => {
+                    return Err(GeoError::DiscoveryError(format!(
+                        "Couldn't receive from STUN server {}: {}",
+                        server, error
+                    )))
+                }
+                Err(_) => break,
+            };
+
+            if let Some(address) = parse_response(&buffer[..received], &transaction_id)? {
+                debug!("{} saw us as {}", server, address);
+                return Ok(address);
+            }
+        }
+
+        wait *= 2;
+    }
+
+    Err(GeoError::DiscoveryError(format!(
+        "STUN server {} didn't answer",
+        server
+    )))
+}
+
+fn transaction_id() -> [u8; 12] {
+    let mut id = [0; 12];
+
+    for chunk in id.chunks_mut(8) {
+        let random = RandomState::new().build_hasher().finish().to_be_bytes();
+        chunk.copy_from_slice(&random[..chunk.len()]);
+    }
+
+    id
+}
+
+fn request(transaction_id: &[u8; 12]) -> Vec<u8> {
+    let mut request = Vec::with_capacity(20);
+    request.extend_from_slice(&BINDING_REQUEST.to_be_bytes());
+    request.extend_from_slice(&0u16.to_be_bytes());
+    request.extend_from_slice(&MAGIC_COOKIE.to_be_bytes());
+    request.extend_from_slice(transaction_id);
+    request
+}
+
+/// Reads the mapped address from a binding response.
+///
+/// Returns `None` for messages that aren't a response to this transaction.
+fn parse_response(
+    message: &[u8],
+    transaction_id: &[u8; 12],
+) -> std::result::Result<Option<SocketAddr>, GeoError> {
+    if message.len() < 20
+        || message[4..8] != MAGIC_COOKIE.to_be_bytes()
+        || message[8..20] != transaction_id[..]
+    {
+        return Ok(None);
+    }
+
+    let message_type = u16::from_be_bytes([message[0], message[1]]);
+    if message_type != BINDING_SUCCESS {
+        return Err(GeoError::DiscoveryError(format!(
+            "STUN server answered with message type {:#06x}",
+            message_type
+        )));
+    }
+
+    let length = u16::from_be_bytes([message[2], message[3]]) as usize;
+    let attributes = &message[20..message.len().min(20 + length)];
+
+    let mut mapped = None;
+    let mut offset = 0;
+    while offset + 4 <= attributes.len() {
+        let kind = u16::from_be_bytes([attributes[offset], attributes[offset + 1]]);
+        let size = u16::from_be_bytes([attributes[offset + 2], attributes[offset + 3]]) as usize;
+
+        let value = match attributes.get(offset + 4..offset + 4 + size) {
+            Some(value) => value,
+            None => break,
+        };
+
+        match kind {
+            XOR_MAPPED_ADDRESS => return Ok(address(value, Some(transaction_id))),
+            MAPPED_ADDRESS => mapped = address(value, None),
+            _ => (),
+        }
+
+        // Attributes are padded to a multiple of four bytes.
+        offset += 4 + size.div_ceil(4) * 4;
+    }
+
+    match mapped {
+        Some(mapped) => Ok(Some(mapped)),
+        None => Err(GeoError::DiscoveryError(
+            "STUN response didn't include a mapped address".to_string(),
+        )),
+    }
+}
+
+/// Decodes a (XOR-)MAPPED-ADDRESS value. The transaction ID is only given for XOR-MAPPED-ADDRESS.
+fn address(value: &[u8], transaction_id: Option<&[u8; 12]>) -> Option<SocketAddr> {
+    if value.len() < 4 {
+        return None;
+    }
+
+    let mut mask = [0; 16];
+    let mut port = u16::from_be_bytes([value[2], value[3]]);
+
+    if let Some(transaction_id) = transaction_id {
+        mask[..4].copy_from_slice(&MAGIC_COOKIE.to_be_bytes());
+        mask[4..].copy_from_slice(transaction_id);
+        port ^= (MAGIC_COOKIE >> 16) as u16;
+    }
+
+    let ip = match (value[1], value.len()) {
+        (0x01, 8) => {
+            let mut octets = [0; 4];
+            for (index, octet) in octets.iter_mut().enumerate() {
+                *octet = value[4 + index] ^ mask[index];
+            }
+            IpAddr::from(octets)
+        }
+        (0x02, 20) => {
+            let mut octets = [0; 16];
+            for (index, octet) in octets.iter_mut().enumerate() {
+                *octet = value[4 + index] ^ mask[index];
+            }
+            IpAddr::from(octets)
+        }
+        _ => return None,
+    };
+
+    Some(SocketAddr::new(ip, port))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TRANSACTION_ID: [u8; 12] = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12];
+    const SOFTWARE: u16 = 0x8022;
+
+    /// A binding response to `transaction_id` carrying `attributes`, padded as on the wire.
+    fn response(transaction_id: &[u8; 12], attributes: &[(u16, Vec<u8>)]) -> Vec<u8> {
+        let mut body = Vec::new();
+        for (kind, value) in attributes {
+            body.extend_from_slice(&kind.to_be_bytes());
+            body.extend_from_slice(&(value.len() as u16).to_be_bytes());
+            body.extend_from_slice(value);
+            body.resize(body.len().div_ceil(4) * 4, 0);
+        }
+
+        let mut message = Vec::new();
+        message.extend_from_slice(&BINDING_SUCCESS.to_be_bytes());
+        message.extend_from_slice(&(body.len() as u16).to_be_bytes());
+        message.extend_from_slice(&MAGIC_COOKIE.to_be_bytes());
+        message.extend_from_slice(transaction_id);
+        message.extend_from_slice(&body);
+        message
+    }
+
+    /// Encodes a MAPPED-ADDRESS value, XORed with the cookie and `transaction_id` if given.
+    fn mapped(address: SocketAddr, transaction_id: Option<&[u8; 12]>) -> Vec<u8> {
+        let mut mask = [0; 16];
+        let mut port = address.port();
+        if let Some(transaction_id) = transaction_id {
+            mask[..4].copy_from_slice(&MAGIC_COOKIE.to_be_bytes());
+            mask[4..].copy_from_slice(transaction_id);
+            port ^= (MAGIC_COOKIE >> 16) as u16;
+        }
+
+        let (family, octets) = match address.ip() {
+            IpAddr::V4(ip) => (1, ip.octets().to_vec()),
+            IpAddr::V6(ip) => (2, ip.octets().to_vec()),
+        };
+
+        let mut value = vec![0, family];
+        value.extend_from_slice(&port.to_be_bytes());
+        value.extend(
+            octets
+                .iter()
+                .zip(mask.iter())
+                .map(|(octet, mask)| octet ^ mask),
+        );
+        value
+    }
+
+    fn parse(message: &[u8]) -> Option<SocketAddr> {
+        parse_response(message, &TRANSACTION_ID).unwrap()
+    }
+
+    fn address_of(value: &[u8]) -> Option<SocketAddr> {
+        address(value, None)
+    }
+
+    #[test]
+    fn reads_xor_mapped_ipv4() {
+        let address: SocketAddr = "192.0.2.1:40000".parse().unwrap();
+        let message = response(
+            &TRANSACTION_ID,
+            &[(XOR_MAPPED_ADDRESS, mapped(address, Some(&TRANSACTION_ID)))],
+        );
+
+        assert_eq!(parse(&message), Some(address));
+    }
+
+    #[test]
+    fn reads_xor_mapped_ipv6() {
+        let address: SocketAddr = "[2001:db8::5]:40000".parse().unwrap();
+        let message = response(
+            &TRANSACTION_ID,
+            &[(XOR_MAPPED_ADDRESS, mapped(address, Some(&TRANSACTION_ID)))],
+        );
+
+        assert_eq!(parse(&message), Some(address));
+    }
+
+    #[test]
+    fn falls_back_to_mapped_address() {
+        let address: SocketAddr = "198.51.100.7:3478".parse().unwrap();
+        let message = response(&TRANSACTION_ID, &[(MAPPED_ADDRESS, mapped(address, None))]);
+
+        assert_eq!(parse(&message), Some(address));
+    }
+
+    #[test]
+    fn prefers_xor_mapped_address() {
+        let xor: SocketAddr = "192.0.2.1:40000".parse().unwrap();
+        let plain: SocketAddr = "10.0.0.1:40000".parse().unwrap();
+        let message = response(
+            &TRANSACTION_ID,
+            &[
+                (MAPPED_ADDRESS, mapped(plain, None)),
+                (XOR_MAPPED_ADDRESS, mapped(xor, Some(&TRANSACTION_ID))),
+            ],
+        );
+
+        assert_eq!(parse(&message), Some(xor));
+    }
+
+    #[test]
+    fn skips_padded_attributes() {
+        let address: SocketAddr = "192.0.2.1:40000".parse().unwrap();
+        let message = response(
+            &TRANSACTION_ID,
+            &[
+                (SOFTWARE, b"stund".to_vec()),
+                (XOR_MAPPED_ADDRESS, mapped(address, Some(&TRANSACTION_ID))),
+            ],
+        );
+
+        assert_eq!(parse(&message), Some(address));
+    }
+
+    #[test]
+    fn ignores_other_transactions() {
+        let address: SocketAddr = "192.0.2.1:40000".parse().unwrap();
+        let other = [0; 12];
+        let message = response(
+            &other,
+            &[(XOR_MAPPED_ADDRESS, mapped(address, Some(&other)))],
+        );
+
+        assert_eq!(parse(&message), None);
+        assert_eq!(parse(&message[..19]), None);
+    }
+
+    #[test]
+    fn rejects_truncated_attributes() {
+        let address: SocketAddr = "192.0.2.1:40000".parse().unwrap();
+        let mut message = response(
+            &TRANSACTION_ID,
+            &[(XOR_MAPPED_ADDRESS, mapped(address, Some(&TRANSACTION_ID)))],
+        );
+        message.truncate(message.len() - 2);
+
+        assert!(parse_response(&message, &TRANSACTION_ID).is_err());
+
+        // Values too short for their family aren't addresses.
+        assert_eq!(address_of(&[0, 1, 0x9c, 0x40, 192, 0, 2]), None);
+        assert_eq!(address_of(&[0, 2, 0x9c, 0x40, 192, 0, 2, 1]), None);
+        assert_eq!(address_of(&[0, 1]), None);
+    }
+
+    #[test]
+    fn rejects_error_responses() {
+        let mut message = response(&TRANSACTION_ID, &[]);
+        message[..2].copy_from_slice(&0x0111u16.to_be_bytes());
+
+        assert!(parse_response(&message, &TRANSACTION_ID).is_err());
+    }
+
+    #[tokio::test]
+    async fn binds_against_local_responder() {
+        let server = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let server_address = server.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            let mut request = [0; 576];
+            let (length, peer) = server.recv_from(&mut request).await.unwrap();
+            assert_eq!(length, 20);
+            assert_eq!(request[..2], BINDING_REQUEST.to_be_bytes());
+
+            let mut transaction_id = [0; 12];
+            transaction_id.copy_from_slice(&request[8..20]);
+
+            // A response to someone else's transaction comes first and is skipped.
+            let stray = response(&[0; 12], &[(MAPPED_ADDRESS, mapped(peer, None))]);
+            server.send_to(&stray, peer).await.unwrap();
+
+            let reply = response(
+                &transaction_id,
+                &[(XOR_MAPPED_ADDRESS, mapped(peer, Some(&transaction_id)))],
+            );
+            server.send_to(&reply, peer).await.unwrap();
+        });
+
+        let address = binding(
+            &server_address.to_string(),
+            IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+            Duration::from_secs(1),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(address.ip(), IpAddr::V4(Ipv4Addr::LOCALHOST));
+        assert_ne!(address.port(), 0);
+    }
+}
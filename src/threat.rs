@@ -0,0 +1,280 @@
+//! Whether an address is a proxy, VPN, Tor exit or hosting provider.
+//!
+//! Some services can tell (ip-api.com with the [`Proxy`](crate::ipapi::Field::Proxy) and
+//! [`Hosting`](crate::ipapi::Field::Hosting) fields, ipinfo.io's privacy data, and the
+//! security data on ipwhois and ipapi.co paid plans), others can't. Each flag in a
+//! [`Security`] is therefore [`Flag::Unknown`] until a source says otherwise, and records
+//! which source that was. Offline lists of Tor exits and VPN ranges are another source (see
+//! [`anonymizers`](crate::anonymizers)).
+
+use crate::Service;
+use serde::Deserialize;
+use std::fmt;
+
+/// A yes/no answer that may not be known.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Flag {
+    Yes,
+    No,
+    #[default]
+    Unknown,
+}
+
+impl Flag {
+    /// Whether the answer is known to be yes.
+    pub fn is_yes(&self) -> bool {
+        matches!(self, Flag::Yes)
+    }
+}
+
+impl From<bool> for Flag {
+    fn from(value: bool) -> Self {
+        if value {
+            Flag::Yes
+        } else {
+            Flag::No
+        }
+    }
+}
+
+impl fmt::Display for Flag {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Flag::Yes => write!(f, "yes"),
+            Flag::No => write!(f, "no"),
+            Flag::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
+/// Where a flag came from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Source {
+    /// The service that answered the lookup.
+    Service(Service),
+    /// An offline list, by file name (see [`Anonymizers`](crate::anonymizers::Anonymizers)).
+    List(String),
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Source::Service(service) => write!(f, "{}", service),
+            Source::List(name) => write!(f, "{}", name),
+        }
+    }
+}
+
+/// A flag and the source that set it. The source is `None` while the flag is unknown.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Signal {
+    pub flag: Flag,
+    pub source: Option<Source>,
+}
+
+impl Signal {
+    /// Records `flag` from `source`, unless it would lose information: a known flag isn't
+    /// replaced by [`Flag::Unknown`], and [`Flag::Yes`] isn't replaced by [`Flag::No`].
+    pub fn set(&mut self, flag: Flag, source: Source) {
+        let replace = !matches!(
+            (self.flag, flag),
+            (_, Flag::Unknown) | (Flag::Yes, Flag::No)
+        );
+
+        if replace {
+            self.flag = flag;
+            self.source = Some(source);
+        }
+    }
+}
+
+/// Proxy, VPN, Tor and hosting flags for an address.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Security {
+    /// Whether the address is an open or anonymous proxy. ip-api.com also counts VPNs and Tor
+    /// exits as proxies.
+    pub proxy: Signal,
+    pub vpn: Signal,
+    /// Whether the address is a Tor exit node.
+    pub tor: Signal,
+    /// Whether the address belongs to a hosting provider or data center.
+    pub hosting: Signal,
+}
+
+impl Security {
+    /// Whether the address is known to be a proxy, VPN or Tor exit.
+    pub fn is_anonymous(&self) -> bool {
+        self.proxy.flag.is_yes() || self.vpn.flag.is_yes() || self.tor.flag.is_yes()
+    }
+
+    /// Records every known flag in `other` (see [`Signal::set`]).
+    pub fn merge(&mut self, other: Security) {
+        let signals = [
+            (&mut self.proxy, other.proxy),
+            (&mut self.vpn, other.vpn),
+            (&mut self.tor, other.tor),
+            (&mut self.hosting, other.hosting),
+        ];
+
+        for (signal, other) in signals {
+            if let Some(source) = other.source {
+                signal.set(other.flag, source);
+            }
+        }
+    }
+}
+
+/// The `proxy`, `vpn`, `tor` and `hosting` booleans in a service's response. Each is `None`
+/// when the service didn't send it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+pub struct Flags {
+    pub proxy: Option<bool>,
+    pub vpn: Option<bool>,
+    pub tor: Option<bool>,
+    pub hosting: Option<bool>,
+}
+
+impl Flags {
+    /// The flags as a [`Security`], with `service` as the source.
+    pub(crate) fn security(&self, service: Service) -> Security {
+        let mut security = Security::default();
+
+        let signals = [
+            (&mut security.proxy, self.proxy),
+            (&mut security.vpn, self.vpn),
+            (&mut security.tor, self.tor),
+            (&mut security.hosting, self.hosting),
+        ];
+
+        for (signal, value) in signals {
+            if let Some(value) = value {
+                signal.set(Flag::from(value), Source::Service(service));
+            }
+        }
+
+        security
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{decode, ipapi, ipwhois, Client};
+    use serde_json::json;
+
+    #[test]
+    fn reads_ipapi_proxy_and_hosting() {
+        let body = json!({
+            "status": "success",
+            "country": "Australia",
+            "proxy": true,
+            "hosting": false
+        });
+
+        let response: ipapi::Response = decode(&body, "ip-api.com").unwrap();
+        let client = Client::new(Service::IpApi).ipapi_fields(&[
+            ipapi::Field::Country,
+            ipapi::Field::Proxy,
+            ipapi::Field::Hosting,
+        ]);
+        let security = response.locator("1.1.1.1", &client).unwrap().security;
+
+        assert_eq!(security.proxy.flag, Flag::Yes);
+        assert_eq!(security.proxy.source, Some(Source::Service(Service::IpApi)));
+        assert_eq!(security.hosting.flag, Flag::No);
+        assert_eq!(
+            security.hosting.source,
+            Some(Source::Service(Service::IpApi))
+        );
+
+        // ip-api.com has no separate VPN or Tor flags.
+        assert_eq!(security.vpn, Signal::default());
+        assert_eq!(security.tor, Signal::default());
+        assert!(security.is_anonymous());
+    }
+
+    #[test]
+    fn reads_ipwhois_pro_security() {
+        let body = json!({
+            "ip": "185.220.101.1",
+            "success": true,
+            "latitude": 52.52,
+            "longitude": 13.405,
+            "city": "Berlin",
+            "region": "Berlin",
+            "country": "Germany",
+            "country_code": "DE",
+            "timezone": {"id": "Europe/Berlin"},
+            "connection": {"isp": "Tor exit"},
+            "security": {
+                "anonymous": true,
+                "proxy": false,
+                "vpn": false,
+                "tor": true,
+                "hosting": true
+            }
+        });
+
+        let response: ipwhois::ProResponse = decode(&body, "ipwhois.pro").unwrap();
+        let client = Client::new(Service::IpWhois).api_key("key");
+        let security = response.locator("185.220.101.1", &client).unwrap().security;
+
+        let source = Some(Source::Service(Service::IpWhois));
+        assert_eq!(security.tor.flag, Flag::Yes);
+        assert_eq!(security.hosting.flag, Flag::Yes);
+        assert_eq!(security.proxy.flag, Flag::No);
+        assert_eq!(security.vpn.flag, Flag::No);
+        for signal in [
+            &security.proxy,
+            &security.vpn,
+            &security.tor,
+            &security.hosting,
+        ] {
+            assert_eq!(signal.source, source);
+        }
+        assert!(security.is_anonymous());
+    }
+
+    #[test]
+    fn leaves_absent_flags_unknown() {
+        assert_eq!(
+            Flags::default().security(Service::IpApiCo),
+            Security::default()
+        );
+
+        let flags = Flags {
+            vpn: Some(false),
+            ..Flags::default()
+        };
+        let security = flags.security(Service::IpApiCo);
+
+        assert_eq!(security.vpn.flag, Flag::No);
+        assert_eq!(security.proxy, Signal::default());
+        assert!(!security.is_anonymous());
+    }
+
+    #[test]
+    fn keeps_yes_over_no() {
+        let mut security = Flags {
+            tor: Some(true),
+            ..Flags::default()
+        }
+        .security(Service::IpApi);
+
+        security.merge(
+            Flags {
+                tor: Some(false),
+                hosting: Some(true),
+                ..Flags::default()
+            }
+            .security(Service::IpWhois),
+        );
+
+        assert_eq!(security.tor.flag, Flag::Yes);
+        assert_eq!(security.tor.source, Some(Source::Service(Service::IpApi)));
+        assert_eq!(
+            security.hosting.source,
+            Some(Source::Service(Service::IpWhois))
+        );
+    }
+}
@@ -0,0 +1,200 @@
+//! IANA time zones from the bundled tz database.
+//!
+//! Services name the zone of an address (`America/Denver`), which [`Locator::timezone`]
+//! keeps as they sent it. [`Locator::timezone_info`] holds the same zone parsed against the
+//! tz database, so the current offset and local time can be worked out; names the database
+//! doesn't know are rejected rather than guessed at, and the error is kept in
+//! [`Locator::timezone_error`].
+//!
+//! [`Locator::timezone`]: crate::Locator::timezone
+//! [`Locator::timezone_info`]: crate::Locator::timezone_info
+//! [`Locator::timezone_error`]: crate::Locator::timezone_error
+
+use crate::GeoError;
+use chrono::{DateTime, FixedOffset, NaiveTime, Offset, TimeZone as _, Utc};
+use chrono_tz::{OffsetComponents, Tz};
+use std::fmt;
+
+/// A time zone from the IANA tz database, like `America/Denver`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TimeZone {
+    tz: Tz,
+}
+
+impl TimeZone {
+    /// Parses an IANA zone name, failing if the tz database doesn't have it.
+    pub fn parse(name: &str) -> std::result::Result<Self, GeoError> {
+        match name.trim().parse::<Tz>() {
+            Ok(tz) => Ok(TimeZone { tz }),
+            Err(_) => Err(GeoError::ParseError(format!(
+                "{} isn't a known time zone",
+                name
+            ))),
+        }
+    }
+
+    /// The zone's IANA name.
+    pub fn name(&self) -> &'static str {
+        self.tz.name()
+    }
+
+    /// The zone as a [`chrono_tz::Tz`], for anything not covered here.
+    pub fn tz(&self) -> Tz {
+        self.tz
+    }
+
+    /// The current time in the zone.
+    pub fn now(&self) -> DateTime<Tz> {
+        Utc::now().with_timezone(&self.tz)
+    }
+
+    /// The zone's current offset from UTC, including daylight saving time.
+    pub fn offset(&self) -> FixedOffset {
+        self.offset_at(Utc::now())
+    }
+
+    /// The zone's offset from UTC at `time`.
+    pub fn offset_at(&self, time: DateTime<Utc>) -> FixedOffset {
+        self.tz.offset_from_utc_datetime(&time.naive_utc()).fix()
+    }
+
+    /// Whether daylight saving time is currently in effect.
+    pub fn is_dst(&self) -> bool {
+        self.is_dst_at(Utc::now())
+    }
+
+    /// Whether daylight saving time is in effect at `time`.
+    pub fn is_dst_at(&self, time: DateTime<Utc>) -> bool {
+        !self
+            .tz
+            .offset_from_utc_datetime(&time.naive_utc())
+            .dst_offset()
+            .is_zero()
+    }
+
+    /// Whether the local time is currently between `start` and `end`, like business hours.
+    ///
+    /// A range where `end` comes before `start` wraps past midnight.
+    pub fn is_between(&self, start: NaiveTime, end: NaiveTime) -> bool {
+        self.is_between_at(start, end, Utc::now())
+    }
+
+    /// Whether the local time at `time` is between `start` and `end`.
+    pub fn is_between_at(&self, start: NaiveTime, end: NaiveTime, time: DateTime<Utc>) -> bool {
+        let local = time.with_timezone(&self.tz).time();
+
+        if start <= end {
+            start <= local && local < end
+        } else {
+            start <= local || local < end
+        }
+    }
+}
+
+impl fmt::Display for TimeZone {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Locator;
+
+    // America/Denver moved from MST (UTC-7) to MDT (UTC-6) at 2024-03-10 09:00 UTC.
+    fn utc(hour: u32, minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 3, 10, hour, minute, 0).unwrap()
+    }
+
+    fn hours(hours: i32) -> FixedOffset {
+        FixedOffset::east_opt(hours * 3600).unwrap()
+    }
+
+    fn time(hour: u32, minute: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(hour, minute, 0).unwrap()
+    }
+
+    #[test]
+    fn offset_changes_at_transition() {
+        let denver = TimeZone::parse("America/Denver").unwrap();
+
+        assert_eq!(denver.offset_at(utc(8, 59)), hours(-7));
+        assert_eq!(denver.offset_at(utc(9, 0)), hours(-6));
+    }
+
+    #[test]
+    fn dst_starts_at_transition() {
+        let denver = TimeZone::parse("America/Denver").unwrap();
+
+        assert!(!denver.is_dst_at(utc(8, 59)));
+        assert!(denver.is_dst_at(utc(9, 0)));
+    }
+
+    #[test]
+    fn zones_without_dst_never_observe_it() {
+        let phoenix = TimeZone::parse("America/Phoenix").unwrap();
+
+        assert!(!phoenix.is_dst_at(utc(9, 0)));
+        assert_eq!(phoenix.offset_at(utc(9, 0)), hours(-7));
+    }
+
+    #[test]
+    fn compares_local_time_across_transition() {
+        let denver = TimeZone::parse("America/Denver").unwrap();
+
+        // 01:59 MST, then 03:00 MDT a minute later: local 02:00-03:00 never happens.
+        assert!(denver.is_between_at(time(1, 0), time(2, 0), utc(8, 59)));
+        assert!(!denver.is_between_at(time(2, 0), time(3, 0), utc(8, 59)));
+        assert!(!denver.is_between_at(time(2, 0), time(3, 0), utc(9, 0)));
+        assert!(denver.is_between_at(time(3, 0), time(4, 0), utc(9, 0)));
+    }
+
+    #[test]
+    fn wraps_past_midnight() {
+        let denver = TimeZone::parse("America/Denver").unwrap();
+
+        // 06:30 UTC is 23:30 MST the night before.
+        assert!(denver.is_between_at(time(22, 0), time(6, 0), utc(6, 30)));
+        assert!(!denver.is_between_at(time(22, 0), time(6, 0), utc(18, 0)));
+    }
+
+    #[test]
+    fn rejects_unknown_names() {
+        assert!(TimeZone::parse("Mars/Olympus_Mons").is_err());
+        assert_eq!(
+            TimeZone::parse(" Europe/Berlin ").unwrap().name(),
+            "Europe/Berlin"
+        );
+    }
+
+    #[test]
+    fn keeps_parse_error_on_locator() {
+        let mut unknown = Locator {
+            timezone: "Mars/Olympus_Mons".to_string(),
+            ..Locator::default()
+        };
+        unknown.normalize();
+
+        assert!(unknown.timezone_info.is_none());
+        assert!(matches!(
+            unknown.timezone_error,
+            Some(GeoError::ParseError(_))
+        ));
+
+        let mut missing = Locator::default();
+        missing.normalize();
+
+        assert!(missing.timezone_info.is_none());
+        assert!(missing.timezone_error.is_none());
+
+        let mut known = Locator {
+            timezone: "America/Denver".to_string(),
+            ..Locator::default()
+        };
+        known.normalize();
+
+        assert_eq!(known.timezone_info.unwrap().name(), "America/Denver");
+        assert!(known.timezone_error.is_none());
+    }
+}
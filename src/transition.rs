@@ -0,0 +1,149 @@
+//! IPv6 transition addresses that carry an IPv4 address.
+//!
+//! IPv4-mapped, 6to4, Teredo, NAT64 and ISATAP addresses all embed the IPv4 address
+//! of the host behind them, which is the address that actually needs geolocating.
+
+use std::fmt;
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+/// The transition mechanism an IPv6 address was formed with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Mechanism {
+    /// `::ffff:a.b.c.d`
+    Ipv4Mapped,
+    /// `2002::/16`, with the IPv4 address in the next 32 bits.
+    SixToFour,
+    /// `2001::/32`, with the client address and port obfuscated in the last 48 bits.
+    Teredo {
+        /// The Teredo server the client registered with.
+        server: Ipv4Addr,
+        /// The client's external UDP port.
+        port: u16,
+    },
+    /// The `64:ff9b::/96` well-known NAT64 prefix.
+    Nat64,
+    /// An ISATAP interface identifier (`::0:5efe:a.b.c.d` or `::200:5efe:a.b.c.d`) under a
+    /// global unicast (`2000::/3`) prefix. Link-local and unique local ISATAP addresses only
+    /// reach the local network, so they aren't looked up through their IPv4 address.
+    Isatap,
+}
+
+impl fmt::Display for Mechanism {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Mechanism::Ipv4Mapped => write!(f, "IPv4-mapped"),
+            Mechanism::SixToFour => write!(f, "6to4"),
+            Mechanism::Teredo { .. } => write!(f, "Teredo"),
+            Mechanism::Nat64 => write!(f, "NAT64"),
+            Mechanism::Isatap => write!(f, "ISATAP"),
+        }
+    }
+}
+
+/// An IPv6 address that was looked up through the IPv4 address embedded in it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Transition {
+    /// The IPv6 address that was asked about.
+    pub original: Ipv6Addr,
+    /// The IPv4 address extracted from it.
+    pub ipv4: Ipv4Addr,
+    /// How the IPv4 address was embedded.
+    pub mechanism: Mechanism,
+}
+
+/// Extracts the IPv4 address embedded in a transition address, if `ip` is one.
+pub fn embedded_ipv4(ip: Ipv6Addr) -> Option<Transition> {
+    let segments = ip.segments();
+    let bits = u128::from(ip);
+    let low = Ipv4Addr::from(bits as u32);
+
+    let (ipv4, mechanism) = if let Some(ipv4) = ip.to_ipv4_mapped() {
+        (ipv4, Mechanism::Ipv4Mapped)
+    } else if segments[0] == 0x2002 {
+        (Ipv4Addr::from((bits >> 80) as u32), Mechanism::SixToFour)
+    } else if segments[0] == 0x2001 && segments[1] == 0 {
+        let server = Ipv4Addr::from((bits >> 64) as u32);
+        let port = !segments[5];
+        let client = Ipv4Addr::from(!(bits as u32));
+
+        (client, Mechanism::Teredo { server, port })
+    } else if segments[..6] == [0x64, 0xff9b, 0, 0, 0, 0] {
+        (low, Mechanism::Nat64)
+    } else if segments[0] & 0xe000 == 0x2000 && segments[4] & 0xfdff == 0 && segments[5] == 0x5efe {
+        (low, Mechanism::Isatap)
+    } else {
+        return None;
+    };
+
+    Some(Transition {
+        original: ip,
+        ipv4,
+        mechanism,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn embedded(ip: &str) -> Option<(Ipv4Addr, Mechanism)> {
+        embedded_ipv4(ip.parse().unwrap()).map(|transition| (transition.ipv4, transition.mechanism))
+    }
+
+    #[test]
+    fn extracts_ipv4_mapped() {
+        assert_eq!(
+            embedded("::ffff:1.1.1.1"),
+            Some((Ipv4Addr::new(1, 1, 1, 1), Mechanism::Ipv4Mapped))
+        );
+    }
+
+    #[test]
+    fn extracts_six_to_four() {
+        assert_eq!(
+            embedded("2002:c000:0204::1"),
+            Some((Ipv4Addr::new(192, 0, 2, 4), Mechanism::SixToFour))
+        );
+    }
+
+    #[test]
+    fn deobfuscates_teredo() {
+        // RFC 4380's example: server 65.54.227.120, client 192.0.2.45 on port 40000.
+        assert_eq!(
+            embedded("2001:0:4136:e378:8000:63bf:3fff:fdd2"),
+            Some((
+                Ipv4Addr::new(192, 0, 2, 45),
+                Mechanism::Teredo {
+                    server: Ipv4Addr::new(65, 54, 227, 120),
+                    port: 40000,
+                }
+            ))
+        );
+    }
+
+    #[test]
+    fn extracts_nat64_and_isatap() {
+        assert_eq!(
+            embedded("64:ff9b::808:808"),
+            Some((Ipv4Addr::new(8, 8, 8, 8), Mechanism::Nat64))
+        );
+        assert_eq!(
+            embedded("2001:db8::200:5efe:c000:21e"),
+            Some((Ipv4Addr::new(192, 0, 2, 30), Mechanism::Isatap))
+        );
+    }
+
+    #[test]
+    fn ignores_local_isatap() {
+        assert_eq!(embedded("fe80::5efe:c000:21e"), None);
+        assert_eq!(embedded("fd00::5efe:808:808"), None);
+    }
+
+    #[test]
+    fn ignores_native_ipv6() {
+        assert_eq!(embedded("2606:4700:4700::1111"), None);
+        assert_eq!(embedded("::1"), None);
+        // Local-use NAT64 isn't the well-known prefix.
+        assert_eq!(embedded("64:ff9b:1::808:808"), None);
+    }
+}
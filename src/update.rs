@@ -0,0 +1,735 @@
+//! Downloading and installing offline databases.
+//!
+//! An [`Updater`] fetches a GeoLite2 or DB-IP style archive, checks it against the
+//! SHA-256 checksum published next to it, extracts the `.mmdb` or `.csv` file inside (see
+//! [`Updater::entry`]) and moves it over the installed copy in one rename. An `.mmdb` file is
+//! opened as a [`Database`] before it's moved into place, so a broken download never replaces
+//! a working database.
+
+use crate::{Database, GeoError, TransportPolicy};
+use flate2::read::GzDecoder;
+use reqwest::Url;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io::{Cursor, Read};
+use std::path::{Path, PathBuf};
+use tracing::debug;
+
+/// What an [`Updater`] did when it was run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UpdateStatus {
+    /// A new database was downloaded and installed.
+    Updated,
+    /// The remote database matches the installed one, so nothing was downloaded.
+    Unchanged,
+}
+
+/// Downloads a database archive and installs it at a path.
+#[derive(Debug, Clone)]
+pub struct Updater {
+    url: String,
+    checksum_url: Option<String>,
+    license_key: Option<String>,
+    entry: Option<String>,
+    destination: PathBuf,
+    transport: TransportPolicy,
+}
+
+impl Updater {
+    /// Creates an updater that downloads `url` and installs it at `destination`.
+    ///
+    /// `url` may point to a `.tar.gz` archive, a gzipped file or an uncompressed
+    /// `.mmdb`/`.csv` file.
+    pub fn new<P: AsRef<Path>>(url: &str, destination: P) -> Self {
+        Updater {
+            url: url.to_string(),
+            checksum_url: None,
+            license_key: None,
+            entry: None,
+            destination: destination.as_ref().to_path_buf(),
+            transport: TransportPolicy::new(),
+        }
+    }
+
+    /// Sends `license_key` as the `license_key` query parameter (as MaxMind requires).
+    pub fn license_key(mut self, license_key: &str) -> Self {
+        self.license_key = Some(license_key.to_string());
+        self
+    }
+
+    /// Installs the archive file named `name`, like `GeoLite2-City-Blocks-IPv4.csv`.
+    ///
+    /// By default the archive's only `.mmdb` file is installed, or its only `.csv` file. CSV
+    /// archives have several, so one must be named.
+    pub fn entry(mut self, name: &str) -> Self {
+        self.entry = Some(name.to_string());
+        self
+    }
+
+    /// Sets the [`TransportPolicy`] for downloads, like refusing plain HTTP or trusting a
+    /// proxy's root certificate.
+    pub fn transport(mut self, transport: TransportPolicy) -> Self {
+        self.transport = transport;
+        self
+    }
+
+    /// Sets where the SHA-256 checksum is published.
+    ///
+    /// Defaults to the download URL with `.sha256` appended, which matches both
+    /// MaxMind's `suffix=tar.gz.sha256` and plain `<file>.sha256` layouts.
+    pub fn checksum_url(mut self, checksum_url: &str) -> Self {
+        self.checksum_url = Some(checksum_url.to_string());
+        self
+    }
+
+    /// Downloads and installs the database unless the published checksum matches
+    /// the one recorded at the last install.
+    ///
+    /// Unless it's a CSV file, the new database is opened before it's installed. If that
+    /// fails, the installed file and its recorded checksum are left as they were, so the next
+    /// run tries again.
+    pub async fn update(&self) -> std::result::Result<UpdateStatus, GeoError> {
+        let http = self.transport.http_client(None)?;
+
+        let checksum = self.remote_checksum(&http).await?;
+
+        if self.installed_checksum().as_deref() == Some(checksum.as_str()) {
+            debug!("{} is already up to date", self.destination.display());
+            return Ok(UpdateStatus::Unchanged);
+        }
+
+        let archive = self.download(&http, &self.url).await?;
+
+        let digest = hex(&Sha256::digest(&archive));
+        if digest != checksum {
+            return Err(GeoError::DatabaseError(format!(
+                "Checksum mismatch for {}: expected {}, got {}",
+                self.url, checksum, digest
+            )));
+        }
+
+        let contents = extract(&archive, self.entry.as_deref())?;
+        self.install(&contents, &checksum)?;
+
+        Ok(UpdateStatus::Updated)
+    }
+
+    /// Runs [`update`](Updater::update) and reloads `database` if a new file was installed.
+    ///
+    /// Fails without downloading anything if the destination is a `.csv` file, which a
+    /// [`Database`] can't load.
+    pub async fn update_database(
+        &self,
+        database: &Database,
+    ) -> std::result::Result<UpdateStatus, GeoError> {
+        self.check_loadable()?;
+
+        let status = self.update().await?;
+
+        if status == UpdateStatus::Updated {
+            database.reload_from(&self.destination)?;
+        }
+
+        Ok(status)
+    }
+
+    /// Fails if the installed file would be a CSV database, which [`Database`] can't load.
+    fn check_loadable(&self) -> std::result::Result<(), GeoError> {
+        if self.is_csv() {
+            return Err(GeoError::DatabaseError(format!(
+                "{} is a CSV database, which can't be loaded as a Database",
+                self.destination.display()
+            )));
+        }
+
+        Ok(())
+    }
+
+    fn is_csv(&self) -> bool {
+        self.destination.to_string_lossy().ends_with(".csv")
+            || matches!(&self.entry, Some(entry) if entry.ends_with(".csv"))
+    }
+
+    async fn remote_checksum(
+        &self,
+        http: &reqwest::Client,
+    ) -> std::result::Result<String, GeoError> {
+        let checksum_url = match &self.checksum_url {
+            Some(checksum_url) => checksum_url.clone(),
+            None => format!("{}.sha256", self.url),
+        };
+
+        let body = self.download(http, &checksum_url).await?;
+
+        // Checksum files look like `<hex digest>  <file name>`.
+        match String::from_utf8_lossy(&body).split_whitespace().next() {
+            Some(checksum) if checksum.len() == 64 => Ok(checksum.to_lowercase()),
+            _ => Err(GeoError::ParseError(format!(
+                "Couldn't find a SHA-256 checksum at {}",
+                checksum_url
+            ))),
+        }
+    }
+
+    async fn download(
+        &self,
+        http: &reqwest::Client,
+        url: &str,
+    ) -> std::result::Result<Vec<u8>, GeoError> {
+        self.transport.check(url)?;
+
+        let mut url = match Url::parse(url) {
+            Ok(url) => url,
+            Err(error) => {
+                return Err(GeoError::HttpError(format!(
+                    "Invalid URL {}: {}",
+                    url, error
+                )))
+            }
+        };
+
+        if let Some(license_key) = &self.license_key {
+            url.query_pairs_mut()
+                .append_pair("license_key", license_key);
+        }
+
+        let response = match http.get(url.clone()).send().await {
+            Ok(response) => response,
+            Err(_) => {
+                return Err(GeoError::HttpError(format!(
+                    "Couldn't connect to {}",
+                    url.host_str().unwrap_or_default()
+                )))
+            }
+        };
+
+        if !response.status().is_success() {
+            return Err(GeoError::HttpError(format!(
+                "{} returned {}",
+                url.host_str().unwrap_or_default(),
+                response.status()
+            )));
+        }
+
+        match response.bytes().await {
+            Ok(bytes) => Ok(bytes.to_vec()),
+            Err(_) => Err(GeoError::HttpError(format!(
+                "Couldn't read data from {}",
+                url.host_str().unwrap_or_default()
+            ))),
+        }
+    }
+
+    fn installed_checksum(&self) -> Option<String> {
+        if !self.destination.exists() {
+            return None;
+        }
+
+        fs::read_to_string(self.checksum_path())
+            .ok()
+            .map(|checksum| checksum.trim().to_string())
+    }
+
+    fn install(&self, contents: &[u8], checksum: &str) -> std::result::Result<(), GeoError> {
+        let mut staging = self.destination.clone().into_os_string();
+        staging.push(".download");
+        let staging = PathBuf::from(staging);
+
+        if let Err(error) = fs::write(&staging, contents) {
+            let _ = fs::remove_file(&staging);
+
+            return Err(GeoError::DatabaseError(format!(
+                "Couldn't install {}: {}",
+                self.destination.display(),
+                error
+            )));
+        }
+
+        if !self.is_csv() {
+            if let Err(error) = Database::open(&staging) {
+                let _ = fs::remove_file(&staging);
+                return Err(error);
+            }
+        }
+
+        let result = fs::rename(&staging, &self.destination)
+            .and_then(|_| fs::write(self.checksum_path(), checksum));
+
+        if let Err(error) = result {
+            let _ = fs::remove_file(&staging);
+
+            return Err(GeoError::DatabaseError(format!(
+                "Couldn't install {}: {}",
+                self.destination.display(),
+                error
+            )));
+        }
+
+        debug!("installed new database at {}", self.destination.display());
+
+        Ok(())
+    }
+
+    fn checksum_path(&self) -> PathBuf {
+        let mut path = self.destination.clone().into_os_string();
+        path.push(".sha256");
+        PathBuf::from(path)
+    }
+}
+
+/// Pulls the database file out of a downloaded archive: the entry named `entry`, or else the
+/// only `.mmdb` file, or else the only `.csv` file.
+fn extract(archive: &[u8], entry: Option<&str>) -> std::result::Result<Vec<u8>, GeoError> {
+    // Gzip magic number.
+    if !archive.starts_with(&[0x1f, 0x8b]) {
+        return Ok(archive.to_vec());
+    }
+
+    let mut decompressed = Vec::new();
+    if let Err(error) = GzDecoder::new(archive).read_to_end(&mut decompressed) {
+        return Err(GeoError::DatabaseError(format!(
+            "Couldn't decompress archive: {}",
+            error
+        )));
+    }
+
+    // A plain `.mmdb.gz` or `.csv.gz` rather than a tarball.
+    if decompressed.len() < 512 || &decompressed[257..262] != b"ustar" {
+        return Ok(decompressed);
+    }
+
+    let names = entry_names(&decompressed)?;
+    let name = match entry {
+        Some(entry) => match names.iter().find(|name| file_name(name) == entry) {
+            Some(name) => name.clone(),
+            None => {
+                return Err(GeoError::DatabaseError(format!(
+                    "Archive doesn't contain {}",
+                    entry
+                )))
+            }
+        },
+        None => choose(&names)?,
+    };
+
+    let mut tarball = tar::Archive::new(Cursor::new(decompressed));
+    let entries = match tarball.entries() {
+        Ok(entries) => entries,
+        Err(error) => {
+            return Err(GeoError::DatabaseError(format!(
+                "Couldn't read archive: {}",
+                error
+            )))
+        }
+    };
+
+    for mut entry in entries.flatten() {
+        let matches = match entry.path() {
+            Ok(path) => path.to_string_lossy() == name,
+            Err(_) => false,
+        };
+
+        if matches {
+            let mut contents = Vec::new();
+            if let Err(error) = entry.read_to_end(&mut contents) {
+                return Err(GeoError::DatabaseError(format!(
+                    "Couldn't extract database: {}",
+                    error
+                )));
+            }
+
+            return Ok(contents);
+        }
+    }
+
+    Err(GeoError::DatabaseError(format!(
+        "Archive doesn't contain {}",
+        name
+    )))
+}
+
+/// The paths of the files in a tarball.
+fn entry_names(tarball: &[u8]) -> std::result::Result<Vec<String>, GeoError> {
+    let mut tarball = tar::Archive::new(Cursor::new(tarball));
+    let entries = match tarball.entries() {
+        Ok(entries) => entries,
+        Err(error) => {
+            return Err(GeoError::DatabaseError(format!(
+                "Couldn't read archive: {}",
+                error
+            )))
+        }
+    };
+
+    let mut names = Vec::new();
+    for entry in entries {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(error) => {
+                return Err(GeoError::DatabaseError(format!(
+                    "Couldn't read archive: {}",
+                    error
+                )))
+            }
+        };
+
+        if let Ok(path) = entry.path() {
+            names.push(path.to_string_lossy().into_owned());
+        }
+    }
+
+    Ok(names)
+}
+
+/// The only `.mmdb` file in a tarball, or else the only `.csv` file.
+///
+/// CSV archives hold several files (`Blocks-IPv4`, `Blocks-IPv6`, `Locations-en`, ...), so
+/// one of them has to be picked with [`Updater::entry`].
+fn choose(names: &[String]) -> std::result::Result<String, GeoError> {
+    for extension in [".mmdb", ".csv"].iter() {
+        let candidates: Vec<&String> = names
+            .iter()
+            .filter(|name| name.ends_with(extension))
+            .collect();
+
+        match candidates.len() {
+            0 => continue,
+            1 => return Ok(candidates[0].clone()),
+            _ => {
+                let candidates: Vec<&str> = candidates.iter().map(|name| file_name(name)).collect();
+                return Err(GeoError::DatabaseError(format!(
+                    "Archive contains several {} files ({}); pick one with Updater::entry",
+                    extension,
+                    candidates.join(", ")
+                )));
+            }
+        }
+    }
+
+    Err(GeoError::DatabaseError(
+        "Archive doesn't contain a .mmdb or .csv file".to_string(),
+    ))
+}
+
+fn file_name(path: &str) -> &str {
+    path.rsplit('/').next().unwrap_or(path)
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::offline::tests::city_database;
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// A local HTTP stand-in serving fixed files and recording the paths asked for.
+    struct Server {
+        url: String,
+        files: Arc<Mutex<HashMap<String, Vec<u8>>>>,
+        requests: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl Server {
+        async fn start() -> Self {
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let url = format!("http://{}", listener.local_addr().unwrap());
+            let files = Arc::new(Mutex::new(HashMap::<String, Vec<u8>>::new()));
+            let requests = Arc::new(Mutex::new(Vec::new()));
+
+            let (served, recorded) = (files.clone(), requests.clone());
+            tokio::spawn(async move {
+                loop {
+                    let (mut stream, _) = listener.accept().await.unwrap();
+                    let mut request = Vec::new();
+                    let mut buffer = [0; 1024];
+                    while !request.ends_with(b"\r\n\r\n") {
+                        match stream.read(&mut buffer).await {
+                            Ok(0) | Err(_) => break,
+                            Ok(read) => request.extend_from_slice(&buffer[..read]),
+                        }
+                    }
+
+                    let request = String::from_utf8_lossy(&request);
+                    let path = request.split(' ').nth(1).unwrap_or_default().to_string();
+                    recorded.lock().unwrap().push(path.clone());
+
+                    let file = served.lock().unwrap().get(&path).cloned();
+                    let (status, body) = match file {
+                        Some(body) => ("200 OK", body),
+                        None => ("404 Not Found", Vec::new()),
+                    };
+                    let head = format!(
+                        "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                        status,
+                        body.len()
+                    );
+                    let _ = stream.write_all(head.as_bytes()).await;
+                    let _ = stream.write_all(&body).await;
+                }
+            });
+
+            Server {
+                url,
+                files,
+                requests,
+            }
+        }
+
+        /// Serves `archive` at `/<name>` and its checksum (or `checksum`) at `/<name>.sha256`.
+        fn publish(&self, name: &str, archive: &[u8], checksum: Option<&str>) {
+            let checksum = match checksum {
+                Some(checksum) => checksum.to_string(),
+                None => hex(&Sha256::digest(archive)),
+            };
+
+            let mut files = self.files.lock().unwrap();
+            files.insert(format!("/{}", name), archive.to_vec());
+            files.insert(
+                format!("/{}.sha256", name),
+                format!("{}  {}\n", checksum, name).into_bytes(),
+            );
+        }
+
+        fn requests_for(&self, path: &str) -> usize {
+            let requests = self.requests.lock().unwrap();
+            requests.iter().filter(|request| *request == path).count()
+        }
+    }
+
+    /// A `.tar.gz` archive holding `files` in a dated directory, like MaxMind's.
+    fn archive(files: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut builder = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+        for (name, contents) in files {
+            let mut header = tar::Header::new_ustar();
+            header.set_size(contents.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder
+                .append_data(
+                    &mut header,
+                    format!("GeoLite2-City_20240101/{}", name),
+                    *contents,
+                )
+                .unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap()
+    }
+
+    fn directory(test: &str) -> PathBuf {
+        let directory = std::env::temp_dir().join(format!(
+            "ipgeolocate-update-{}-{}",
+            std::process::id(),
+            test
+        ));
+        let _ = fs::remove_dir_all(&directory);
+        fs::create_dir_all(&directory).unwrap();
+        directory
+    }
+
+    fn staged_files(directory: &Path) -> Vec<PathBuf> {
+        fs::read_dir(directory)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.to_string_lossy().ends_with(".download"))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn rejects_checksum_mismatch() {
+        let server = Server::start().await;
+        let new = city_database("192.0.2.0/24", "Hamburg");
+        let archive = archive(&[("GeoLite2-City.mmdb", &new)]);
+        server.publish("db.tar.gz", &archive, Some(&hex(&Sha256::digest(b"other"))));
+
+        let directory = directory("mismatch");
+        let destination = directory.join("GeoLite2-City.mmdb");
+        fs::write(&destination, b"old database").unwrap();
+
+        let updater = Updater::new(&format!("{}/db.tar.gz", server.url), &destination);
+        match updater.update().await {
+            Err(GeoError::DatabaseError(error)) => assert!(error.contains("Checksum mismatch")),
+            other => panic!("expected a checksum mismatch, got {:?}", other),
+        }
+
+        assert_eq!(fs::read(&destination).unwrap(), b"old database");
+        assert!(!updater.checksum_path().exists());
+        assert!(staged_files(&directory).is_empty());
+
+        let _ = fs::remove_dir_all(&directory);
+    }
+
+    #[tokio::test]
+    async fn skips_unchanged_database() {
+        let server = Server::start().await;
+        let new = city_database("192.0.2.0/24", "Hamburg");
+        let archive = archive(&[
+            ("COPYRIGHT.txt", b"copyright"),
+            ("GeoLite2-City.mmdb", &new),
+        ]);
+        server.publish("db.tar.gz", &archive, None);
+
+        let directory = directory("unchanged");
+        let destination = directory.join("GeoLite2-City.mmdb");
+        let updater = Updater::new(&format!("{}/db.tar.gz", server.url), &destination);
+
+        assert_eq!(updater.update().await.unwrap(), UpdateStatus::Updated);
+        assert_eq!(fs::read(&destination).unwrap(), new);
+        assert_eq!(updater.update().await.unwrap(), UpdateStatus::Unchanged);
+
+        assert_eq!(server.requests_for("/db.tar.gz"), 1);
+        assert_eq!(server.requests_for("/db.tar.gz.sha256"), 2);
+
+        let _ = fs::remove_dir_all(&directory);
+    }
+
+    #[tokio::test]
+    async fn installs_atomically() {
+        let server = Server::start().await;
+        let first = city_database("192.0.2.0/24", "Frankfurt");
+        server.publish(
+            "db.tar.gz",
+            &archive(&[("GeoLite2-City.mmdb", &first)]),
+            None,
+        );
+
+        let directory = directory("atomic");
+        let destination = directory.join("GeoLite2-City.mmdb");
+        fs::write(&destination, b"old database").unwrap();
+        let updater = Updater::new(&format!("{}/db.tar.gz", server.url), &destination);
+
+        assert_eq!(updater.update().await.unwrap(), UpdateStatus::Updated);
+        assert_eq!(fs::read(&destination).unwrap(), first);
+
+        // A republished archive replaces the installed file without leaving the staged copy.
+        let database = Database::open(&destination).unwrap();
+        let second = city_database("192.0.2.0/24", "Hamburg");
+        let archive = archive(&[("GeoLite2-City.mmdb", &second)]);
+        server.publish("db.tar.gz", &archive, None);
+
+        assert_eq!(
+            updater.update_database(&database).await.unwrap(),
+            UpdateStatus::Updated
+        );
+        assert_eq!(fs::read(&destination).unwrap(), second);
+        assert_eq!(
+            fs::read_to_string(updater.checksum_path()).unwrap(),
+            hex(&Sha256::digest(&archive))
+        );
+        assert!(staged_files(&directory).is_empty());
+
+        let city = database.lookup("192.0.2.7".parse().unwrap()).unwrap().city;
+        assert_eq!(city, "Hamburg");
+
+        let _ = fs::remove_dir_all(&directory);
+    }
+
+    #[tokio::test]
+    async fn keeps_database_when_download_isnt_one() {
+        let server = Server::start().await;
+        let directory = directory("invalid");
+        let destination = directory.join("GeoLite2-City.mmdb");
+        let old = city_database("192.0.2.0/24", "Frankfurt");
+        fs::write(&destination, &old).unwrap();
+        let database = Database::open(&destination).unwrap();
+        let updater = Updater::new(&format!("{}/db.tar.gz", server.url), &destination);
+        fs::write(updater.checksum_path(), "previous").unwrap();
+
+        let broken = archive(&[("GeoLite2-City.mmdb", b"<html>Not found</html>")]);
+        server.publish("db.tar.gz", &broken, None);
+
+        match updater.update_database(&database).await {
+            Err(GeoError::DatabaseError(error)) => assert!(error.contains("isn't a valid")),
+            other => panic!("expected an invalid database, got {:?}", other),
+        }
+
+        assert_eq!(fs::read(&destination).unwrap(), old);
+        assert_eq!(
+            fs::read_to_string(updater.checksum_path()).unwrap(),
+            "previous"
+        );
+        assert!(staged_files(&directory).is_empty());
+        let city = database.lookup("192.0.2.7".parse().unwrap()).unwrap().city;
+        assert_eq!(city, "Frankfurt");
+
+        // The failed update isn't recorded, so it's retried once the archive is fixed.
+        let fixed = archive(&[(
+            "GeoLite2-City.mmdb",
+            &city_database("192.0.2.0/24", "Hamburg"),
+        )]);
+        server.publish("db.tar.gz", &fixed, None);
+
+        assert_eq!(
+            updater.update_database(&database).await.unwrap(),
+            UpdateStatus::Updated
+        );
+        let city = database.lookup("192.0.2.7".parse().unwrap()).unwrap().city;
+        assert_eq!(city, "Hamburg");
+
+        let _ = fs::remove_dir_all(&directory);
+    }
+
+    #[tokio::test]
+    async fn refuses_plain_http_when_https_only() {
+        let server = Server::start().await;
+        let new = city_database("192.0.2.0/24", "Hamburg");
+        server.publish("db.tar.gz", &archive(&[("GeoLite2-City.mmdb", &new)]), None);
+
+        let directory = directory("https-only");
+        let destination = directory.join("GeoLite2-City.mmdb");
+        let updater = Updater::new(&format!("{}/db.tar.gz", server.url), &destination)
+            .transport(TransportPolicy::new().https_only(true));
+
+        match updater.update().await {
+            Err(GeoError::InsecureTransport(error)) => assert!(error.contains("plain HTTP")),
+            other => panic!("expected an insecure transport error, got {:?}", other),
+        }
+
+        assert!(server.requests.lock().unwrap().is_empty());
+        assert!(!destination.exists());
+
+        let _ = fs::remove_dir_all(&directory);
+    }
+
+    #[test]
+    fn extracts_named_csv_entry() {
+        let archive = archive(&[
+            ("GeoLite2-City-Blocks-IPv4.csv", b"ipv4"),
+            ("GeoLite2-City-Blocks-IPv6.csv", b"ipv6"),
+            ("GeoLite2-City-Locations-en.csv", b"locations"),
+        ]);
+
+        match extract(&archive, None) {
+            Err(GeoError::DatabaseError(error)) => assert!(error.contains("several .csv files")),
+            other => panic!("expected an ambiguous archive, got {:?}", other),
+        }
+
+        let contents = extract(&archive, Some("GeoLite2-City-Blocks-IPv6.csv")).unwrap();
+        assert_eq!(contents, b"ipv6");
+
+        assert!(extract(&archive, Some("GeoLite2-City.mmdb")).is_err());
+    }
+
+    #[test]
+    fn refuses_to_reload_csv() {
+        let updater = Updater::new(
+            "http://127.0.0.1/db.tar.gz",
+            "GeoLite2-City-Blocks-IPv4.csv",
+        );
+        assert!(updater.check_loadable().is_err());
+
+        let updater = Updater::new("http://127.0.0.1/db.tar.gz", "blocks.dat")
+            .entry("GeoLite2-City-Blocks-IPv4.csv");
+        assert!(updater.check_loadable().is_err());
+
+        let updater = Updater::new("http://127.0.0.1/db.tar.gz", "GeoLite2-City.mmdb");
+        assert!(updater.check_loadable().is_ok());
+    }
+}